@@ -0,0 +1,306 @@
+// Values read after an option's name
+#[derive(Clone, Copy, PartialEq)]
+pub enum Arity {
+    Flag,
+    One,
+    Two,
+    Color // One rgb value, or red, green, and blue values
+}
+
+pub struct OptionSpec {
+    pub short: &'static str,
+    pub long: &'static str,
+    pub arity: Arity,
+    pub help: &'static str
+}
+
+// Command line options in the order of the help message
+pub const OPTIONS: &[OptionSpec] = &[
+    OptionSpec{short: "", long: "assemble", arity: Arity::One,
+        help: "<Rom path>  Assembles the source file's mnemonics into a rom and exits"},
+    OptionSpec{short: "", long: "autofire", arity: Arity::One,
+        help: "<Chip8 keys separated by commas>  Pulses those keys on and off while they're held"},
+    OptionSpec{short: "", long: "autofirerate", arity: Arity::One,
+        help: "<Pulses per second from 1 to 30>    (default: 15)"},
+    OptionSpec{short: "", long: "backend", arity: Arity::One,
+        help: "<interp | threaded>                 (default: interp)"},
+    OptionSpec{short: "bg", long: "background", arity: Arity::Color,
+        help: "<RGB color> | <Red> <Green> <Blue>  (default: 0, 0, 0)"},
+    OptionSpec{short: "c", long: "clock", arity: Arity::One,
+        help: "<Cycles per second> | max | cosmac  (default: 500 hz)"},
+    OptionSpec{short: "", long: "compare", arity: Arity::One,
+        help: "<Quirks separated by commas>  Runs the rom beside itself with those quirks flipped"},
+    OptionSpec{short: "", long: "confirmquit", arity: Arity::Flag, help: "Asks before quitting without a save state in the last 5 minutes"},
+    OptionSpec{short: "", long: "coverage", arity: Arity::One,
+        help: "<Map path>  Records how ram is executed, read, and written, and writes the map on exit"},
+    OptionSpec{short: "", long: "debug", arity: Arity::Flag, help: "Starts paused with the console debugger"},
+    OptionSpec{short: "", long: "disassemble", arity: Arity::Flag, help: "Prints the rom's instructions and exits"},
+    OptionSpec{short: "", long: "display", arity: Arity::One, help: "<Display number>  Opens the window and fullscreen on a display, counting from 1"},
+    OptionSpec{short: "", long: "dump-audio", arity: Arity::One,
+        help: "<Wav path>  Records the beeps over the emulated time, and writes them on exit"},
+    OptionSpec{short: "", long: "dump-frames", arity: Arity::One,
+        help: "<Directory>  Writes every frame as a numbered png headlessly until -frames, and exits"},
+    OptionSpec{short: "", long: "escape", arity: Arity::One,
+        help: "<menu | quit | fullscreen>          (default: menu)"},
+    OptionSpec{short: "", long: "filter", arity: Arity::One,
+        help: "<none | scanlines | crt>            (default: none)"},
+    OptionSpec{short: "fg", long: "foreground", arity: Arity::Color,
+        help: "<RGB color> | <Red> <Green> <Blue>  (default: 255, 255, 255)"},
+    OptionSpec{short: "", long: "frames", arity: Arity::One, help: "<Count>  Frames that -dump-frames writes"},
+    OptionSpec{short: "", long: "frontend", arity: Arity::One,
+        help: "<sdl | tui>                         (default: sdl)"},
+    OptionSpec{short: "fs", long: "fullscreen", arity: Arity::One,
+        help: "<off | borderless | exclusive>      (default: off)"},
+    OptionSpec{short: "", long: "gdb", arity: Arity::One, help: "<Port>  Listens for gdb remote debugging on localhost"},
+    OptionSpec{short: "h", long: "help", arity: Arity::Flag, help: "Displays this help message"},
+    OptionSpec{short: "", long: "hooks", arity: Arity::One, help: "<Hook file path>  Runs hooks on frames, instructions, and memory"},
+    OptionSpec{short: "", long: "host", arity: Arity::One, help: "<Port>  Shares the machine with a remote player over netplay"},
+    OptionSpec{short: "", long: "inputdelay", arity: Arity::One,
+        help: "<Frames of lockstep input delay>    (default: 2)"},
+    OptionSpec{short: "", long: "join", arity: Arity::One, help: "<Address:Port>  Plays a machine hosted over netplay"},
+    OptionSpec{short: "", long: "keypress", arity: Arity::Flag, help: "Loads FX0A's key when it's pressed instead of released"},
+    OptionSpec{short: "", long: "lockstep", arity: Arity::Flag, help: "Runs netplay on both machines with a shared seed"},
+    OptionSpec{short: "", long: "movie", arity: Arity::One,
+        help: "<Movie path>  Records inputs and the random seed to a movie file"},
+    OptionSpec{short: "", long: "nodrawsync", arity: Arity::Flag, help: "Turns off the 60hz draw sync"},
+    OptionSpec{short: "", long: "noyregshift", arity: Arity::Flag, help: "Shifts from the x register"},
+    OptionSpec{short: "", long: "novsync", arity: Arity::Flag, help: "Turns off vertical sync"},
+    OptionSpec{short: "", long: "onerror", arity: Arity::One,
+        help: "<terminate | debug | skip>          (default: terminate)"},
+    OptionSpec{short: "p", long: "profile", arity: Arity::One,
+        help: "<chip8 | vip | schip | xochip>      (default: chip8)"},
+    OptionSpec{short: "", long: "profile-out", arity: Arity::One,
+        help: "<Json path>  Counts executed instructions and writes the hotspots on exit"},
+    OptionSpec{short: "", long: "palette", arity: Arity::One,
+        help: "<default | octo | gameboy | amber> | <Hex colors separated by commas>"},
+    OptionSpec{short: "", long: "permissive", arity: Arity::Flag, help: "Lets programs run and write below 0x200"},
+    OptionSpec{short: "", long: "phosphor", arity: Arity::Flag, help: "Fades erased pixels like a CRT to hide flicker"},
+    OptionSpec{short: "", long: "playback", arity: Arity::One,
+        help: "<Movie path>  Replays a movie's inputs with its random seed"},
+    OptionSpec{short: "", long: "record", arity: Arity::Flag, help: "Records an animated png until F9 or exit"},
+    OptionSpec{short: "", long: "record-video", arity: Arity::One,
+        help: "<Video path>  Records the display and beeps through ffmpeg, and writes the video on exit"},
+    OptionSpec{short: "", long: "reserved", arity: Arity::One,
+        help: "<Bytes at the end of ram>           (default: 0x160 for chip8, 0 otherwise)"},
+    OptionSpec{short: "", long: "rnglog", arity: Arity::One,
+        help: "<Log path>  Logs every CXKK roll with its address and frame, and writes the log on exit"},
+    OptionSpec{short: "", long: "rngreplay", arity: Arity::One,
+        help: "<Log path>  Replays a log's CXKK rolls, and reports where the program stops matching it"},
+    OptionSpec{short: "", long: "rom2", arity: Arity::One, help: "<Rom path>  Runs a second machine beside the first with numpad keys"},
+    OptionSpec{short: "", long: "romicon", arity: Arity::Flag, help: "Shows the rom's screen as the window icon a second after it starts"},
+    OptionSpec{short: "", long: "scaling", arity: Arity::One,
+        help: "<integer | fit | stretch>           (default: integer)"},
+    OptionSpec{short: "sf", long: "scalefactor", arity: Arity::One, help: "<Scale factor of the profile's screen>"},
+    OptionSpec{short: "", long: "selftest", arity: Arity::One,
+        help: "<Suite directory>  Runs Timendus's test roms headlessly under every profile, and exits"},
+    OptionSpec{short: "", long: "smooth", arity: Arity::Flag, help: "Smooths pixels when scaling by a fraction"},
+    OptionSpec{short: "", long: "stack", arity: Arity::One,
+        help: "<Subroutine levels> | unlimited     (default: 16)"},
+    OptionSpec{short: "", long: "start", arity: Arity::One,
+        help: "<Load and start address>            (default: 0x200)"},
+    OptionSpec{short: "", long: "symbols", arity: Arity::One,
+        help: "<Symbol file path>  Names addresses in the debugger, instead of the .sym file beside the rom"},
+    OptionSpec{short: "", long: "test", arity: Arity::One,
+        help: "<Manifest path>  Runs the manifest's rom tests headlessly and exits"},
+    OptionSpec{short: "", long: "tone", arity: Arity::One,
+        help: "<Buzzer frequency in hz>            (default: 261.63 hz)"},
+    OptionSpec{short: "", long: "twoplayer", arity: Arity::Flag, help: "Lets a second player press the same keys on the numpad"},
+    OptionSpec{short: "", long: "unfocused", arity: Arity::One,
+        help: "<run | throttle | pause>            (default: run)"},
+    OptionSpec{short: "", long: "volume", arity: Arity::One,
+        help: "<0 to 100>                          (default: 100)"},
+    OptionSpec{short: "", long: "wave", arity: Arity::One,
+        help: "<square | triangle | sine | noise>  (default: square)"},
+    OptionSpec{short: "w", long: "windowsize", arity: Arity::Two, help: "<Pixel width> <Pixel height>"},
+    OptionSpec{short: "", long: "watch", arity: Arity::Flag, help: "Reloads the rom when its file changes"},
+];
+
+// Options by their long names and their values, followed by the arguments that aren't options
+pub struct Arguments {
+    pub options: Vec<(&'static str, Vec<String>)>,
+    pub positionals: Vec<String>
+}
+
+impl Arguments {
+    pub fn has(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| *option == name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        let (_, values) = self.options.iter().find(|(option, _)| *option == name)?;
+        values.first().map(String::as_str)
+    }
+}
+
+// Parses "-name value", "--name value", and "--name=value" options, where "--" ends the options
+// Numeric values may also follow the name directly, like "-c500"
+pub fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Arguments, &'static str> {
+    let mut arguments = Arguments{options: Vec::new(), positionals: Vec::new()};
+    let mut args = args.collect::<Vec<String>>();
+
+    // "selftest <Suite directory>" is the -selftest option written as a subcommand
+    if args.first().is_some_and(|arg| arg == "selftest") { args[0] = String::from("-selftest"); }
+    let mut args = args.into_iter();
+    let mut is_options_ended = false;
+
+    while let Some(arg) = args.next() {
+        // A lone dash is a path for standard input
+        if is_options_ended || !arg.starts_with('-') || arg == "-" {
+            arguments.positionals.push(arg);
+            continue
+        }
+        if arg == "--" {
+            is_options_ended = true;
+            continue
+        }
+
+        let body = arg.strip_prefix("--").unwrap_or(&arg[1..]);
+        let (name, mut inline_value) = match body.split_once('=') {
+            Some((name, value)) => (name, Some(String::from(value))),
+            None => (body, None)
+        };
+
+        // Finds the option, or an option with a number attached to its name
+        let spec = match find_option(name) {
+            Some(spec) => spec,
+            None => {
+                let trimmed = name.trim_end_matches(|character: char| character.is_ascii_digit());
+                match find_option(trimmed) {
+                    Some(spec) if spec.arity != Arity::Flag && trimmed != name && inline_value.is_none() => {
+                        inline_value = Some(String::from(&name[trimmed.len()..]));
+                        spec
+                    }
+                    _ => {
+                        match suggest_option(name) {
+                            Some(suggestion) => println!("Unknown option \"{arg}\". Did you mean \"-{suggestion}\"?"),
+                            None => println!("Unknown option \"{arg}\".")
+                        }
+                        return Err("Unknown command line option!")
+                    }
+                }
+            }
+        };
+
+        if arguments.has(spec.long) {
+            println!("Option \"-{}\" is given more than once.", spec.long);
+            return Err("Command line options can only be given once!")
+        }
+
+        // Reads values from after the equals sign separated by commas, or from the following arguments
+        let values: Vec<String> = match inline_value {
+            Some(value) => match spec.arity {
+                Arity::Flag => {
+                    println!("Option \"-{}\" doesn't take a value.", spec.long);
+                    return Err("Invalid command line option!")
+                }
+                Arity::One => vec![value],
+                Arity::Two | Arity::Color => value.split(',').map(String::from).collect()
+            }
+            None => {
+                let count = match spec.arity {
+                    Arity::Flag => 0,
+                    Arity::One => 1,
+                    Arity::Two => 2,
+
+                    // Reads red, green, and blue when the value after the first is also a number
+                    Arity::Color => match args.clone().nth(1).is_some_and(|value| value.parse::<u32>().is_ok()) {
+                        true => 3,
+                        false => 1
+                    }
+                };
+                args.by_ref().take(count).collect()
+            }
+        };
+
+        let is_valid_count = match spec.arity {
+            Arity::Flag => values.is_empty(),
+            Arity::One => values.len() == 1,
+            Arity::Two => values.len() == 2,
+            Arity::Color => values.len() == 1 || values.len() == 3
+        };
+        if !is_valid_count {
+            println!("Option \"-{}\" expects {}", spec.long, spec.help);
+            return Err("Command line option is missing a value!")
+        }
+
+        arguments.options.push((spec.long, values));
+    }
+    Ok(arguments)
+}
+
+// Generates the help message from the option table
+pub fn help() -> String {
+    let mut help = String::from("chip8-emulator <Rom path> <Options>\nchip8-emulator selftest <Suite directory> <Options>\nOptions:\n");
+    for spec in OPTIONS {
+        let short = match spec.short {
+            "" => String::new(),
+            short => format!("-{short}")
+        };
+        help += &format!("    {short:<4}{:<15}{}\n", format!("-{}", spec.long), spec.help);
+    }
+    help
+}
+
+fn find_option(name: &str) -> Option<&'static OptionSpec> {
+    OPTIONS.iter().find(|spec| spec.long == name || (!spec.short.is_empty() && spec.short == name))
+}
+
+// Suggests the long name closest to a misspelled option
+fn suggest_option(name: &str) -> Option<&'static str> {
+    OPTIONS.iter()
+        .map(|spec| (spec.long, edit_distance(name, spec.long)))
+        .filter(|&(long, distance)| distance <= 2 || (name.len() >= 2 && long.starts_with(name)))
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(long, _)| long)
+}
+
+// Levenshtein distance between two option names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_character) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b_character) in b.iter().enumerate() {
+            let substitution = previous[j] + (a_character != b_character) as usize;
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Arguments, &'static str> {
+        parse_args(args.iter().map(|arg| String::from(*arg)))
+    }
+
+    #[test]
+    fn parses_option_forms() {
+        let arguments = parse(&["game.ch8", "-c500", "--volume=50", "-w", "640", "320", "-fg", "255", "128", "0", "-bg", "navy",
+            "--", "-rom"]).unwrap();
+        assert_eq!(arguments.value("clock"), Some("500"));
+        assert_eq!(arguments.value("volume"), Some("50"));
+        assert_eq!(arguments.options.iter().find(|(name, _)| *name == "windowsize").map(|(_, values)| values.len()), Some(2));
+        assert_eq!(arguments.options.iter().find(|(name, _)| *name == "foreground").map(|(_, values)| values.len()), Some(3));
+        assert_eq!(arguments.value("background"), Some("navy"));
+        assert_eq!(arguments.positionals, ["game.ch8", "-rom"]);
+    }
+
+    #[test]
+    fn parses_selftest_subcommand() {
+        let arguments = parse(&["selftest", "suite"]).unwrap();
+        assert_eq!(arguments.value("selftest"), Some("suite"));
+        assert!(arguments.positionals.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        assert!(parse(&["-clok", "500"]).is_err());
+        assert!(parse(&["-help=yes"]).is_err());
+        assert!(parse(&["-c", "500", "--clock", "600"]).is_err());
+        assert!(parse(&["-w", "640"]).is_err());
+    }
+}
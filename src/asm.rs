@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use crate::{chip8::Profile, disasm::{Fields, OpcodeForm, OPCODE_FORMS}, error::Chip8Error, symbols::Symbols};
+
+// Assembles the disassembler's mnemonics into a rom loaded at the start address
+// Lines hold an optional "label:", then an instruction or DB bytes and DW words, and comments start with a semicolon
+// Numbers are decimal, or hexadecimal and binary with 0x and 0b, and addresses may be labels
+// Returns the rom with its labels as symbols
+pub fn assemble(source: &str, profile: Profile, start: u16) -> Result<(Vec<u8>, Symbols), Chip8Error> {
+    // Finds the labels' addresses first, sizing instructions as if labels defined later were at 0
+    let mut labels = HashMap::new();
+    let mut symbols = Symbols::default();
+    let mut sizes = Vec::new();
+    let mut address = start as usize;
+    for (line_index, line) in source.lines().enumerate() {
+        let error = |message| Chip8Error::Assembly{line: line_index + 1, message};
+        let (label, words) = split_line(line).map_err(error)?;
+        if let Some(label) = label && labels.insert(label.to_ascii_lowercase(), address).is_some() {
+            return Err(error("Label is defined more than once!"))
+        }
+        if let Some(label) = label { symbols.insert(address as u16, label); }
+        let size = encode(&words, profile, &labels, false).map_err(error)?.len();
+        sizes.push(size);
+        address += size;
+    }
+
+    // Encodes with every label known, where a label can't change the size of an instruction
+    let mut rom = Vec::new();
+    for ((line_index, line), size) in source.lines().enumerate().zip(sizes) {
+        let error = |message| Chip8Error::Assembly{line: line_index + 1, message};
+        let (_, words) = split_line(line).map_err(error)?;
+        let bytes = encode(&words, profile, &labels, true).map_err(error)?;
+        if bytes.len() != size { return Err(error("A label defined later doesn't fit the instruction!")) }
+        rom.extend(bytes);
+    }
+    Ok((rom, symbols))
+}
+
+// Splits a line into its label and words, where commas separate words like spaces
+fn split_line(line: &str) -> Result<(Option<&str>, Vec<&str>), &'static str> {
+    let code = line.split(';').next().unwrap_or("").trim();
+    let (label, code) = match code.split_once(':') {
+        Some((label, code)) => {
+            let label = label.trim();
+            let is_name = label.starts_with(|character: char| character.is_ascii_alphabetic() || character == '_')
+                && label.chars().all(|character| character.is_ascii_alphanumeric() || character == '_');
+            if !is_name || parse_register(label).is_some() { return Err("Invalid label name!") }
+            (Some(label), code)
+        }
+        None => (None, code)
+    };
+    Ok((label, code.split(|character: char| character == ',' || character.is_whitespace()).filter(|word| !word.is_empty()).collect()))
+}
+
+// Encodes data or the first instruction form the words fit, resolving unknown labels as 0 until every label is known
+fn encode(words: &[&str], profile: Profile, labels: &HashMap<String, usize>, is_final: bool) -> Result<Vec<u8>, &'static str> {
+    let Some(name) = words.first() else { return Ok(Vec::new()) };
+    let value = |word: &str| match parse_number(word) {
+        Some(number) => Ok(number),
+        None => match labels.get(&word.to_ascii_lowercase()) {
+            Some(&address) => Ok(address as u32),
+            None if is_final => Err("Unknown label!"),
+            None => Ok(0)
+        }
+    };
+
+    if name.eq_ignore_ascii_case("DB") || name.eq_ignore_ascii_case("DW") {
+        if words.len() < 2 { return Err("Expected data after DB or DW!") }
+        let mut bytes = Vec::new();
+        for word in &words[1..] {
+            let number = value(word)?;
+            match name.eq_ignore_ascii_case("DB") {
+                true if number <= 0xFF => bytes.push(number as u8),
+                false if number <= 0xFFFF => bytes.extend((number as u16).to_be_bytes()),
+                _ => return Err("Data is out of range!")
+            }
+        }
+        return Ok(bytes)
+    }
+
+    // Tries every form of the mnemonic, so "LD I" falls back to XO-CHIP's long load for addresses above 0xFFF
+    let mut error = "Unknown instruction!";
+    for form in OPCODE_FORMS {
+        match encode_form(form, words, &value) {
+            None => continue,
+            Some(Err(message)) => {
+                error = message;
+                continue
+            }
+            Some(Ok(_)) if !form.set.is_supported(profile) => error = "The instruction isn't supported by the profile!",
+            Some(Ok(bytes)) => return Ok(bytes)
+        }
+    }
+    Err(error)
+}
+
+// Encodes the words as a form, or returns None when the words don't have the form's mnemonic and operands
+fn encode_form(form: &OpcodeForm, words: &[&str], value: &impl Fn(&str) -> Result<u32, &'static str>)
+    -> Option<Result<Vec<u8>, &'static str>> {
+    let pattern: Vec<&str> = form.pattern.split([' ', ',']).filter(|word| !word.is_empty()).collect();
+    if pattern.len() != words.len() { return None }
+
+    // Places each operand in its nibbles, then reads them back to find operands that don't fit
+    let mut opcode = form.opcode as u32;
+    let mut operands = Vec::new();
+    let mut long_address = None;
+    for (&expected, &word) in pattern.iter().zip(words) {
+        let (operand, shift) = match expected {
+            "Vx" => (parse_register(word)?, 8),
+            "Vy" => (parse_register(word)?, 4),
+            "x" => (parse_number(word)?, 8),
+            "n" => (parse_number(word)?, 0),
+            "kk" => (parse_number(word)?, 0),
+            "nnn" => match value(word) {
+                Ok(address) => (address, 0),
+                Err(message) => return Some(Err(message))
+            }
+            "long" => {
+                match value(word) {
+                    Ok(address) if address <= 0xFFFF => long_address = Some(address as u16),
+                    Ok(_) => return Some(Err("Operand is out of range!")),
+                    Err(message) => return Some(Err(message))
+                }
+                continue
+            }
+            _ if expected.eq_ignore_ascii_case(word) => continue,
+            _ => return None
+        };
+        opcode |= operand << shift;
+        operands.push((expected, operand));
+    }
+
+    let fields = Fields::of(opcode as u16);
+    let is_fitting = opcode <= 0xFFFF && operands.iter().all(|&(expected, operand)| operand == match expected {
+        "Vx" | "x" => fields.x,
+        "Vy" => fields.y,
+        "n" => fields.n,
+        "kk" => fields.kk,
+        _ => fields.nnn
+    } as u32);
+    if !is_fitting { return Some(Err("Operand is out of range!")) }
+
+    let mut bytes = (opcode as u16).to_be_bytes().to_vec();
+    if let Some(address) = long_address { bytes.extend(address.to_be_bytes()); }
+    Some(Ok(bytes))
+}
+
+// Reads V0 to VF
+fn parse_register(word: &str) -> Option<u32> {
+    let digit = word.strip_prefix(['V', 'v'])?;
+    match digit.len() {
+        1 => u32::from_str_radix(digit, 16).ok(),
+        _ => None
+    }
+}
+
+fn parse_number(word: &str) -> Option<u32> {
+    let lowercase = word.to_ascii_lowercase();
+    match (lowercase.strip_prefix("0x"), lowercase.strip_prefix("0b")) {
+        (Some(digits), _) => u32::from_str_radix(digits, 16).ok(),
+        (_, Some(digits)) => u32::from_str_radix(digits, 2).ok(),
+        _ => lowercase.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::disassemble_rom;
+
+    #[test]
+    fn assembles_labels_and_data() {
+        let source = "start:\n    LD V0, 0x12 ; Comment\n    JP end\n    DB 1, 0b10\nend: DW 0xABCD\n    LD I, start";
+        let (rom, symbols) = assemble(source, Profile::Chip8, 0x200).unwrap();
+        assert_eq!(rom, [0x60, 0x12, 0x12, 0x06, 0x01, 0x02, 0xAB, 0xCD, 0xA2, 0x00]);
+        assert_eq!(symbols.address("end"), Some(0x206));
+    }
+
+    #[test]
+    fn reports_errors_by_line() {
+        let line = |source| match assemble(source, Profile::Chip8, 0x200) {
+            Err(Chip8Error::Assembly{line, ..}) => Some(line),
+            _ => None
+        };
+        assert_eq!(line("CLS\nLD V0, missing"), Some(2));
+        assert_eq!(line("a:\na: CLS"), Some(2));
+        assert_eq!(line("V1: CLS"), Some(1));
+    }
+
+    // A disassembly of every opcode assembles back to the same bytes, apart from the addresses and bytes column
+    #[test]
+    fn reassembles_disassembly() {
+        for profile in [Profile::Chip8, Profile::Schip, Profile::XoChip] {
+            let rom: Vec<u8> = (0..=u16::MAX).step_by(7).flat_map(u16::to_be_bytes).collect();
+            let listing = disassemble_rom(&rom, profile, 0x200, &Symbols::default());
+            let source: String = listing.lines().map(|line| line[line.find("  ").unwrap() + 2..].trim_start()[8..].trim().to_string() + "\n")
+                .collect();
+            assert_eq!(assemble(&source, profile, 0x200).unwrap().0, rom);
+        }
+    }
+}
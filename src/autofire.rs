@@ -0,0 +1,52 @@
+use crate::NANOS_IN_SECOND;
+
+// Pulses held autofire keys on and off, so programs polling EX9E see a press on every pulse
+// The pulse follows emulated time, so it pauses with the machine and speeds up with fast forward
+pub struct Autofire {
+    keys: [bool; 16],              // Chip8 keys that pulse while held
+    half_period_nanos: u64,        // Time the key spends on, then off
+    held_nanos: [Option<u64>; 16], // Emulated time each autofire key has been held
+    is_on: [bool; 16]              // Whether the machine currently sees each autofire key pressed
+}
+
+impl Autofire {
+    pub fn new(keys: [bool; 16], rate_hz: u32) -> Autofire {
+        Autofire{keys, half_period_nanos: NANOS_IN_SECOND / 2 / rate_hz.max(1) as u64, held_nanos: [None; 16], is_on: [false; 16]}
+    }
+
+    // Tracks a host press or release, returning whether it should reach the machine now
+    // Autofire keys start on when pressed, and their release only matters while they're on
+    pub fn press(&mut self, key: usize, is_pressed: bool) -> bool {
+        if !self.keys[key] { return true }
+        match is_pressed {
+            true => {
+                if self.held_nanos[key].is_some() { return false }
+                self.held_nanos[key] = Some(0);
+                self.is_on[key] = true;
+                true
+            }
+            false => {
+                self.held_nanos[key] = None;
+                std::mem::replace(&mut self.is_on[key], false)
+            }
+        }
+    }
+
+    // Whether an autofire key is held, whose pulse needs every frame
+    pub fn is_pulsing(&self) -> bool {
+        self.held_nanos.iter().any(Option::is_some)
+    }
+
+    // Advances the held keys by the emulated time, passing the keys whose pulse flipped to set_key
+    pub fn update(&mut self, delta_nanos: u64, set_key: &mut impl FnMut(usize, bool)) {
+        for key in 0..self.keys.len() {
+            let Some(held_nanos) = &mut self.held_nanos[key] else { continue };
+            *held_nanos += delta_nanos;
+            let is_on = (*held_nanos / self.half_period_nanos).is_multiple_of(2);
+            if is_on != self.is_on[key] {
+                self.is_on[key] = is_on;
+                set_key(key, is_on);
+            }
+        }
+    }
+}
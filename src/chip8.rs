@@ -1,18 +1,29 @@
 // Namespace imports
 use rand::{rngs::SmallRng, RngCore, SeedableRng};
-use std::sync::{Arc, atomic::{AtomicI32, Ordering}};
-use crate::{config::Chip8Configuration};
+use std::{collections::VecDeque, fmt, ops::{Deref, DerefMut, Range}};
+#[cfg(feature = "threaded")]
+use crate::threaded::{compile, Block, MAX_BLOCK_LENGTH};
+use crate::{cosmac::{instruction_cycles, DISPLAY_CYCLES, MACHINE_CYCLE_NANOS, SKIP_CYCLES}, decode::{decode, Instruction}, error::Chip8Error, expression::Expression, state::{StateReader, StateWriter}};
 
 extern crate rand;
 
 // Constants
 const CLOCK_DELTA: f32 = 1000000000.0 / 60.0;
-const FLAGS_REGISTER: usize = 0xF;
-pub const FRAME_BUFFER_WIDTH: u16 = 64;
+pub const DEFAULT_START_ADDRESS: u16 = 0x200; // Where the original interpreter loads and starts programs
+pub const DEFAULT_STACK_DEPTH: usize = 16; // Subroutine levels of the original interpreters
+pub const MAX_CLOCK_HZ: u32 = 10000000; // Keeps a cycle longer than f32's precision in a long fast forwarded frame
+pub(crate) const FLAGS_REGISTER: usize = 0xF;
+pub const FRAME_BUFFER_WIDTH: u16 = 64;  // Resolution of the original chip8 display
 pub const FRAME_BUFFER_HEIGHT: u16 = 32;
 pub const FRAME_BUFFER_SIZE: usize = FRAME_BUFFER_WIDTH as usize * FRAME_BUFFER_HEIGHT as usize;
-const MAX_RAM_ADDRESS: u16 = 0x1000 - 0x160; // Last 0x160 bytes are reserved
 const SPRITE_WIDTH: u8 = 8;
+const HIRES_FONT_ADDRESS: u16 = 0x270; // End of the page HiRes programs give their patched interpreter, before the program at 0x2C0
+const STATE_MAGIC: &[u8; 4] = b"C8ST";
+const STATE_VERSION: u16 = 5;
+const OLD_STACK_SIZE: usize = 12; // Stack entries in states before version 5
+pub const COVERAGE_EXECUTED: u8 = 1; // Bits of each ram byte's coverage flags
+pub const COVERAGE_READ: u8 = 2;
+pub const COVERAGE_WRITTEN: u8 = 4;
 
 // Pixel data for numerical font in the chip8 interpreter
 const FONTS: [u8; 0x50] = [
@@ -34,469 +45,1922 @@ const FONTS: [u8; 0x50] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80, // 0xF
 ];
 
+// Behaviors which differ between chip8 interpreters
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quirks {
+    pub is_drawsync: bool,        // DRW waits for the next 60hz tick
+    pub is_shift_quirk: bool,     // SHR and SHL shift from the y register
+    pub is_vf_reset: bool,        // OR, AND, and XOR reset the flags register
+    pub is_index_increment: bool, // FX55 and FX65 increment the index register
+    pub is_sprite_clipping: bool, // DRW clips sprites at the screen edge instead of wrapping them
+    pub is_jump_quirk: bool,      // BXNN jumps to XNN + register x instead of NNN + register 0
+    pub is_key_release: bool,     // FX0A loads a key when it's released instead of when it's pressed
+    pub is_key_tone: bool,        // FX0A beeps while a key is held like the VIP
+}
+
+// Named bundles of quirks, memory, display, and clock speed for common interpreters
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Profile {
+    Chip8,
+    Vip, // The original chip8 at the COSMAC VIP's own speed
+    Schip,
+    XoChip
+}
+
+impl Profile {
+    pub fn parse(name: &str) -> Option<Profile> {
+        match name {
+            "chip8" => Some(Profile::Chip8),
+            "vip" => Some(Profile::Vip),
+            "schip" => Some(Profile::Schip),
+            "xochip" => Some(Profile::XoChip),
+            _ => None
+        }
+    }
+
+    // The name parse() reads
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Chip8 => "chip8",
+            Profile::Vip => "vip",
+            Profile::Schip => "schip",
+            Profile::XoChip => "xochip"
+        }
+    }
+
+    pub fn quirks(self) -> Quirks {
+        match self {
+            Profile::Chip8 => Quirks{is_drawsync: true, is_shift_quirk: true, is_vf_reset: true,
+                is_index_increment: true, is_sprite_clipping: true, is_jump_quirk: false, is_key_release: true, is_key_tone: false},
+            Profile::Vip => Quirks{is_key_tone: true, ..Profile::Chip8.quirks()},
+            Profile::Schip => Quirks{is_drawsync: false, is_shift_quirk: false, is_vf_reset: false,
+                is_index_increment: false, is_sprite_clipping: true, is_jump_quirk: true, is_key_release: true, is_key_tone: false},
+            Profile::XoChip => Quirks{is_drawsync: false, is_shift_quirk: true, is_vf_reset: false,
+                is_index_increment: true, is_sprite_clipping: false, is_jump_quirk: false, is_key_release: true, is_key_tone: false},
+        }
+    }
+
+    // Ram in bytes, including any reserved by the interpreter
+    pub fn memory_size(self) -> usize {
+        match self {
+            Profile::Chip8 | Profile::Vip | Profile::Schip => 0x1000,
+            Profile::XoChip => 0x10000
+        }
+    }
+
+    // Bytes at the end of ram that programs can't use, where the original interpreter keeps its stack and display there
+    pub fn reserved_size(self) -> usize {
+        match self {
+            Profile::Chip8 | Profile::Vip => 0x160,
+            Profile::Schip | Profile::XoChip => 0
+        }
+    }
+
+    // Resolution of the highest resolution display mode the machine can draw, where SCHIP and XO-CHIP stay at 64x32
+    // until their 128x64 instructions are emulated
+    pub fn display_size(self) -> (u32, u32) {
+        match self {
+            Profile::Chip8 | Profile::Vip | Profile::Schip | Profile::XoChip => DisplayMode::LowRes.size()
+        }
+    }
+
+    // Instructions a second, where the VIP profile counts machine cycles instead
+    pub fn clock_hz(self) -> u32 {
+        match self {
+            Profile::Chip8 | Profile::Vip => 500,
+            Profile::Schip => 1800,
+            Profile::XoChip => 60000
+        }
+    }
+}
+
+// Resolutions the display can switch between while a program runs
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DisplayMode {
+    LowRes,   // 64x32 of the original chip8
+    HiRes,    // 64x64 of two page HiRes programs
+    SuperRes, // 128x64 of SCHIP and XO-CHIP's high resolution mode
+    MegaRes   // 256x192 of MegaChip
+}
+
+impl DisplayMode {
+    pub fn size(self) -> (u32, u32) {
+        match self {
+            DisplayMode::LowRes => (FRAME_BUFFER_WIDTH as u32, FRAME_BUFFER_HEIGHT as u32),
+            DisplayMode::HiRes => (64, 64),
+            DisplayMode::SuperRes => (128, 64),
+            DisplayMode::MegaRes => (256, 192)
+        }
+    }
+}
+
+// ARGB8888 colors which frontends give the frame buffer's palette indices
+// 0 is the background, 1 is the foreground, and 2 and 3 color XO-CHIP's second plane
+pub type Palette = [u32; 16];
+
+// Returns a named palette, where entries past the first four repeat the foreground
+pub fn named_palette(name: &str) -> Option<Palette> {
+    let colors = match name {
+        "default" => [0x000000, 0xFFFFFF, 0xAAAAAA, 0x555555],
+        "octo" => [0x996600, 0xFFCC00, 0xFF6600, 0x662200],
+        "gameboy" => [0x9BBC0F, 0x0F380F, 0x306230, 0x8BAC0F],
+        "amber" => [0x1A0F00, 0xFFB000, 0xCC8400, 0x664200],
+        _ => return None
+    };
+
+    let mut palette = [0xFF000000 | colors[1]; 16];
+    for (entry, color) in palette.iter_mut().zip(colors) {
+        *entry = 0xFF000000 | color;
+    }
+    Some(palette)
+}
+
+// Settings for a chip8 machine independent of the frontend
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chip8Options {
+    pub clock_hz: u32,
+    pub profile: Profile,
+    pub quirks: Quirks,
+    pub is_cosmac_timing: bool, // Runs instructions at the COSMAC VIP's cycle costs instead of clock_hz a second
+    pub stack_depth: Option<usize>, // Subroutine levels before CALL overflows, where None is unlimited
+    pub reserved_size: usize,       // Bytes at the end of the profile's ram that programs can't use
+    pub start_address: u16,         // Where the rom loads and runs from, like 0x600 for ETI-660 programs
+    pub is_permissive_memory: bool, // Lets programs run and write below 0x200, where the interpreter normally lives
+    pub seed: Option<u64> // Seeds the random number generator for deterministic runs
+}
+
+impl Chip8Options {
+    // The profile's defaults with a fixed seed, for headless tools and tests
+    pub fn new(profile: Profile, seed: u64) -> Chip8Options {
+        Chip8Options{clock_hz: profile.clock_hz(), profile, quirks: profile.quirks(), is_cosmac_timing: false,
+            stack_depth: Some(DEFAULT_STACK_DEPTH), reserved_size: profile.reserved_size(), start_address: DEFAULT_START_ADDRESS,
+            is_permissive_memory: false, seed: Some(seed)}
+    }
+}
+
+// Buzzer changes, timed in emulated nanoseconds from the start of the run() call that caused them
+// The time since the machine started also places them on one timeline, for recording the beep
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AudioEvent {
+    Start{offset_nanos: f32, duration_nanos: f32, time_nanos: f64}, // Replaces any playing beep, ending when the sound timer reaches zero
+    Stop{offset_nanos: f32, time_nanos: f64}                        // The sound timer was cleared before it ran out
+}
+
+// Receives buzzer events so a frontend can play the sound timer
+pub trait AudioSink {
+    fn audio_event(&mut self, event: AudioEvent);
+}
+
+// Watches execution for hook files and tools, where hooks read and change the machine through its public methods
+pub trait Observer {
+    fn frame_start(&mut self, _chip8: &mut Chip8) {}                              // Each 60hz tick, after the timers count down
+    fn execute(&mut self, _chip8: &mut Chip8, _address: u16) {}                   // Before the instruction at the address executes
+    fn memory_read(&mut self, _chip8: &mut Chip8, _addresses: Range<usize>) {}  // Before an instruction reads ram
+    fn memory_write(&mut self, _chip8: &mut Chip8, _addresses: Range<usize>) {} // After an instruction writes ram
+}
+
+// Memory accesses that pause the machine after the instruction at a watchpoint
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Watch {
+    Read,
+    Write,
+    Access // Reads or writes
+}
+
+// The watched access that paused the machine, reported until it resumes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WatchHit {
+    pub address: u16,
+    pub is_write: bool,
+    pub pc: u16 // Address of the instruction that accessed memory
+}
+
+// A CXKK roll with the instruction and 60hz frame it happened on, which a later session can replay
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomRoll {
+    pub frame: u64,
+    pub pc: u16,
+    pub byte: u8,  // Generated before the instruction's mask, so a replay gives the same result for any mask
+    pub result: u8
+}
+
+// The first replayed roll taken by a different instruction than the one that logged it
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomDivergence {
+    pub expected: RandomRoll,
+    pub pc: u16,
+    pub frame: u64
+}
+
+// Result of executing a single instruction
+#[derive(Clone, Copy, PartialEq)]
+pub enum Step {
+    Executed,
+    Drew,         // Lets the caller wait for the next vertical blank
+    WaitingForKey, // The program counter stays on FX0A until a key is released, or pressed without the release quirk
+    Exited         // The program counter stays on SCHIP's 00FD until the machine is reset
+}
+
+// How instructions are executed, where the threaded backend caches register only basic blocks as chains of closures in builds
+// with the threaded feature
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    Interpreter,
+    #[cfg(feature = "threaded")]
+    Threaded
+}
+
+// The general registers V0 to VF, which index like an array, where VF is also the flag that arithmetic and draws set
+#[derive(Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Registers(pub [u8; 16]);
+
+impl Registers {
+    pub fn flag(&self) -> u8 {
+        self.0[FLAGS_REGISTER]
+    }
+}
+
+impl Deref for Registers {
+    type Target = [u8; 16];
+
+    fn deref(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl DerefMut for Registers {
+    fn deref_mut(&mut self) -> &mut [u8; 16] {
+        &mut self.0
+    }
+}
+
+// Lists the registers by name, like "V0=00 V1=3F", instead of as an array of decimal numbers
+impl fmt::Debug for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<String> = self.0.iter().enumerate().map(|(register, value)| format!("V{register:X}={value:02X}")).collect();
+        write!(f, "{}", names.join(" "))
+    }
+}
+
+// The architectural state at one moment, which tests compare whole after executing handcrafted instructions
+// Ram and the frame buffer are left to memory() and frame_buffer(), since they're too large to print on a mismatch
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    pub registers: Registers,
+    pub index: u16,
+    pub pc: u16,
+    pub stack: Vec<u16>,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub display_mode: DisplayMode,
+    pub is_exited: bool
+}
+
+// A machine state to return to, which unlike a save state also restores the random number generator, instruction
+// count, and random log and replay positions, so instructions executed again from it take the same path
+pub struct Checkpoint {
+    state: Vec<u8>,
+    random_generator: SmallRng,
+    instruction_count: u64,
+    random_log_length: usize,
+    random_replay: VecDeque<RandomRoll>,
+    random_divergence: Option<RandomDivergence>,
+    edit_count: u64
+}
+
 // The chip8 state which can be initialized and ran
 pub struct Chip8 {
-    ram: [u8; MAX_RAM_ADDRESS as usize],
-    pub frame_buffer: [u32; FRAME_BUFFER_SIZE],
-    stack: [u16; 12],
-    pub keyboard: [bool; 16],
-    pub key_released: [bool; 16],
-
-    general_registers: [u8; 16],
+    ram: Vec<u8>,
+    decode_cache: Vec<Option<Instruction>>, // Decoded instruction at each address, cleared where ram is written
+    backend: Backend,
+    #[cfg(feature = "threaded")]
+    blocks: Vec<Option<Block>>, // Compiled block starting at each address, cleared when ram under it is written
+    frame_buffer: Vec<u8>, // Palette index of each pixel, which instructions draw into
+    front_buffer: Vec<u8>, // The frame buffer as of the last 60hz tick, which frontends show
+    display_mode: DisplayMode,
+    is_display_mode_changed: bool, // Lets the frontend resize its texture once per change
+    dirty_rows: Range<u16>,        // Rows drawn since the last tick, where empty means unchanged
+    front_dirty_rows: Range<u16>,  // Rows of the front buffer changed since the frontend last uploaded them
+    stack: Vec<u16>, // Return addresses of the subroutines being run, innermost last
+    stack_depth: Option<usize>,
+    start_address: u16,
+    rom_size: usize, // Bytes of the loaded rom, which tools compare to how ram was used
+    is_permissive_memory: bool,
+    keyboard: [bool; 16],
+    key_released: [bool; 16],
+    key_pressed: [bool; 16],
+
+    general_registers: Registers,
+    rpl_flags: [u8; 16], // SCHIP's user flags in HP48 memory, which persist across resets
     program_counter: u16,
     index_register: u16,
-    stack_pointer: u8,
 
     delay_timer: u8,
-    pub remaining_samples: Arc<AtomicI32>,
+    sound_timer: u8,
+    audio_sink: Option<Box<dyn AudioSink>>,
+    observer: Option<Box<dyn Observer>>,
+    run_nanos: f32, // Emulated time since the start of the current run() call
 
     cycle_hz: u32,
-    cycle_buffer: f32,
+    is_cosmac_timing: bool,
+    cycle_buffer: f32, // Nanoseconds not yet run, which COSMAC timing lets go negative when an instruction overruns the frame
     clock_buffer: f32,
 
-    pub background_color: u32,
-    foreground_color: u32,
-    is_drawsync: bool,
-    is_shift_quirk: bool,
+    profile: Profile,
+    quirks: Quirks,
     random_generator: SmallRng,
+    instruction_count: u64, // Executed instructions, which frontends sample to measure speed
+    address_counts: Vec<u64>, // Executed instructions by address and by opcode, which are empty unless profiling
+    opcode_counts: Vec<u64>,
+    coverage: Vec<u8>, // How each ram byte was used, which is empty unless tracking coverage
+    frame_count: u64,
+    random_log: Option<Vec<RandomRoll>>,
+    random_replay: VecDeque<RandomRoll>, // Rolls taken by CXKK instead of the generator until they run out
+    random_divergence: Option<RandomDivergence>,
+    edit_count: u64, // Changes made from outside of execution, after which older checkpoints no longer lead here
+    is_replaying: bool, // Executing again from a checkpoint, which leaves out observers, profiling, and audio
+
+    breakpoints: Vec<u16>,
+    breakpoint_conditions: Vec<(u16, Expression)>, // Breakpoints with a condition only pause when it's true
+    return_depth: Option<usize>, // Pauses once the stack is this shallow, for stepping over or out of subroutines
+    watchpoints: Vec<(u16, Watch)>,
+    watch_hit: Option<WatchHit>,
+    is_paused: bool,
+    is_resuming: bool,
+    is_exited: bool
 }
 
 impl Chip8 {
-    pub fn init(config: &Chip8Configuration) -> Result<Chip8, &'static str> {
-        // Reads rom from file
-        let rom = match std::fs::read(&config.rom_path) {
-            Ok(file) => file,
-            Err(_) => return Err("Path to the rom is invalid!")
+    pub fn init(rom: &[u8], config: &Chip8Options) -> Result<Chip8, Chip8Error> {
+        // Initializes non cryptographic random number generator, from the seed if one is given
+        let rng = match config.seed {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => os_rng()
         };
 
+        // Initializes registers and memory to zero, and program counter to the start, leaving out the reserved end of ram
+        let memory_size = config.profile.memory_size().saturating_sub(config.reserved_size).max(0x200);
+        let mut chip8 = Chip8 {ram: vec![0; memory_size], decode_cache: vec![None; memory_size],
+            backend: Backend::Interpreter, #[cfg(feature = "threaded")] blocks: (0..memory_size).map(|_| None).collect(), frame_buffer: vec![0; FRAME_BUFFER_SIZE],
+            front_buffer: vec![0; FRAME_BUFFER_SIZE], display_mode: DisplayMode::LowRes, is_display_mode_changed: false, dirty_rows: 0..0,
+            front_dirty_rows: 0..0,
+            stack: Vec::new(), stack_depth: config.stack_depth,
+            start_address: config.start_address, rom_size: 0, is_permissive_memory: config.is_permissive_memory, keyboard: [false; 16], key_released: [false; 16],
+            key_pressed: [false; 16],
+            general_registers: Registers::default(), rpl_flags: [0; 16], program_counter: config.start_address, index_register: 0, delay_timer: 0,
+            sound_timer: 0, audio_sink: None, observer: None, run_nanos: 0.0, cycle_hz: config.clock_hz.clamp(1, MAX_CLOCK_HZ),
+            is_cosmac_timing: config.is_cosmac_timing || config.profile == Profile::Vip, cycle_buffer: 0.0, clock_buffer: 0.0,
+            profile: config.profile, quirks: config.quirks,
+            random_generator: rng, instruction_count: 0, address_counts: Vec::new(), opcode_counts: Vec::new(), coverage: Vec::new(),
+            frame_count: 0, random_log: None, random_replay: VecDeque::new(), random_divergence: None,
+            edit_count: 0, is_replaying: false,
+            breakpoints: Vec::new(), breakpoint_conditions: Vec::new(), return_depth: None,
+            watchpoints: Vec::new(), watch_hit: None, is_paused: false, is_resuming: false, is_exited: false};
+
+        // The first rom's display mode is the starting resolution rather than a change
+        chip8.load_rom(rom)?;
+        chip8.is_display_mode_changed = false;
+        Ok(chip8)
+    }
+
+    // Copies a rom into cleared ram and resets, leaving the machine untouched on failure
+    pub fn load_rom(&mut self, rom: &[u8]) -> Result<(), Chip8Error> {
         // Copies font data and rom into ram
-        if rom.len() > MAX_RAM_ADDRESS as usize - 0x200 {
-            return Err("The rom is too large for the ram!")
+        let start = self.start_address as usize;
+        let capacity = self.ram.len().saturating_sub(start);
+        if rom.len() > capacity {
+            return Err(Chip8Error::RomTooLarge{size: rom.len(), capacity})
         }
 
-        let mut ram = [0; MAX_RAM_ADDRESS as usize];
-        ram[..FONTS.len()].clone_from_slice(&FONTS);
-        ram[0x200..0x200 + rom.len()].clone_from_slice(&rom);
-
-        // Initializes non cryptographic random number generator
-        let rng = SmallRng::from_os_rng();
+        self.ram.fill(0);
+        self.ram[..FONTS.len()].clone_from_slice(&FONTS);
+        self.ram[start..start + rom.len()].clone_from_slice(rom);
+        self.rom_size = rom.len();
+        self.clear_code_caches();
+
+        // Two page HiRes programs start by jumping to 0x260, where the VIP's patched interpreter took over
+        // Their chip8 code starts at 0x2C0 and draws to a 64x64 display, with the font in the interpreter's page
+        let is_hires = matches!(self.profile, Profile::Chip8 | Profile::Vip) && start == 0x200 && rom.starts_with(&[0x12, 0x60]);
+        if is_hires {
+            self.ram[0x200..0x202].copy_from_slice(&[0x12, 0xC0]);
+            self.ram[HIRES_FONT_ADDRESS as usize..][..FONTS.len()].copy_from_slice(&FONTS);
+        }
+        self.set_display_mode(match is_hires {
+            true => DisplayMode::HiRes,
+            false => DisplayMode::LowRes
+        });
+        self.reset();
+        Ok(())
+    }
 
-        // Initializes registers and memory to zero, and program counter to 0x200
-        Ok(Chip8 {ram, frame_buffer: [0; FRAME_BUFFER_SIZE], stack: [0; 12], keyboard: [false; 16], key_released: [false; 16],
-            general_registers: [0; 16], program_counter: 0x200, index_register: 0, stack_pointer: 0, delay_timer: 0,
-            remaining_samples: Arc::new(AtomicI32::new(0)), cycle_hz: config.clock_hz, cycle_buffer: 0.0, clock_buffer: 0.0,
-            background_color: config.background_color, foreground_color: config.foreground_color, is_drawsync: config.is_drawsync,
-            is_shift_quirk: config.is_shift_quirk, random_generator: rng})
+    // Clears the registers, stack, timers, and display, then restarts at the start address without touching ram
+    pub fn reset(&mut self) {
+        self.edit_count += 1;
+        self.frame_buffer.fill(0);
+        self.mark_all_dirty();
+        self.stack.clear();
+        self.general_registers.fill(0);
+        self.program_counter = self.start_address;
+        self.index_register = 0;
+        self.delay_timer = 0;
+        self.set_sound_timer(0);
+        self.cycle_buffer = 0.0;
+        self.clock_buffer = 0.0;
+        self.is_exited = false;
+        self.latch_frame();
     }
 
-    pub fn run(&mut self, delta: f32) -> Option<&'static str> {
+    // Emulates delta nanoseconds, where timers tick every 60th of an emulated second however often it's called
+    // Frontends pass the time since their last frame, so a 144hz display runs shorter frames rather than faster timers
+    pub fn run(&mut self, delta: f32) -> Option<Chip8Error> {
+        // Time doesn't pass while paused
+        if self.is_paused { return None }
+
         // Runs cycle_hz instructions a second and 60 ticks per second
         self.cycle_buffer += delta;
+        self.run_nanos = 0.0;
+        if self.is_cosmac_timing { return self.run_cosmac() }
         let cycle_delta = 1000000000.0 / self.cycle_hz as f32;
-        'cycle_loop: while self.cycle_buffer >= cycle_delta {
-            // Terminates if the program counter is out of range or unaligned
-            if self.program_counter < 0x200 || self.program_counter >= MAX_RAM_ADDRESS - 1 {
-                return Some("Invalid program counter address!")
+        while self.cycle_buffer >= cycle_delta {
+            #[cfg(feature = "threaded")]
+            if self.backend == Backend::Threaded && self.run_block(cycle_delta) { continue }
+
+            if self.should_pause() {
+                self.is_paused = true;
+                self.cycle_buffer = 0.0;
+                break
             }
+            self.is_resuming = false;
 
             // https://www.gafferongames.com/post/fix_your_timestep/
             while self.clock_buffer >= CLOCK_DELTA {
                 // Decrements timers at the end of a cycle
                 self.clock_buffer -= CLOCK_DELTA;
-                if self.delay_timer > 0 { self.delay_timer -= 1; }
+                self.tick_timers();
             }
 
             self.cycle_buffer -= cycle_delta;
             self.clock_buffer += cycle_delta;
+            self.run_nanos += cycle_delta;
 
-            // Parses opcode for its values
-            let opcode = &self.ram[self.program_counter as usize..self.program_counter as usize + 2];
-            let (op0, op1, op2, op3) = (opcode[0] >> 4, opcode[0] & 0xF, opcode[1] >> 4, opcode[1] & 0xF);
-            let (x, y, n) = (op1, op2, op3);
-            let kk = opcode[1];
-            let nnn = u16::from_be_bytes([opcode[0], opcode[1]]) & 0xFFF;
-
-            // Parses rom instructions
-            match op0 {
-                0x0 => match nnn {
-                    // opcode CLS - clears the display
-                    0x0E0 => {
-                        for pixel in self.frame_buffer.iter_mut() {
-                            *pixel = self.background_color;
-                        }
+            match self.step() {
+                Ok(Step::Executed) => (),
+
+                // Waits until next vertical blank
+                Ok(Step::Drew) => if self.quirks.is_drawsync {
+                    let old_buffer = self.clock_buffer;
+                    self.clock_buffer = CLOCK_DELTA + old_buffer % cycle_delta;
+                    self.cycle_buffer -= self.clock_buffer - old_buffer;
+                    self.run_nanos += self.clock_buffer - old_buffer;
+                }
+
+                // Waits and updates buffers if no key is released
+                Ok(Step::WaitingForKey) => {
+                    let old_buffer = self.cycle_buffer;
+                    self.cycle_buffer %= cycle_delta;
+                    self.clock_buffer += old_buffer - self.cycle_buffer;
 
-                        self.program_counter += 2;
+                    while self.clock_buffer >= CLOCK_DELTA {
+                        self.clock_buffer -= CLOCK_DELTA;
+                        self.tick_timers();
                     }
+                    break
+                }
 
-                    // opcode RET - returns from subroutine
-                    0x0EE => {
-                        if self.stack_pointer as usize == 0 {
-                            return Some("Stack underflow on function return!")
-                        }
-                        self.stack_pointer -= 1;
-                        self.program_counter = self.stack[self.stack_pointer as usize];
-                    },
-                    
-                    // opcode SYS addr - jumps to machine code runtime (ignored by modern interpreters)
-                    _ => self.program_counter += 2,
+                // Stops running instructions after the program exits
+                Ok(Step::Exited) => {
+                    self.cycle_buffer = 0.0;
+                    break
                 }
+                Err(message) => return Some(message)
+            }
 
-                // opcode JP addr - jumps to address nnn
-                0x1 => self.program_counter = nnn,
+            // Stops after the instruction when an observer paused the machine
+            if self.is_paused {
+                self.cycle_buffer = 0.0;
+                break
+            }
+        }
 
-                // opcode CALL Vx, byte - calls subroutine at nnn
-                0x2 => {
-                    if self.stack_pointer as usize >= self.stack.len() {
-                        return Some("Stack overflow on function call!")
-                    }
-                    self.stack[self.stack_pointer as usize] = self.program_counter + 2;
-                    self.stack_pointer += 1;
-                    self.program_counter = nnn;
-                },
-
-                // opcode SE Vx, byte - skips instruction if register x == kk
-                0x3 => {
-                    if self.general_registers[x as usize] == kk { self.program_counter += 4; }
-                    else { self.program_counter += 2; }
+        // Keeps track of the previous keyboard state to know when a key is pressed or released
+        self.key_released.fill(false);
+        self.key_pressed.fill(false);
+        None
+    }
+
+    // Runs instructions for their COSMAC VIP machine cycles, where the display's DMA takes its share of every 60hz frame
+    // With drawsync, a draw waits for the vertical interrupt before drawing like the VIP, instead of after
+    fn run_cosmac(&mut self) -> Option<Chip8Error> {
+        while self.cycle_buffer > 0.0 {
+            if self.should_pause() {
+                self.is_paused = true;
+                self.cycle_buffer = 0.0;
+                break
+            }
+            self.is_resuming = false;
+
+            let address = self.program_counter as usize;
+            let opcode = match self.ram.get(address..address + 2) {
+                Some(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]),
+                None => 0
+            };
+            let instruction = decode(opcode, self.profile);
+            if self.quirks.is_drawsync && matches!(instruction, Instruction::Drw{..}) { self.wait_for_interrupt(); }
+
+            let mut cycles = instruction_cycles(instruction, &self.general_registers, self.display_mode.size().1);
+            let step = self.step();
+            let is_skip = matches!(instruction, Instruction::SeByte{..} | Instruction::SneByte{..} | Instruction::Se{..}
+                | Instruction::Sne{..} | Instruction::Skp{..} | Instruction::Sknp{..});
+            if is_skip && self.program_counter as usize == address + 4 { cycles += SKIP_CYCLES; }
+            self.advance_cosmac(cycles as f32 * MACHINE_CYCLE_NANOS);
+            match step {
+                Ok(Step::Executed | Step::Drew) => (),
+
+                // Spends the rest of the time waiting, like the interpreter's key loop
+                Ok(Step::WaitingForKey) => {
+                    self.advance_cosmac(self.cycle_buffer.max(0.0));
+                    break
+                }
+                Ok(Step::Exited) => {
+                    self.cycle_buffer = 0.0;
+                    break
+                }
+                Err(message) => return Some(message)
+            }
+
+            if self.is_paused {
+                self.cycle_buffer = 0.0;
+                break
+            }
+        }
+
+        self.key_released.fill(false);
+        self.key_pressed.fill(false);
+        None
+    }
+
+    // Spends the rest of the frame until the vertical interrupt, landing exactly on it despite rounding
+    fn wait_for_interrupt(&mut self) {
+        let wait = CLOCK_DELTA - self.clock_buffer;
+        self.cycle_buffer -= wait;
+        self.run_nanos += wait;
+        self.clock_buffer = CLOCK_DELTA;
+        self.advance_cosmac(0.0);
+    }
+
+    // Spends emulated time, ticking the timers at every vertical interrupt, where the display's DMA stalls the processor
+    fn advance_cosmac(&mut self, nanos: f32) {
+        self.cycle_buffer -= nanos;
+        self.clock_buffer += nanos;
+        self.run_nanos += nanos;
+        while self.clock_buffer >= CLOCK_DELTA {
+            self.clock_buffer -= CLOCK_DELTA;
+            self.tick_timers();
+
+            let display_nanos = DISPLAY_CYCLES as f32 * MACHINE_CYCLE_NANOS;
+            self.cycle_buffer -= display_nanos;
+            self.clock_buffer += display_nanos;
+            self.run_nanos += display_nanos;
+        }
+    }
+
+    // Runs up to max_instructions over delta nanoseconds instead of a fixed clock, spread evenly between the 60hz ticks
+    // Frontends choose the budget from how long emulation takes, so it runs as fast as real time allows
+    // A draw with the drawsync quirk and a key wait both end their tick early, like they do at a fixed clock
+    pub fn run_uncapped(&mut self, delta: f32, max_instructions: u64) -> Option<Chip8Error> {
+        if self.is_paused { return None }
+
+        self.clock_buffer += delta;
+        self.cycle_buffer = 0.0;
+        self.run_nanos = 0.0;
+        let ticks = (self.clock_buffer / CLOCK_DELTA) as u64;
+        let tick_instructions = max_instructions / ticks.max(1);
+        let mut is_stopped = false;
+        for tick in 0..ticks.max(1) {
+            let mut instructions = 0;
+            while !is_stopped && instructions < tick_instructions {
+                if self.should_pause() {
+                    self.is_paused = true;
+                    break
+                }
+                self.is_resuming = false;
+                instructions += 1;
+
+                match self.step() {
+                    Ok(Step::Executed) => (),
+                    Ok(Step::Drew) => if self.quirks.is_drawsync { break },
+                    Ok(Step::WaitingForKey | Step::Exited) => is_stopped = true,
+                    Err(message) => return Some(message)
                 }
+                if self.is_paused { break }
+            }
+
+            // Time stops with the machine, but keeps ticking the timers while it waits for a key or has exited
+            if self.is_paused { break }
+            if tick < ticks {
+                self.clock_buffer -= CLOCK_DELTA;
+                self.run_nanos += CLOCK_DELTA;
+                self.tick_timers();
+            }
+        }
+
+        self.key_released.fill(false);
+        self.key_pressed.fill(false);
+        None
+    }
+
+    // Instructions per second run() executes, which takes effect on the next run
+    pub fn set_clock_hz(&mut self, hz: u32) {
+        self.cycle_hz = hz.clamp(1, MAX_CLOCK_HZ);
+    }
+
+    pub fn clock_hz(&self) -> u32 {
+        self.cycle_hz
+    }
+
+    // Whether instructions take their COSMAC VIP cycle costs, which ignores the clock speed
+    pub fn is_cosmac_timing(&self) -> bool {
+        self.is_cosmac_timing
+    }
+
+    // Runs the compiled block at the program counter with the same timing as interpreting it, returning false when the
+    // interpreter must run the next instruction instead, like when hooks or breakpoints need every instruction
+    #[cfg(feature = "threaded")]
+    fn run_block(&mut self, cycle_delta: f32) -> bool {
+        if self.observer.is_some() || !self.breakpoints.is_empty() || self.return_depth.is_some() || self.is_profiling() || self.is_tracking_coverage() { return false }
+
+        let pc = self.program_counter;
+        if pc < self.lowest_address() || pc as usize >= self.ram.len() - 1 { return false }
+        let (ram, profile, quirks, font_address) = (&self.ram, self.profile, self.quirks, self.font_address());
+        let length = self.blocks[pc as usize].get_or_insert_with(|| compile(ram, pc, profile, quirks, font_address)).length;
+        if length == 0 { return false }
+
+        // Waits for enough cycles to run the whole block, since its instructions can't be split
+        let mut cycle_buffer = self.cycle_buffer;
+        for _ in 0..length {
+            if cycle_buffer < cycle_delta { return false }
+            cycle_buffer -= cycle_delta;
+        }
+        self.cycle_buffer = cycle_buffer;
+
+        // Ticks timers between the instructions like the interpreter, which is safe since blocks don't read them
+        for _ in 0..length {
+            while self.clock_buffer >= CLOCK_DELTA {
+                self.clock_buffer -= CLOCK_DELTA;
+                self.tick_timers();
+            }
+            self.clock_buffer += cycle_delta;
+            self.run_nanos += cycle_delta;
+        }
+
+        self.is_resuming = false;
+        self.instruction_count += length as u64;
+        if let Some(block) = &self.blocks[pc as usize] {
+            self.program_counter = block.execute(&mut self.general_registers, &mut self.index_register);
+        }
+        true
+    }
+
+    // Executes exactly one instruction without advancing the timers
+    pub fn step(&mut self) -> Result<Step, Chip8Error> {
+        // Lets the observer change the machine before the instruction at the program counter executes
+        let address = self.program_counter;
+        self.notify(|observer, chip8| observer.execute(chip8, address));
+
+        // Terminates if the program counter is out of range or unaligned
+        if self.program_counter < self.lowest_address() || self.program_counter as usize >= self.ram.len() - 1 {
+            return Err(Chip8Error::InvalidProgramCounter{address: self.program_counter})
+        }
+        self.instruction_count += 1;
+
+        // Decodes the opcode once, until a write to its address clears the cached instruction
+        let pc = self.program_counter;
+        let opcode = u16::from_be_bytes([self.ram[pc as usize], self.ram[pc as usize + 1]]);
+        let instruction = match self.decode_cache[pc as usize] {
+            Some(instruction) => instruction,
+            None => {
+                let instruction = decode(opcode, self.profile);
+                self.decode_cache[pc as usize] = Some(instruction);
+                instruction
+            }
+        };
+
+        if !self.address_counts.is_empty() && !self.is_replaying {
+            self.address_counts[pc as usize] += 1;
+            self.opcode_counts[opcode as usize] += 1;
+        }
+        if !self.coverage.is_empty() {
+            self.coverage[pc as usize] |= COVERAGE_EXECUTED;
+            self.coverage[pc as usize + 1] |= COVERAGE_EXECUTED;
+        }
+
+        // Executes rom instructions
+        match instruction {
+            // opcode CLS - clears the display
+            Instruction::Cls => {
+                self.frame_buffer.fill(0);
+                self.mark_all_dirty();
+
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode RET - returns from subroutine
+            Instruction::Ret => {
+                self.program_counter = match self.stack.pop() {
+                    Some(address) => address,
+                    None => return Err(Chip8Error::StackUnderflow{address: pc})
+                };
+            },
+            
+            // opcode EXIT - exits the SCHIP interpreter, which frontends see through is_exited()
+            Instruction::Exit => {
+                self.is_exited = true;
+                return Ok(Step::Exited)
+            }
+
+            // opcode SYS addr - jumps to machine code runtime (ignored by modern interpreters)
+            Instruction::Sys => self.program_counter = self.program_counter.wrapping_add(2),
 
-                // opcode SNE Vx, byte - skips instruction if register x != kk
-                0x4 => {
-                    if self.general_registers[x as usize] != kk { self.program_counter += 4; }
-                    else { self.program_counter += 2; }
+            // opcode JP addr - jumps to address nnn
+            Instruction::Jp{nnn} => self.program_counter = nnn,
+
+            // opcode CALL Vx, byte - calls subroutine at nnn
+            Instruction::Call{nnn} => {
+                if self.stack_depth.is_some_and(|depth| self.stack.len() >= depth) {
+                    return Err(Chip8Error::StackOverflow{address: pc})
                 }
+                self.stack.push(self.program_counter.wrapping_add(2));
+                self.program_counter = nnn;
+            },
+
+            // opcode SE Vx, byte - skips instruction if register x == kk
+            Instruction::SeByte{x, kk} => {
+                if self.general_registers[x as usize] == kk { self.program_counter = self.program_counter.wrapping_add(4); }
+                else { self.program_counter = self.program_counter.wrapping_add(2); }
+            }
+
+            // opcode SNE Vx, byte - skips instruction if register x != kk
+            Instruction::SneByte{x, kk} => {
+                if self.general_registers[x as usize] != kk { self.program_counter = self.program_counter.wrapping_add(4); }
+                else { self.program_counter = self.program_counter.wrapping_add(2); }
+            }
+
+            // opcode SE Vx, Vy - skips instruction if register x == register y
+            Instruction::Se{x, y} => {
+                if self.general_registers[x as usize] == self.general_registers[y as usize] {
+                    self.program_counter = self.program_counter.wrapping_add(4);
+                } else { self.program_counter = self.program_counter.wrapping_add(2); }
+            }
+
+            // opcode LD Vx, byte - kk is loaded in register x
+            Instruction::LdByte{x, kk} => {
+                self.general_registers[x as usize] = kk;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode ADD Vx, byte - register x plus kk is loaded in register x
+            Instruction::AddByte{x, kk} => {
+                self.general_registers[x as usize] = self.general_registers[x as usize].wrapping_add(kk);
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
 
-                0x5 => match op3 {
-                    // opcode SE Vx, Vy - skips instruction if register x == register y
-                    0x0 =>{
-                        if self.general_registers[x as usize] == self.general_registers[y as usize] {
-                            self.program_counter += 4;
-                        } else { self.program_counter += 2; }
+            // opcode LD Vx, Vy - registered y is loaded in register x
+            Instruction::Ld{x, y} => {
+                self.general_registers[x as usize] = self.general_registers[y as usize];
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // the following opcodes reset the flags register to 0 with the vf reset quirk
+            // opcode OR Vx, Vy - register x = register x | register y
+            Instruction::Or{x, y} => {
+                self.general_registers[x as usize] |= self.general_registers[y as usize];
+                if self.quirks.is_vf_reset { self.general_registers[FLAGS_REGISTER] = 0; }
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode AND Vx, Vy - register x = register x & register y
+            Instruction::And{x, y} => {
+                self.general_registers[x as usize] &= self.general_registers[y as usize];
+                if self.quirks.is_vf_reset { self.general_registers[FLAGS_REGISTER] = 0; }
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode XOR Vx, Vy - register x = register x ^ register y
+            Instruction::Xor{x, y} => {
+                self.general_registers[x as usize] ^= self.general_registers[y as usize];
+                if self.quirks.is_vf_reset { self.general_registers[FLAGS_REGISTER] = 0; }
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode AND Vx, Vy - register x = register x + register y
+            // sets the flags register to 1 on overflow
+            Instruction::Add{x, y} => {
+                let (result, overflow) = self.general_registers[x as usize].overflowing_add(self.general_registers[y as usize]);
+                self.general_registers[x as usize] = result;
+                self.general_registers[FLAGS_REGISTER] = overflow as u8;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode SUB Vx, Vy - register x = register x - register y
+            // sets the flags register to 0 on overflow
+            Instruction::Sub{x, y} => {
+                let (result, overflow) = self.general_registers[x as usize].overflowing_sub(self.general_registers[y as usize]);
+                self.general_registers[x as usize] = result;
+                self.general_registers[FLAGS_REGISTER] = !overflow as u8;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode SHR Vx, Vy - register x is shifted to the right by one
+            // sets the flags register to 1 when shifting out a 1 bit
+            Instruction::Shr{x, y} => {
+                // Some programs expect the shift instructions to operate from the x register
+                let value = match self.quirks.is_shift_quirk {
+                    true => self.general_registers[y as usize],
+                    false => self.general_registers[x as usize]
+                };
+
+                self.general_registers[x as usize] = value >> 1;
+                self.general_registers[FLAGS_REGISTER] = value & 1;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode SUBN Vx, Vy - register x = register y - register x
+            // sets the flags register to 0 on overflow
+            Instruction::Subn{x, y} => {
+                let (result, overflow) = self.general_registers[y as usize].overflowing_sub(self.general_registers[x as usize]);
+                self.general_registers[x as usize] = result;
+                self.general_registers[FLAGS_REGISTER] = !overflow as u8;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode SHL Vx, Vy - register x is shifted to the left by one
+            // sets the flags register to 1 when shifting out a 1 bit
+            Instruction::Shl{x, y} => {
+                // Some programs expect the shift instructions to operate from the x register
+                let value = match self.quirks.is_shift_quirk {
+                    true => self.general_registers[y as usize],
+                    false => self.general_registers[x as usize]
+                };
+
+                self.general_registers[x as usize] = value << 1;
+                self.general_registers[FLAGS_REGISTER] = value >> 7;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode SNE Vx, Vy - skips instruction if register x != register y
+            Instruction::Sne{x, y} => {
+                if self.general_registers[x as usize] != self.general_registers[y as usize] {
+                    self.program_counter = self.program_counter.wrapping_add(4);
+                } else { self.program_counter = self.program_counter.wrapping_add(2); }
+            }
+
+            // opcode LD I, addr - nnn is loaded in the index register
+            Instruction::LdIndex{nnn} => {
+                self.index_register = nnn;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode JP V0, addr - jumps to address nnn + register 0
+            // some programs expect to jump to address xnn + register x
+            Instruction::JpOffset{x, nnn} => {
+                let offset = match self.quirks.is_jump_quirk {
+                    true => self.general_registers[x as usize],
+                    false => self.general_registers[0]
+                };
+                self.program_counter = nnn + offset as u16;
+            }
+
+            // opcode RND Vx, byte - register x = random byte & register x
+            Instruction::Rnd{x, kk} => {
+                self.general_registers[x as usize] = self.random_byte(pc, kk) & kk;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode DRW Vx, Vy, n - draws n byte sized sprite at the index register
+            // the x position is in the x register and the y position is in the y register
+            // the flag register is set when a old sprite xor'd onto the screen erases another
+            // out of bounds starting coordinates wrap around the screen
+            // sprites partially drawn offscreen are clipped, or wrapped without the clipping quirk
+            // waits for the next vsync on completion
+            Instruction::Drw{x, y, n} => {
+                // A draw doesn't erase a sprite until proven otherwise
+                self.general_registers[FLAGS_REGISTER] = 0;
+
+                // Wraps around the screen if the sprite is drawing offscreen
+                let (width, height) = self.display_mode.size();
+                let (width, height) = (width as u16, height as u16);
+                let x = self.general_registers[x as usize] as u16 % width;
+                let y = self.general_registers[y as usize] as u16 % height;
+
+                // Terminates if the draw is accessing invalid ram, after the observer has had a chance to change the index
+                let sprite = self.index_register as usize..self.index_register as usize + n as usize;
+                self.access_memory(sprite, false);
+                if self.index_register as usize + n as usize > self.ram.len() {
+                    return Err(Chip8Error::InvalidMemoryAccess{opcode, address: pc, index: self.index_register})
+                }
+
+                // Iterates the n rows of the sprite
+                for i in 0..n {
+                    // Caps y at the screen height for vertical screen clipping
+                    if self.quirks.is_sprite_clipping && y + i as u16 >= height { break }
+
+                    // Iterates the 8 columns of the sprite
+                    let mut row_data = self.ram[self.index_register as usize + i as usize];
+                    let row = (y + i as u16) % height;
+                    let row_index = row * width;
+                    self.dirty_rows = match self.dirty_rows.is_empty() {
+                        true => row..row + 1,
+                        false => self.dirty_rows.start.min(row)..self.dirty_rows.end.max(row + 1)
+                    };
+                    for j in 0..SPRITE_WIDTH {
+                        // Caps x at the screen width for horizontal screen clipping
+                        if self.quirks.is_sprite_clipping && x + j as u16 >= width { break }
+
+                        // The row data is a bit field for the pixel data
+                        let is_pixel_set = row_data & 0x80;
+                        row_data <<= 1;
+
+                        // Xor's the sprite with the frame buffer to draw
+                        // Sets the flags register to 1 if another sprite is erased
+                        let pixel_index = row_index + (x + j as u16) % width;
+                        let pixel = &mut self.frame_buffer[pixel_index as usize];
+
+                        if is_pixel_set != 0 {
+                            if *pixel == 1 { self.general_registers[FLAGS_REGISTER] = 1; }
+                            *pixel ^= 1;
+                        }
                     }
-                    _ => return Some("Unsupported opcode!")
-                },
+                }
+
+                self.program_counter = self.program_counter.wrapping_add(2);
+                return Ok(Step::Drew)
+            },
 
-                // opcode LD Vx, byte - kk is loaded in register x
-                0x6 => {
-                    self.general_registers[x as usize] = kk;
-                    self.program_counter += 2;
+            // opcode SKP Vx - skips instruction if the key value in register x is pressed
+            Instruction::Skp{x} => {
+                if self.keyboard[self.general_registers[x as usize] as usize & 0xF] {
+                    self.program_counter = self.program_counter.wrapping_add(4);
+                } else { self.program_counter = self.program_counter.wrapping_add(2); }
+            }
+            
+            // opcode SKP Vx - skips instruction if the key value in register x is not pressed
+            Instruction::Sknp{x} => {
+                if !self.keyboard[self.general_registers[x as usize] as usize & 0xF] {
+                    self.program_counter = self.program_counter.wrapping_add(4);
+                } else { self.program_counter = self.program_counter.wrapping_add(2); }
+            }
+
+            // opcode LD Vx, DT - the delay timer is loaded in register x
+            Instruction::LdDelay{x} => {
+                self.general_registers[x as usize] = self.delay_timer;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+            
+            // opcode LD Vx, K - waits for a key press, then the key is loaded in register x
+            Instruction::LdKey{x} => {
+                // Iterates to find a released key, or a pressed key without the release quirk
+                let events = match self.quirks.is_key_release {
+                    true => &mut self.key_released,
+                    false => &mut self.key_pressed
+                };
+                if let Some(key) = events.iter().position(|&is_event| is_event) {
+                    // Handles the event to avoid repeat detections
+                    events[key] = false;
+
+                    // Returns the key in register x and stops waiting
+                    self.general_registers[x as usize] = key as u8;
+                    self.program_counter = self.program_counter.wrapping_add(2);
+                    return Ok(Step::Executed)
                 }
 
-                // opcode ADD Vx, byte - register x plus kk is loaded in register x
-                0x7 => {
-                    self.general_registers[x as usize] = self.general_registers[x as usize].wrapping_add(kk);
-                    self.program_counter += 2;
+                // Keeps the buzzer sounding while a key is held, like the VIP's keypad routine
+                if self.quirks.is_key_tone && self.keyboard.contains(&true) && self.sound_timer < 2 {
+                    self.set_sound_timer(2);
                 }
 
-                0x8 => match op3 {
-                    // opcode LD Vx, Vy - registered y is loaded in register x
-                    0x0 => {
-                        self.general_registers[x as usize] = self.general_registers[y as usize];
-                        self.program_counter += 2;
-                    }
+                // Waits without advancing the program counter until a key is found
+                return Ok(Step::WaitingForKey)
+            }
 
-                    // the following opcodes reset the flags register to 0 
-                    // opcode OR Vx, Vy - register x = register x | register y
-                    0x1 => {
-                        self.general_registers[x as usize] |= self.general_registers[y as usize];
-                        self.general_registers[FLAGS_REGISTER] = 0;
-                        self.program_counter += 2;
-                    }
+            // opcode LD DT, VX - register x is loaded in the delay timer
+            Instruction::SetDelay{x} => {
+                self.delay_timer = self.general_registers[x as usize];
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
 
-                    // opcode AND Vx, Vy - register x = register x & register y
-                    0x2 => {
-                        self.general_registers[x as usize] &= self.general_registers[y as usize];
-                        self.general_registers[FLAGS_REGISTER] = 0;
-                        self.program_counter += 2;
-                    }
+            // opcode LD ST, Vx - register x is loaded in the sound timer
+            Instruction::SetSound{x} => {
+                self.set_sound_timer(self.general_registers[x as usize]);
 
-                    // opcode XOR Vx, Vy - register x = register x ^ register y
-                    0x3 => {
-                        self.general_registers[x as usize] ^= self.general_registers[y as usize];
-                        self.general_registers[FLAGS_REGISTER] = 0;
-                        self.program_counter += 2;
-                    }
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode ADD I, Vx - index register = index register + register x
+            Instruction::AddIndex{x} => {
+                self.index_register = self.index_register.wrapping_add(self.general_registers[x as usize] as u16);
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
+
+            // opcode LD B, Vx - address of the sprite for the digit in register x is loaded in the index register
+            // index register = font address + register x * 5
+            Instruction::LdFont{x} => {
+                self.index_register = self.font_address() + self.general_registers[x as usize & 0xF] as u16 * 5;
+                self.program_counter = self.program_counter.wrapping_add(2);
+            }
 
-                    // opcode AND Vx, Vy - register x = register x + register y
-                    // sets the flags register to 1 on overflow
-                    0x4 => {
-                        let (result, overflow) = self.general_registers[x as usize].overflowing_add(self.general_registers[y as usize]);
-                        self.general_registers[x as usize] = result;
-                        self.general_registers[FLAGS_REGISTER] = overflow as u8;
-                        self.program_counter += 2;
-                    },
-
-                    // opcode SUB Vx, Vy - register x = register x - register y
-                    // sets the flags register to 0 on overflow
-                    0x5 => {
-                        let (result, overflow) = self.general_registers[x as usize].overflowing_sub(self.general_registers[y as usize]);
-                        self.general_registers[x as usize] = result;
-                        self.general_registers[FLAGS_REGISTER] = !overflow as u8;
-                        self.program_counter += 2;
-                    },
-
-                    // opcode SHR Vx, Vy - register x is shifted to the right by one
-                    // sets the flags register to 1 when shifting out a 1 bit
-                    0x6 => {
-                        // Some programs expect the shift instructions to operate from the x register
-                        let value = match self.is_shift_quirk {
-                            true => self.general_registers[y as usize],
-                            false => self.general_registers[x as usize]
-                        };
-
-                        self.general_registers[x as usize] = value >> 1;
-                        self.general_registers[FLAGS_REGISTER] = value & 1;
-                        self.program_counter += 2;
-                    },
-
-                    // opcode SUBN Vx, Vy - register x = register y - register x
-                    // sets the flags register to 0 on overflow
-                    0x7 => {
-                        let (result, overflow) = self.general_registers[y as usize].overflowing_sub(self.general_registers[x as usize]);
-                        self.general_registers[x as usize] = result;
-                        self.general_registers[FLAGS_REGISTER] = !overflow as u8;
-                        self.program_counter += 2;
-                    },
-
-                    // opcode SHL Vx, Vy - register x is shifted to the left by one
-                    // sets the flags register to 1 when shifting out a 1 bit
-                    0xE => {
-                        // Some programs expect the shift instructions to operate from the x register
-                        let value = match self.is_shift_quirk {
-                            true => self.general_registers[y as usize],
-                            false => self.general_registers[x as usize]
-                        };
-
-                        self.general_registers[x as usize] = value << 1;
-                        self.general_registers[FLAGS_REGISTER] = value >> 7;
-                        self.program_counter += 2;
-                    },
-                    _ => return Some("Unsupported opcode!")
+            // opcode LD F, Vx - the BCD representation of register x is loaded at the index register
+            Instruction::LdBcd{x} => {
+                // Terminates if the BCD store is accessing invalid ram
+                if self.index_register < self.lowest_address() || self.index_register as usize + 2 >= self.ram.len() {
+                    return Err(Chip8Error::InvalidMemoryAccess{opcode, address: pc, index: self.index_register})
                 }
 
-                0x9 => match op3 {
-                    // opcode SNE Vx, Vy - skips instruction if register x != register y
-                    0x0 => {
-                        if self.general_registers[x as usize] != self.general_registers[y as usize] {
-                            self.program_counter += 4;
-                        } else { self.program_counter += 2; }
-                    }
-                    _ => return Some("Unsupported opcode!")
+                self.ram[self.index_register as usize]     = self.general_registers[x as usize] / 100;
+                self.ram[self.index_register as usize + 1] = self.general_registers[x as usize] / 10 % 10;
+                self.ram[self.index_register as usize + 2] = self.general_registers[x as usize] % 10;
+                let digits = self.index_register as usize..self.index_register as usize + 3;
+                self.access_memory(digits, true);
+
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode LD [I], Vx - registers 0 to x are loaded at the index register
+            // index register = index register + x + 1 with the index increment quirk
+            Instruction::Store{x} => {
+                // Terminates if the store is accessing invalid ram
+                let max_ram_access = self.index_register as usize + x as usize;
+                if self.index_register < self.lowest_address() || max_ram_access >= self.ram.len() {
+                    return Err(Chip8Error::InvalidMemoryAccess{opcode, address: pc, index: self.index_register})
                 }
 
-                // opcode LD I, addr - nnn is loaded in the index register
-                0xA => {
-                    self.index_register = nnn;
-                    self.program_counter += 2;
+                let destination = &mut self.ram[self.index_register as usize..max_ram_access + 1];
+                destination.copy_from_slice(&self.general_registers[0..x as usize + 1]);
+                let stored = self.index_register as usize..max_ram_access + 1;
+                self.access_memory(stored, true);
+                if self.quirks.is_index_increment {
+                    self.index_register = self.index_register.wrapping_add(x as u16 + 1);
                 }
 
-                // opcode JP V0, addr - jumps to address nnn + register 0
-                0xB => self.program_counter = nnn + self.general_registers[0] as u16,
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode LD Vx, [I] - memory starting at the index register is loaded in registers 0 to x
+            // index register = index register + x + 1 with the index increment quirk
+            Instruction::Load{x} => {
+                // Terminates if the load is accessing invalid ram, after the observer has had a chance to change the index
+                let loaded = self.index_register as usize..self.index_register as usize + x as usize + 1;
+                self.access_memory(loaded, false);
+                let max_ram_access = self.index_register as usize + x as usize;
+                if max_ram_access >= self.ram.len() {
+                    return Err(Chip8Error::InvalidMemoryAccess{opcode, address: pc, index: self.index_register});
+                }
 
-                // opcode RND Vx, byte - register x = random byte & register x
-                0xC => {
-                    self.general_registers[x as usize] = self.random_generator.next_u64() as u8 & kk;
-                    self.program_counter += 2;
+                let source = &self.ram[self.index_register as usize..max_ram_access + 1];
+                self.general_registers[0..x as usize + 1].copy_from_slice(source);
+                if self.quirks.is_index_increment {
+                    self.index_register = self.index_register.wrapping_add(x as u16 + 1);
                 }
 
-                // opcode DRW Vx, Vy, n - draws n byte sized sprite at the index register
-                // the x position is in the x register and the y position is in the y register
-                // the flag register is set when a old sprite xor'd onto the screen erases another
-                // out of bounds starting coordinates wrap around the screen
-                // sprites partially drawn offscreen are clipped
-                // waits for the next vsync on completion
-                0xD => {
-                    // A draw doesn't erase a sprite until proven otherwise
-                    self.general_registers[FLAGS_REGISTER] = 0;
-
-                    // Wraps around the screen if the sprite is drawing offscreen
-                    let x = self.general_registers[x as usize] % FRAME_BUFFER_WIDTH as u8;
-                    let y = self.general_registers[y as usize] % FRAME_BUFFER_HEIGHT as u8;
-
-                    // Terminates if the draw is accessing invalid ram
-                    if self.index_register + n as u16 > MAX_RAM_ADDRESS {
-                        return Some("Invalid memory access in draw!")
-                    }
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
 
-                    // Iterates the n rows of the sprite
-                    for i in 0..n {
-                        // Caps y at the screen height for vertical screen clipping
-                        if y + i >= FRAME_BUFFER_HEIGHT as u8 { break }
-
-                        // Iterates the 8 columns of the sprite
-                        let mut row_data = self.ram[self.index_register as usize + i as usize];
-                        let row_index = (y + i) as u16 * FRAME_BUFFER_WIDTH;
-                        for j in 0..SPRITE_WIDTH {
-                            // Caps x at the screen width for horizontal screen clipping
-                            if x + j >= FRAME_BUFFER_WIDTH as u8 { break }
-
-                            // The row data is a bit field for the pixel data
-                            let is_pixel_set = row_data & 0x80;
-                            row_data <<= 1;
-
-                            // Xor's the sprite with the frame buffer to draw
-                            // Sets the flags register to 1 if another sprite is erased
-                            let pixel_index = row_index + (x + j) as u16;
-                            let pixel = &mut self.frame_buffer[pixel_index as usize];
-
-                            if is_pixel_set != 0 { match *pixel == self.foreground_color {
-                                false => *pixel = self.foreground_color,
-                                true => {
-                                    self.general_registers[FLAGS_REGISTER] = 1;
-                                    *pixel = self.background_color;
-                                }
-                            }}
-                        }
-                    }
+            // opcode LD R, Vx - registers 0 to x are saved in the RPL user flags, where SCHIP only has 8
+            Instruction::SaveFlags{x} => {
+                if self.profile == Profile::Schip && x >= 8 {
+                    return Err(Chip8Error::RplFlagOverflow{opcode, address: pc})
+                }
+                self.rpl_flags[0..x as usize + 1].copy_from_slice(&self.general_registers[0..x as usize + 1]);
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+
+            // opcode LD Vx, R - registers 0 to x are loaded from the RPL user flags
+            Instruction::LoadFlags{x} => {
+                if self.profile == Profile::Schip && x >= 8 {
+                    return Err(Chip8Error::RplFlagOverflow{opcode, address: pc})
+                }
+                self.general_registers[0..x as usize + 1].copy_from_slice(&self.rpl_flags[0..x as usize + 1]);
+                self.program_counter = self.program_counter.wrapping_add(2);
+            },
+            Instruction::Unsupported => return Err(Chip8Error::UnsupportedOpcode{opcode, address: pc})
+        }
 
-                    // Waits until next vertical blank
-                    if self.is_drawsync {
-                        let old_buffer = self.clock_buffer;
-                        self.clock_buffer = CLOCK_DELTA + old_buffer % cycle_delta;
-                        self.cycle_buffer -= self.clock_buffer - old_buffer;
-                    }
+        Ok(Step::Executed)
+    }
 
-                    self.program_counter += 2;
-                },
+    // Executes up to count instructions without advancing the timers, stopping early at an error or SCHIP's exit
+    // Returns the last instruction's step, which is Executed for a count of zero
+    pub fn step_n(&mut self, count: u32) -> Result<Step, Chip8Error> {
+        let mut step = Step::Executed;
+        for _ in 0..count {
+            step = self.step()?;
+            if step == Step::Exited { break }
+        }
+        Ok(step)
+    }
 
-                0xE => match kk {
-                    // opcode SKP Vx - skips instruction if the key value in register x is pressed
-                    0x9E => {
-                        if self.keyboard[self.general_registers[x as usize] as usize & 0xF] {
-                            self.program_counter += 4;
-                        } else { self.program_counter += 2; }
-                    }
-                    
-                    // opcode SKP Vx - skips instruction if the key value in register x is not pressed
-                    0xA1 => {
-                        if !self.keyboard[self.general_registers[x as usize] as usize & 0xF] {
-                            self.program_counter += 4;
-                        } else { self.program_counter += 2; }
-                    }
-                    _ => return Some("Unsupported opcode!")
+    // Decrements the timers, which frontends driving step() should call at 60hz
+    pub fn tick_timers(&mut self) {
+        self.latch_frame();
+        if self.delay_timer > 0 { self.delay_timer -= 1; }
+        if self.sound_timer > 0 { self.sound_timer -= 1; }
+        self.frame_count += 1;
+        self.notify(|observer, chip8| observer.frame_start(chip8));
+    }
+
+    // Stops run() from executing until resumed
+    pub fn pause(&mut self) {
+        self.is_paused = true;
+    }
+
+    // Continues execution without stopping at a breakpoint on the current instruction, and clears the watch hit
+    pub fn resume(&mut self) {
+        self.is_paused = false;
+        self.is_resuming = true;
+        self.watch_hit = None;
+        self.return_depth = None;
+    }
+
+    // Resumes until the stack is no deeper than the depth, where the current depth steps over a CALL and one less
+    // steps out of the current subroutine, though breakpoints and watchpoints still pause first
+    pub fn resume_until_return(&mut self, depth: usize) {
+        self.resume();
+        self.return_depth = Some(depth);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    // Whether the program exited with 00FD since the last reset
+    pub fn is_exited(&self) -> bool {
+        self.is_exited
+    }
+
+    // Whether the program counter is on FX0A, where the program waits until a key
+    pub fn is_waiting_for_key(&self) -> bool {
+        let pc = self.program_counter as usize;
+        matches!(self.ram.get(pc..pc + 2), Some(&[high, 0x0A]) if high & 0xF0 == 0xF0)
+    }
+
+    // Replaces any condition of a breakpoint at the address
+    pub fn set_breakpoint(&mut self, address: u16) {
+        if !self.breakpoints.contains(&address) { self.breakpoints.push(address); }
+        self.breakpoint_conditions.retain(|(other, _)| *other != address);
+    }
+
+    // Sets a breakpoint that only pauses when the condition is true
+    pub fn set_conditional_breakpoint(&mut self, address: u16, condition: Expression) {
+        self.set_breakpoint(address);
+        self.breakpoint_conditions.push((address, condition));
+    }
+
+    // Returns false if there was no breakpoint at the address
+    pub fn remove_breakpoint(&mut self, address: u16) -> bool {
+        let old_length = self.breakpoints.len();
+        self.breakpoints.retain(|&breakpoint| breakpoint != address);
+        self.breakpoint_conditions.retain(|(other, _)| *other != address);
+        self.breakpoints.len() != old_length
+    }
+
+    pub fn breakpoints(&self) -> &[u16] {
+        &self.breakpoints
+    }
+
+    pub fn breakpoint_condition(&self, address: u16) -> Option<&Expression> {
+        self.breakpoint_conditions.iter().find(|(other, _)| *other == address).map(|(_, condition)| condition)
+    }
+
+    // Pauses before executing an instruction at a breakpoint whose condition holds, or once a subroutine returns to the
+    // depth being run until, unless resuming from the instruction
+    fn should_pause(&self) -> bool {
+        let is_breakpoint = self.breakpoints.contains(&self.program_counter)
+            && self.breakpoint_condition(self.program_counter).is_none_or(|condition| condition.is_true(self));
+        !self.is_resuming && (is_breakpoint || self.return_depth.is_some_and(|depth| self.stack.len() <= depth))
+    }
+
+    // Replaces any watchpoint at the address
+    pub fn set_watchpoint(&mut self, address: u16, watch: Watch) {
+        self.remove_watchpoint(address);
+        self.watchpoints.push((address, watch));
+    }
+
+    // Returns false if there was no watchpoint at the address
+    pub fn remove_watchpoint(&mut self, address: u16) -> bool {
+        let old_length = self.watchpoints.len();
+        self.watchpoints.retain(|&(watchpoint, _)| watchpoint != address);
+        self.watchpoints.len() != old_length
+    }
+
+    pub fn watchpoints(&self) -> &[(u16, Watch)] {
+        &self.watchpoints
+    }
+
+    pub fn watch_hit(&self) -> Option<WatchHit> {
+        self.watch_hit
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.program_counter
+    }
+
+    pub fn set_pc(&mut self, address: u16) {
+        self.edit_count += 1;
+        self.program_counter = address;
+    }
+
+    pub fn index(&self) -> u16 {
+        self.index_register
+    }
+
+    pub fn set_index(&mut self, address: u16) {
+        self.edit_count += 1;
+        self.index_register = address;
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.general_registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        self.edit_count += 1;
+        &mut self.general_registers
+    }
+
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.edit_count += 1;
+        self.general_registers = registers;
+    }
+
+    // RPL user flags, which frontends can persist so saved high scores survive restarts
+    pub fn rpl_flags(&self) -> &[u8; 16] {
+        &self.rpl_flags
+    }
+
+    pub fn set_rpl_flags(&mut self, flags: [u8; 16]) {
+        self.rpl_flags = flags;
+    }
+
+    // Return addresses of the active subroutine calls, from the oldest to the newest
+    pub fn stack_frames(&self) -> &[u16] {
+        &self.stack
+    }
+
+    // Replaces the return addresses, innermost last, which can't be deeper than the stack depth
+    pub fn set_stack_frames(&mut self, frames: &[u16]) -> Result<(), Chip8Error> {
+        if self.stack_depth.is_some_and(|depth| frames.len() > depth) {
+            return Err("Stack frames are deeper than the stack depth!".into())
+        }
+        self.edit_count += 1;
+        self.stack = frames.to_vec();
+        Ok(())
+    }
+
+    // Subroutine levels before CALL overflows, where None is unlimited
+    pub fn stack_depth(&self) -> Option<usize> {
+        self.stack_depth
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        self.delay_timer
+    }
+
+    pub fn set_delay_timer(&mut self, ticks: u8) {
+        self.edit_count += 1;
+        self.delay_timer = ticks;
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.sound_timer
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.ram
+    }
+
+    // Allows debuggers to edit ram, including the font data, which decodes every instruction again
+    pub fn memory_mut(&mut self) -> &mut [u8] {
+        self.edit_count += 1;
+        self.clear_code_caches();
+        &mut self.ram
+    }
+
+    // Writes a byte of ram for cheats, which only decodes the instructions overlapping it again
+    pub fn poke(&mut self, address: u16, value: u8) -> Result<(), Chip8Error> {
+        let address = address as usize;
+        let Some(byte) = self.ram.get_mut(address) else { return Err("Poke is outside of ram!".into()) };
+        *byte = value;
+        self.edit_count += 1;
+        self.invalidate_code(address..address + 1);
+        Ok(())
+    }
+
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    // Starts counting executed instructions from zero, or stops and clears the counts
+    pub fn set_profiling(&mut self, is_profiling: bool) {
+        let size = match is_profiling { true => 0x10000, false => 0 };
+        self.address_counts = vec![0; size];
+        self.opcode_counts = vec![0; size];
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        !self.address_counts.is_empty()
+    }
+
+    // Executions of the instruction at each address while profiling
+    pub fn address_counts(&self) -> &[u64] {
+        &self.address_counts
+    }
+
+    // Executions of each opcode while profiling, which counts self modifying code by what actually ran
+    pub fn opcode_counts(&self) -> &[u64] {
+        &self.opcode_counts
+    }
+
+    // Starts recording which ram bytes are executed, read, and written, or stops and clears the record
+    pub fn set_coverage(&mut self, is_tracking: bool) {
+        let size = match is_tracking { true => self.ram.len(), false => 0 };
+        self.coverage = vec![0; size];
+    }
+
+    pub fn is_tracking_coverage(&self) -> bool {
+        !self.coverage.is_empty()
+    }
+
+    // Coverage flags of each ram byte while tracking coverage
+    pub fn coverage(&self) -> &[u8] {
+        &self.coverage
+    }
+
+    // 60hz ticks since the machine started, which keep counting across resets and loaded states
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    // Starts logging every CXKK roll, or stops and clears the log
+    pub fn set_random_logging(&mut self, is_logging: bool) {
+        self.random_log = match is_logging { true => Some(Vec::new()), false => None };
+    }
+
+    // Rolls since logging started, which is empty unless logging
+    pub fn random_log(&self) -> &[RandomRoll] {
+        self.random_log.as_deref().unwrap_or(&[])
+    }
+
+    // Makes CXKK take the logged rolls in order instead of generating them, then return to the generator
+    pub fn replay_random(&mut self, rolls: Vec<RandomRoll>) {
+        self.random_replay = rolls.into();
+        self.random_divergence = None;
+    }
+
+    pub fn random_divergence(&self) -> Option<RandomDivergence> {
+        self.random_divergence
+    }
+
+    // Generates or replays a roll for the CXKK at the address, and logs it
+    fn random_byte(&mut self, pc: u16, kk: u8) -> u8 {
+        let byte = match self.random_replay.pop_front() {
+            Some(roll) => {
+                if roll.pc != pc && self.random_divergence.is_none() {
+                    self.random_divergence = Some(RandomDivergence{expected: roll, pc, frame: self.frame_count});
                 }
+                roll.byte
+            }
+            None => self.random_generator.next_u64() as u8
+        };
+        if let Some(log) = &mut self.random_log {
+            log.push(RandomRoll{frame: self.frame_count, pc, byte, result: byte & kk});
+        }
+        byte
+    }
 
-                0xF => match kk {
-                    // opcode LD Vx, DT - the delay timer is loaded in register x
-                    0x07 => {
-                        self.general_registers[x as usize] = self.delay_timer;
-                        self.program_counter += 2;
-                    }
-                    
-                    // opcode LD Vx, K - waits for a key press, then the key is loaded in register x
-                    0x0A => {
-                        for i in 0..self.keyboard.len() {
-                            // Iterates to find a released key
-                            if self.key_released[i] {
-                                // Handles the release to avoid repeat detections
-                                self.key_released[i] = false;
-
-                                // Returns the released key in register x
-                                self.general_registers[x as usize] = i as u8;
-
-                                // Stops waiting if a key is released
-                                self.program_counter += 2;
-                                continue 'cycle_loop;
-                            }
-                        }
+    // Chooses how instructions execute, which doesn't change what the program does
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
 
-                        // Waits and updates buffers if no key is released
-                        let old_buffer = self.cycle_buffer;
-                        self.cycle_buffer %= cycle_delta;
-                        self.clock_buffer += old_buffer - self.cycle_buffer;
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
 
-                        while self.clock_buffer >= CLOCK_DELTA {
-                            self.clock_buffer -= CLOCK_DELTA;
-                            if self.delay_timer > 0 { self.delay_timer -= 1; }
-                        }
-                        break
-                    }
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
 
-                    // opcode LD DT, VX - register x is loaded in the delay timer
-                    0x15 => {
-                        self.delay_timer = self.general_registers[x as usize];
-                        self.program_counter += 2;
-                    }
+    // Changes the quirks between instructions, forgetting compiled blocks that resolved the old ones
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+        self.clear_code_caches();
+    }
 
-                    // opcode LD ST, Vx - register x is loaded in the sound timer
-                    // a value of 1 is not responded to on original hardware
-                    0x18 => {
-                        let sound_timer = self.general_registers[x as usize];
-                        if sound_timer > 1 {
-                            // Calculates the number of audio samples in the sound timer's duration
-                            let remaining_samples = sound_timer as i32 * (48000 / 60) as i32;
-                            self.remaining_samples.store(remaining_samples, Ordering::Release);
-                        }
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
 
-                        self.program_counter += 2;
-                    },
+    pub fn start_address(&self) -> u16 {
+        self.start_address
+    }
 
-                    // opcode ADD I, Vx - index register = index register + register x
-                    0x1E => {
-                        self.index_register += self.general_registers[x as usize] as u16;
-                        self.program_counter += 2;
-                    }
+    // Ram the loaded rom was copied into
+    pub fn rom_range(&self) -> Range<usize> {
+        self.start_address as usize..self.start_address as usize + self.rom_size
+    }
 
-                    // opcode LD B, Vx - address of the sprite for the digit in register x is loaded in the index register
-                    // index register = register x * 5
-                    0x29 => {
-                        self.index_register = self.general_registers[x as usize & 0xF] as u16 * 5;
-                        self.program_counter += 2;
-                    }
+    // Where the digit sprites that FX29 points to start, which moves into the patched interpreter's page for HiRes programs
+    pub fn font_address(&self) -> u16 {
+        match self.display_mode {
+            DisplayMode::HiRes => HIRES_FONT_ADDRESS,
+            _ => 0
+        }
+    }
 
-                    // opcode LD F, Vx - the BCD representation of register x is loaded at the index register
-                    0x33 => {
-                        // Terminates if the BCD store is accessing invalid ram
-                        if self.index_register < 0x200 || self.index_register + 2 >= MAX_RAM_ADDRESS {
-                            return Some("Invalid memory access in BCD store!")
-                        }
+    pub fn display_mode(&self) -> DisplayMode {
+        self.display_mode
+    }
 
-                        self.ram[self.index_register as usize]     = self.general_registers[x as usize] / 100;
-                        self.ram[self.index_register as usize + 1] = self.general_registers[x as usize] / 10 % 10;
-                        self.ram[self.index_register as usize + 2] = self.general_registers[x as usize] % 10;
+    // Returns the new display mode once after the program switches resolutions
+    pub fn take_display_mode_change(&mut self) -> Option<DisplayMode> {
+        match std::mem::take(&mut self.is_display_mode_changed) {
+            true => Some(self.display_mode),
+            false => None
+        }
+    }
 
-                        self.program_counter += 2;
-                    },
+    // Returns the front buffer's rows changed since the last call, so frontends can skip uploading unchanged frames
+    pub fn take_dirty_rows(&mut self) -> Option<Range<u32>> {
+        let rows = std::mem::replace(&mut self.front_dirty_rows, 0..0);
+        match rows.is_empty() {
+            true => None,
+            false => Some(rows.start as u32..rows.end as u32)
+        }
+    }
 
-                    // opcode LD [I], Vx - registers 0 to x are loaded at the index register
-                    // index register = index register + x + 1
-                    0x55 => {
-                        // Terminates if the store is accessing invalid ram
-                        let max_ram_access = self.index_register + x as u16;
-                        if self.index_register < 0x200 || max_ram_access >= MAX_RAM_ADDRESS {
-                            return Some("Invalid memory access in store!")
-                        }
+    fn mark_all_dirty(&mut self) {
+        self.dirty_rows = 0..self.display_mode.size().1 as u16;
+    }
 
-                        let destination = &mut self.ram[self.index_register as usize..max_ram_access as usize + 1];
-                        destination.copy_from_slice(&self.general_registers[0..x as usize + 1]);
-                        self.index_register += x as u16 + 1;
+    // Copies the rows drawn since the last tick to the front buffer, like the vertical blank of a real display
+    // Several draws in one tick then show together, instead of the frontend catching a sprite erased but not redrawn
+    pub(crate) fn latch_frame(&mut self) {
+        if self.dirty_rows.is_empty() && self.front_buffer.len() == self.frame_buffer.len() { return }
+        self.front_buffer.clone_from(&self.frame_buffer);
+        let rows = std::mem::replace(&mut self.dirty_rows, 0..0);
+        self.front_dirty_rows = match self.front_dirty_rows.is_empty() {
+            true => rows,
+            false => self.front_dirty_rows.start.min(rows.start)..self.front_dirty_rows.end.max(rows.end)
+        };
+    }
 
-                        self.program_counter += 2;
-                    },
+    // Resizes and clears the frame buffer for a new resolution
+    fn set_display_mode(&mut self, mode: DisplayMode) {
+        if mode == self.display_mode { return }
+        let (width, height) = mode.size();
+        self.frame_buffer = vec![0; width as usize * height as usize];
+        self.display_mode = mode;
+        self.is_display_mode_changed = true;
+        self.mark_all_dirty();
+        self.latch_frame();
+    }
 
-                    // opcode LD Vx, [I] - memory starting at the index register is loaded in registers 0 to x
-                    // index register = index register + x + 1
-                    0x65 => {
-                        // Terminates if the load is accessing invalid ram
-                        let max_ram_access = self.index_register + x as u16;
-                        if max_ram_access >= MAX_RAM_ADDRESS {
-                            return Some("Invalid memory access in load!");
-                        }
+    // Lowest address programs may run or write, where the interpreter and font live below 0x200 unless memory is permissive
+    // A program started lower than 0x200 may use the memory from its start
+    fn lowest_address(&self) -> u16 {
+        match self.is_permissive_memory {
+            true => 0,
+            false => self.start_address.min(0x200)
+        }
+    }
+
+    // Palette indices of the display's pixels in rows from the top left, including draws since the last tick
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.frame_buffer
+    }
+
+    // The frame buffer as of the last 60hz tick, which is always a whole frame
+    pub fn front_buffer(&self) -> &[u8] {
+        &self.front_buffer
+    }
+
+    // Presses or releases one of the 16 chip8 keys
+    pub fn set_key(&mut self, key: usize, is_pressed: bool) {
+        match is_pressed {
+            true => if !self.keyboard[key] { self.key_pressed[key] = true; }
+            false => self.key_released[key] = true
+        }
+        self.keyboard[key] = is_pressed;
+    }
+
+    pub fn keys(&self) -> &[bool; 16] {
+        &self.keyboard
+    }
+
+    pub fn set_audio_sink(&mut self, sink: Box<dyn AudioSink>) {
+        self.audio_sink = Some(sink);
+    }
+
+    // Hooks execution with hook files and tools, replacing any previous observer
+    pub fn set_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observer = Some(observer);
+    }
 
-                        let source = &self.ram[self.index_register as usize..max_ram_access as usize + 1];
-                        self.general_registers[0..x as usize + 1].copy_from_slice(source);
-                        self.index_register += x as u16 + 1;
+    // Every instruction's ram access comes through here, which tells the observer and pauses at watchpoints
+    // Reads are reported before they happen and writes after, and the instruction still finishes before pausing
+    fn access_memory(&mut self, addresses: Range<usize>, is_write: bool) {
+        // Self modifying code runs what was written
+        if is_write { self.invalidate_code(addresses.clone()); }
+
+        if !self.coverage.is_empty() {
+            let flag = match is_write { true => COVERAGE_WRITTEN, false => COVERAGE_READ };
+            let end = addresses.end.min(self.coverage.len());
+            for flags in &mut self.coverage[addresses.start.min(end)..end] {
+                *flags |= flag;
+            }
+        }
+
+        match is_write {
+            true => self.notify(|observer, chip8| observer.memory_write(chip8, addresses.clone())),
+            false => self.notify(|observer, chip8| observer.memory_read(chip8, addresses.clone()))
+        }
+
+        let is_watched = |&&(address, watch): &&(u16, Watch)| addresses.contains(&(address as usize)) && match watch {
+            Watch::Read => !is_write,
+            Watch::Write => is_write,
+            Watch::Access => true
+        };
+        if let Some(&(address, _)) = self.watchpoints.iter().find(is_watched) {
+            self.watch_hit = Some(WatchHit{address, is_write, pc: self.program_counter});
+            self.is_paused = true;
+        }
+    }
+
+    // Forgets decoded instructions and compiled blocks overlapping the addresses, including an instruction whose second
+    // byte changed, so a write to a single address keeps the rest of the caches
+    fn invalidate_code(&mut self, addresses: Range<usize>) {
+        self.decode_cache[addresses.start.saturating_sub(1)..addresses.end].fill(None);
+        #[cfg(feature = "threaded")]
+        for block in &mut self.blocks[addresses.start.saturating_sub(MAX_BLOCK_LENGTH * 2)..addresses.end] {
+            if block.as_ref().is_some_and(|block| block.overlaps(&addresses)) { *block = None; }
+        }
+    }
+
+    // Forgets decoded and compiled instructions after ram changes in ways that aren't tracked by address
+    fn clear_code_caches(&mut self) {
+        self.decode_cache.fill(None);
+        #[cfg(feature = "threaded")]
+        self.blocks.fill_with(|| None);
+    }
+
+    // Lends the machine to the observer, which is taken out while its hook runs
+    fn notify(&mut self, hook: impl FnOnce(&mut dyn Observer, &mut Chip8)) {
+        if self.is_replaying { return }
+        let Some(mut observer) = self.observer.take() else { return };
+        hook(observer.as_mut(), self);
+        if self.observer.is_none() { self.observer = Some(observer); }
+    }
+
+    // Sets the sound timer and tells the audio sink when the beep starts and how long it lasts
+    // a value of 1 is not responded to on original hardware
+    pub fn set_sound_timer(&mut self, ticks: u8) {
+        let was_playing = self.sound_timer > 1;
+        self.sound_timer = ticks;
+
+        if self.is_replaying { return }
+        let Some(sink) = &mut self.audio_sink else { return };
+        let offset_nanos = self.run_nanos;
+        let time_nanos = self.frame_count as f64 * CLOCK_DELTA as f64 + self.clock_buffer.clamp(0.0, CLOCK_DELTA) as f64;
+        match ticks {
+            // The first tick comes after the rest of the current 60hz period
+            2.. => sink.audio_event(AudioEvent::Start{offset_nanos,
+                duration_nanos: (ticks - 1) as f32 * CLOCK_DELTA + (CLOCK_DELTA - self.clock_buffer).max(0.0), time_nanos}),
+            _ if was_playing => sink.audio_event(AudioEvent::Stop{offset_nanos, time_nanos}),
+            _ => ()
+        }
+    }
+
+    // Serializes the machine state, excluding the configuration and random number generator
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut writer = StateWriter::new(STATE_MAGIC, STATE_VERSION);
+        writer.write_bytes(&self.ram);
+        let (width, height) = self.display_mode.size();
+        writer.write_u16(width as u16);
+        writer.write_u16(height as u16);
+        writer.write_bytes(&self.frame_buffer);
+        writer.write_u32(self.stack.len() as u32);
+        for &address in &self.stack { writer.write_u16(address); }
+        writer.write_bytes(&self.general_registers.0);
+        writer.write_u16(self.program_counter);
+        writer.write_u16(self.index_register);
+        writer.write_u8(self.delay_timer);
+        writer.write_f32(self.cycle_buffer);
+        writer.write_f32(self.clock_buffer);
+        writer.write_u8(self.sound_timer);
+        writer.finish()
+    }
+
+    // Restores a machine state, leaving the current state untouched on failure
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), Chip8Error> {
+        self.read_state(bytes).map_err(Chip8Error::InvalidSaveState)?;
+        self.edit_count += 1;
+        Ok(())
+    }
+
+    // Restores a machine state written by the serde feature, which like load_state needs a machine with the same configuration
+    #[cfg(feature = "serde")]
+    pub fn deserialize_state<'de, D: serde::Deserializer<'de>>(&mut self, deserializer: D) -> Result<(), D::Error> {
+        let bytes = <Vec<u8> as serde::Deserialize>::deserialize(deserializer)?;
+        self.load_state(&bytes).map_err(serde::de::Error::custom)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot{registers: self.general_registers, index: self.index_register, pc: self.program_counter, stack: self.stack.clone(),
+            delay_timer: self.delay_timer, sound_timer: self.sound_timer, display_mode: self.display_mode, is_exited: self.is_exited}
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint{state: self.save_state(), random_generator: self.random_generator.clone(), instruction_count: self.instruction_count,
+            random_log_length: self.random_log().len(), random_replay: self.random_replay.clone(), random_divergence: self.random_divergence,
+            edit_count: self.edit_count}
+    }
 
-                        self.program_counter += 2;
-                    },
-                    _ => return Some("Unsupported opcode!")
+    // Whether nothing has changed the machine from outside of execution since the checkpoint was taken
+    pub fn is_checkpoint_current(&self, checkpoint: &Checkpoint) -> bool {
+        checkpoint.edit_count == self.edit_count
+    }
+
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> Result<(), Chip8Error> {
+        self.read_state(&checkpoint.state).map_err(Chip8Error::InvalidSaveState)?;
+        self.random_generator = checkpoint.random_generator.clone();
+        self.instruction_count = checkpoint.instruction_count;
+        if let Some(log) = &mut self.random_log { log.truncate(checkpoint.random_log_length); }
+        self.random_replay = checkpoint.random_replay.clone();
+        self.random_divergence = checkpoint.random_divergence;
+        Ok(())
+    }
+
+    // Restores the checkpoint and executes the steps again without telling observers, profiling, or the audio sink, which
+    // already saw them, then tells the audio sink once if the sound timer ended up different
+    pub fn replay(&mut self, checkpoint: &Checkpoint, steps: u64) -> Result<(), Chip8Error> {
+        let sound_timer = self.sound_timer;
+        self.is_replaying = true;
+        let result = self.restore(checkpoint).and_then(|_| (0..steps).try_for_each(|_| self.step().map(|_| ())));
+        self.is_replaying = false;
+
+        let replayed_sound_timer = self.sound_timer;
+        self.sound_timer = sound_timer;
+        self.set_sound_timer(replayed_sound_timer);
+        result
+    }
+
+    fn read_state(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+        let mut reader = StateReader::new(bytes, STATE_MAGIC, STATE_VERSION)?;
+
+        // Terminates if the state was saved with a different memory size
+        let ram = reader.read_bytes()?;
+        if ram.len() != self.ram.len() {
+            return Err("Save state doesn't match the profile's memory size!")
+        }
+
+        // Terminates if the state was saved with a different display size, where older states are 64x32
+        let display_size = match reader.version() {
+            1 | 2 => (FRAME_BUFFER_WIDTH, FRAME_BUFFER_HEIGHT),
+            _ => (reader.read_u16()?, reader.read_u16()?)
+        };
+        let (width, height) = self.display_mode.size();
+        if display_size != (width as u16, height as u16) {
+            return Err("Save state doesn't match the display size!")
+        }
+
+        // Older states stored colors, where the most common color is taken as the background
+        let frame_buffer = match reader.version() {
+            1..=3 => {
+                let mut colors = vec![0; self.frame_buffer.len()];
+                for pixel in colors.iter_mut() { *pixel = reader.read_u32()?; }
+                let background = *colors.iter().max_by_key(|&color| colors.iter().filter(|&other| other == color).count())
+                    .unwrap_or(&0);
+                colors.iter().map(|&color| (color != background) as u8).collect()
+            }
+            _ => reader.read_bytes()?.to_vec()
+        };
+        if frame_buffer.len() != self.frame_buffer.len() {
+            return Err("Save state has an invalid frame buffer size!")
+        }
+        // Older states stored a fixed stack with the stack pointer after the registers
+        let stack_length = match reader.version() {
+            1..=4 => OLD_STACK_SIZE,
+            _ => reader.read_u32()? as usize
+        };
+        let mut stack = Vec::new();
+        for _ in 0..stack_length { stack.push(reader.read_u16()?); }
+
+        let general_registers = reader.read_bytes()?;
+        if general_registers.len() != self.general_registers.len() {
+            return Err("Save state has an invalid register count!")
+        }
+
+        let program_counter = reader.read_u16()?;
+        let index_register = reader.read_u16()?;
+        if reader.version() <= 4 {
+            let stack_pointer = reader.read_u8()? as usize;
+            if stack_pointer > stack.len() {
+                return Err("Save state has an invalid stack pointer!")
+            }
+            stack.truncate(stack_pointer);
+        }
+        let delay_timer = reader.read_u8()?;
+        let cycle_buffer = reader.read_f32()?;
+        let clock_buffer = reader.read_f32()?;
+        let sound_timer = match reader.version() {
+            1 => 0,
+            _ => reader.read_u8()?
+        };
+        if self.stack_depth.is_some_and(|depth| stack.len() > depth) {
+            return Err("Save state's stack is deeper than the stack depth!")
+        }
+
+        // Copies the state only after every field is read
+        self.ram.copy_from_slice(ram);
+        self.clear_code_caches();
+        self.frame_buffer = frame_buffer;
+        self.mark_all_dirty();
+        self.latch_frame();
+        self.stack = stack;
+        self.general_registers.copy_from_slice(general_registers);
+        self.program_counter = program_counter;
+        self.index_register = index_register;
+        self.delay_timer = delay_timer;
+        self.cycle_buffer = cycle_buffer;
+        self.clock_buffer = clock_buffer;
+        self.set_sound_timer(sound_timer);
+        Ok(())
+    }
+}
+
+// Serializes the machine as its save state, so serde formats carry the same layout as save state files
+#[cfg(feature = "serde")]
+impl serde::Serialize for Chip8 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.save_state())
+    }
+}
+
+// Seeds from the operating system, which browsers lack, so web frontends should pass a seed instead
+#[cfg(not(target_arch = "wasm32"))]
+fn os_rng() -> SmallRng {
+    SmallRng::from_os_rng()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn os_rng() -> SmallRng {
+    SmallRng::seed_from_u64(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reference results of 8XY4, 8XY5, 8XY6, 8XY7, and 8XYE as register x and the flag, written from the instruction
+    // set's definitions instead of the interpreter's code
+    fn reference(n: u8, vx: u8, vy: u8, is_shift_quirk: bool) -> (u8, u8) {
+        let shifted = match is_shift_quirk {
+            true => vy,
+            false => vx
+        };
+        match n {
+            0x4 => ((vx as u16 + vy as u16) as u8, (vx as u16 + vy as u16 > 0xFF) as u8),
+            0x5 => (vx.wrapping_sub(vy), (vx >= vy) as u8),
+            0x6 => (shifted >> 1, shifted & 1),
+            0x7 => (vy.wrapping_sub(vx), (vy >= vx) as u8),
+            0xE => (shifted << 1, shifted >> 7),
+            _ => unreachable!()
+        }
+    }
+
+    // Runs every pair of register values through each instruction under both shift quirks, where an x of VF checks that
+    // the flag overwrites the result
+    #[test]
+    fn arithmetic_flags_match_reference() {
+        for is_shift_quirk in [false, true] {
+            for x in [0x1, 0xF] {
+                for n in [0x4, 0x5, 0x6, 0x7, 0xE] {
+                    let opcode = 0x8000 | (x as u16) << 8 | 0x20 | n as u16;
+                    let mut chip8 = Chip8::init(&opcode.to_be_bytes(), &Chip8Options::new(Profile::Chip8, 0)).unwrap();
+                    chip8.set_quirks(Quirks{is_shift_quirk, ..chip8.quirks()});
+                    let start = chip8.pc();
+
+                    for vx in 0..=u8::MAX {
+                        for vy in 0..=u8::MAX {
+                            let mut registers = Registers::default();
+                            registers[x] = vx;
+                            registers[2] = vy;
+                            chip8.set_registers(registers);
+                            chip8.set_pc(start);
+                            chip8.step().unwrap();
+
+                            let (result, flag) = reference(n, vx, vy, is_shift_quirk);
+                            let expected = match x == FLAGS_REGISTER {
+                                true => (flag, flag),
+                                false => (result, flag)
+                            };
+                            let registers = chip8.registers();
+                            assert_eq!((registers[x], registers.flag()), expected,
+                                "{opcode:04X} with V{x:X}={vx:02X} V2={vy:02X} and the shift quirk {is_shift_quirk}");
+                        }
+                    }
                 }
-                _ => return Some("Unsupported opcode!")
             }
         }
+    }
 
-        // Keeps track of the previous keyboard state to know when a key is pressed or released
-        self.key_released.fill(false);
-        None
+    // Replaying steps from a checkpoint logs each roll once, and an edit makes the checkpoint stale
+    #[test]
+    fn replay_keeps_random_log() {
+        let rom = [0xC0, 0xFF, 0xC1, 0xFF, 0xC2, 0xFF];
+        let mut chip8 = Chip8::init(&rom, &Chip8Options::new(Profile::Chip8, 0)).unwrap();
+        chip8.set_random_logging(true);
+        let checkpoint = chip8.checkpoint();
+        for _ in 0..3 { chip8.step().unwrap(); }
+        let (registers, log) = (*chip8.registers(), chip8.random_log().to_vec());
+
+        chip8.replay(&checkpoint, 1).unwrap();
+        assert_eq!(chip8.random_log(), &log[..1]);
+        chip8.replay(&checkpoint, 3).unwrap();
+        assert_eq!(chip8.random_log(), &log[..]);
+        assert!(chip8.registers() == &registers);
+
+        assert!(chip8.is_checkpoint_current(&checkpoint));
+        chip8.set_delay_timer(5);
+        assert!(!chip8.is_checkpoint_current(&checkpoint));
+    }
+
+    // Runs a small rom that adds, calls a subroutine, and loops, then compares the whole state
+    #[test]
+    fn step_n_matches_snapshot() {
+        let rom = [0x60, 0x05, 0x61, 0x03, 0x80, 0x14, 0x22, 0x0A, 0x12, 0x08, 0xA3, 0x00, 0xF0, 0x15, 0x00, 0xEE];
+        let mut chip8 = Chip8::init(&rom, &Chip8Options::new(Profile::Chip8, 0)).unwrap();
+        assert!(chip8.step_n(4).unwrap() == Step::Executed);
+        assert_eq!(chip8.snapshot().stack, [0x208]);
+
+        chip8.step_n(3).unwrap();
+        let mut registers = Registers::default();
+        registers[0] = 8;
+        registers[1] = 3;
+        let expected = Snapshot{registers, index: 0x300, pc: 0x208, stack: Vec::new(), delay_timer: 8, sound_timer: 0,
+            display_mode: DisplayMode::LowRes, is_exited: false};
+        assert_eq!(chip8.snapshot(), expected);
+
+        // The loop jumps to itself without changing anything else
+        chip8.step_n(10).unwrap();
+        assert_eq!(chip8.snapshot(), expected);
+        assert_eq!(chip8.instruction_count(), 17);
+    }
+
+    // Poking an instruction that already executed runs the new byte the next time
+    #[test]
+    fn poke_decodes_instruction_again() {
+        let mut chip8 = Chip8::init(&[0x60, 0x01, 0x12, 0x00], &Chip8Options::new(Profile::Chip8, 0)).unwrap();
+        chip8.step_n(2).unwrap();
+        chip8.poke(0x201, 0x07).unwrap();
+        chip8.step().unwrap();
+        assert_eq!(chip8.registers()[0], 7);
+        assert!(chip8.poke(0xFFFF, 0).is_err());
     }
-}
\ No newline at end of file
+
+    // A HiRes program's jump over the patched interpreter goes straight to its code, and FX29 points to the moved font
+    #[test]
+    fn hires_program_moves_font() {
+        let mut rom = vec![0; 0xC4];
+        rom[..2].copy_from_slice(&[0x12, 0x60]);
+        rom[0xC0..].copy_from_slice(&[0x60, 0x03, 0xF0, 0x29]);
+        let mut chip8 = Chip8::init(&rom, &Chip8Options::new(Profile::Chip8, 0)).unwrap();
+        assert_eq!(chip8.display_mode(), DisplayMode::HiRes);
+        assert_eq!(&chip8.memory()[0x200..0x202], [0x12, 0xC0]);
+        assert_eq!(&chip8.memory()[0x270..0x2C0], FONTS);
+
+        chip8.step_n(3).unwrap();
+        assert_eq!(chip8.index(), 0x270 + 3 * 5);
+    }
+
+    // Instructions at the end of XO-CHIP's 64K of ram continue at the start of ram, like its 16 bit program counter
+    #[test]
+    fn program_counter_wraps_at_end_of_ram() {
+        let mut chip8 = Chip8::init(&[0x00, 0xE0], &Chip8Options::new(Profile::XoChip, 0)).unwrap();
+        for (opcode, pc, stack) in [(0x6012u16, 0x0000, vec![]), (0x3012, 0x0002, vec![]), (0x2208, 0x0208, vec![0x0000])] {
+            let [high, low] = opcode.to_be_bytes();
+            chip8.poke(0xFFFE, high).unwrap();
+            chip8.poke(0xFFFF, low).unwrap();
+            chip8.set_pc(0xFFFE);
+            chip8.step().unwrap();
+            assert_eq!((chip8.pc(), chip8.snapshot().stack), (pc, stack), "{opcode:04X} at 0xFFFE");
+        }
+    }
+}
@@ -1,224 +1,750 @@
-use std::{env, iter::Peekable, path::PathBuf, sync::mpsc, thread::sleep, time::Duration};
-use sdl3::{dialog::{show_open_file_dialog, DialogCallback}, EventPump};
+#[cfg(feature = "sdl")]
+use sdl3::{gamepad::Button, keyboard::Keycode};
+use chip8_emulator::{named_palette, Chip8Error, Backend, Chip8Options, Palette, DEFAULT_STACK_DEPTH, DEFAULT_START_ADDRESS, MAX_CLOCK_HZ, Profile, Quirks};
+use crate::{args::Arguments, filter::DisplayFilter, ErrorPolicy, EscapeAction, FocusPolicy, FullscreenMode, FrontendKind, ScalingMode, Waveform, toml::{config_path, parse_toml, TomlValue}};
+#[cfg(feature = "sdl")]
+use crate::gamepad::DEFAULT_GAMEPAD_MAP;
+
+// Host keys are sdl keycodes, or the characters typed into the terminal in builds without sdl
+#[cfg(feature = "sdl")]
+pub type HostKey = Keycode;
+#[cfg(not(feature = "sdl"))]
+pub type HostKey = char;
+
+// Host keys for the chip8 keys 0 to F
+#[cfg(feature = "sdl")]
+const SDL3_CHIP8_KEY_MAP: [Keycode; 16] = [
+    Keycode::X, Keycode::_1, Keycode::_2, Keycode::_3, Keycode::Q, Keycode::W, Keycode::E, Keycode::A,
+    Keycode::S, Keycode::D, Keycode::Z, Keycode::C, Keycode::_4, Keycode::R, Keycode::F, Keycode::V,
+];
+
+#[cfg(not(feature = "sdl"))]
+const TERMINAL_CHIP8_KEY_MAP: [HostKey; 16] = ['x', '1', '2', '3', 'q', 'w', 'e', 'a', 's', 'd', 'z', 'c', '4', 'r', 'f', 'v'];
+
+// The second machine's keys on the numpad, in the same layout as the first machine's keys
+#[cfg(feature = "sdl")]
+const SDL3_CHIP8_KEY_MAP2: [Keycode; 16] = [
+    Keycode::KpPeriod, Keycode::Kp7, Keycode::Kp8, Keycode::Kp9, Keycode::Kp4, Keycode::Kp5, Keycode::Kp6, Keycode::Kp1,
+    Keycode::Kp2, Keycode::Kp3, Keycode::Kp0, Keycode::KpEnter, Keycode::KpDivide, Keycode::KpMultiply, Keycode::KpMinus, Keycode::KpPlus,
+];
 
 pub struct Chip8Configuration {
     pub rom_path: String,
-    pub clock_hz: u32,
-    pub background_color: u32,
-    pub foreground_color: u32,
     pub window_size: Option<Result<(u32 ,u32), u32>>,
-    pub is_fullscreen: bool,
-    pub is_drawsync: bool,
-    pub is_shift_quirk: bool,
-    pub is_vsync: bool
+    pub fullscreen_mode: FullscreenMode,
+    pub display_number: Option<usize>, // Display to open on and fill, counting from 1
+    pub is_vsync: bool,
+    pub is_debug: bool,
+    pub is_disassemble: bool,
+    pub assemble_path: Option<String>, // Writes the rom assembled from the source at the rom path
+    pub frontend: FrontendKind,
+    pub backend: Backend,
+    pub is_clock_max: bool, // Runs as many instructions as real time allows instead of the clock speed
+    pub escape_action: EscapeAction,
+    pub is_confirm_quit: bool, // Asks before quitting when no state was saved recently
+    pub error_policy: ErrorPolicy,
+    pub focus_policy: FocusPolicy,
+    pub is_watch: bool,
+    pub is_record: bool,
+    pub is_phosphor: bool,
+    pub is_rom_icon: bool, // Turns the rom's screen into the window icon after it starts
+    pub display_filter: DisplayFilter,
+    pub scaling_mode: ScalingMode,
+    pub is_smooth: bool,
+    pub volume: u8, // Buzzer loudness from 0 to 100
+    pub tone_hz: f32,
+    pub waveform: Waveform,
+    pub is_platform_set: bool, // Profile, clock, or quirks were chosen, so the rom database doesn't override them
+    pub is_reserved_set: bool, // Reserved memory was chosen, so it doesn't follow a profile from the rom database
+    pub gdb_port: Option<u16>,
+    pub host_port: Option<u16>,        // Shares the machine over netplay
+    pub join_address: Option<String>, // Plays another emulator's machine over netplay
+    pub is_lockstep: bool,            // Netplay runs the machine on both sides instead of streaming it
+    pub input_delay: u8,              // Frames until lockstep keys apply, which hides network latency
+    pub movie_path: Option<String>,
+    pub playback_path: Option<String>,
+    pub hooks_path: Option<String>,
+    pub symbols_path: Option<String>, // Symbol file used instead of the one beside the rom
+    pub profile_path: Option<String>, // Writes the instruction profile on exit
+    pub coverage_path: Option<String>, // Writes the coverage map on exit
+    pub random_log_path: Option<String>, // Writes the CXKK rolls on exit
+    pub random_replay_path: Option<String>, // CXKK takes these logged rolls instead of generating them
+    pub audio_dump_path: Option<String>, // Writes the beeps as a wav file on exit
+    pub video_path: Option<String>, // Records the display and beeps to a video through ffmpeg
+    pub palette: Palette,
+    pub key_map: Vec<(HostKey, usize)>, // Host keys bound to chip8 keys, where a chip8 key can have several
+    pub rom2_path: Option<String>, // Runs a second machine beside the first
+    pub key_map2: Vec<(HostKey, usize)>, // The second machine's keys, or the second player's with -twoplayer
+    pub compare_names: Option<String>, // Quirks flipped on a second machine running the same rom, which pauses when the displays differ
+    #[cfg(feature = "sdl")]
+    pub gamepad_map: Vec<(Button, usize)>,
+    pub autofire_keys: [bool; 16], // Chip8 keys that pulse on and off while held
+    pub autofire_hz: u32,
+    pub options: Chip8Options
 }
 
 impl Chip8Configuration {
-    pub fn parse(window: &sdl3::video::Window, event_pump: &mut EventPump) -> Result<Chip8Configuration, &'static str> {
-        // Reads rom path and other configuration from the command line
+    pub fn parse(arguments: &Arguments) -> Result<Chip8Configuration, Chip8Error> {
+        // Reads rom path and other configuration from the command line, where no rom path boots the splash rom
         let mut rom_path = String::from("");
-        let mut clock_per_sec = 500;
-        let mut background_color = 0xFF000000; // Black
-        let mut foreground_color = 0xFFFFFFFF; // White
+        let mut clock_per_sec = None;
+        let mut is_clock_max = false;
+        let mut is_cosmac_timing = false;
+        let mut stack_depth = Some(DEFAULT_STACK_DEPTH);
+        let mut reserved_size = None;
+        let mut start_address = DEFAULT_START_ADDRESS;
+        let mut is_permissive_memory = false;
+        let mut palette = named_palette("default").unwrap(); // Black and white
         let mut window_size = None;
-        let mut is_fullscreen = false;
-        let mut is_drawsync = true;
-        let mut is_shift_quirk = true;
+        let mut fullscreen_mode = FullscreenMode::Off;
+        let mut display_number = None;
+        let mut is_drawsync = None;
+        let mut is_shift_quirk = None;
+        let mut is_vf_reset = None;
+        let mut is_index_increment = None;
+        let mut is_sprite_clipping = None;
+        let mut is_jump_quirk = None;
+        let mut is_key_release = None;
+        let mut is_key_tone = None;
         let mut is_vsync = true;
+        let mut is_debug = false;
+        let mut is_disassemble = false;
+        let mut assemble_path = None;
+        let mut frontend = match cfg!(feature = "sdl") {
+            true => FrontendKind::Sdl,
+            false => FrontendKind::Tui // Builds without sdl only have the terminal
+        };
+        let mut backend = Backend::Interpreter;
+        let mut escape_action = EscapeAction::Menu;
+        let mut is_confirm_quit = false;
+        let mut error_policy = ErrorPolicy::Terminate;
+        let mut focus_policy = FocusPolicy::Run;
+        let mut is_watch = false;
+        let mut is_record = false;
+        let mut is_phosphor = false;
+        let mut is_rom_icon = false;
+        let mut display_filter = DisplayFilter::None;
+        let mut scaling_mode = ScalingMode::Integer;
+        let mut is_smooth = false;
+        let mut volume = 100;
+        let mut tone_hz = 261.63; // Middle C
+        let mut waveform = Waveform::Square;
+        let mut gdb_port = None;
+        let mut host_port = None;
+        let mut join_address = None;
+        let mut is_lockstep = false;
+        let mut input_delay = 2;
+        let mut movie_path = None;
+        let mut playback_path = None;
+        let mut hooks_path = None;
+        let mut symbols_path = None;
+        let mut profile_path = None;
+        let mut coverage_path = None;
+        let mut random_log_path = None;
+        let mut random_replay_path = None;
+        let mut audio_dump_path = None;
+        let mut video_path = None;
+        #[cfg(feature = "sdl")]
+        let (mut key_map, mut key_map2) = (key_bindings(SDL3_CHIP8_KEY_MAP), key_bindings(SDL3_CHIP8_KEY_MAP2));
+        #[cfg(not(feature = "sdl"))]
+        let (mut key_map, mut key_map2) = (key_bindings(TERMINAL_CHIP8_KEY_MAP), Vec::new()); // Terminals type the numpad as digits
+        let mut rom2_path = None;
+        let mut is_two_player = false;
+        let mut compare_names = None;
+        #[cfg(feature = "sdl")]
+        let mut gamepad_map = DEFAULT_GAMEPAD_MAP.to_vec();
+        let mut autofire_keys = [false; 16];
+        let mut autofire_hz = 15;
+        let mut profile = Profile::Chip8;
+        let mut is_profile_set = false;
 
-        let mut args =  env::args().skip(1).peekable();
-        loop {
-            // Exits iterator at the end of the environment args
-            let arg = match args.next() {
-                Some(arg) => arg,
-                None => break
-            };
-
-            // Parses command parameters and the numerical postfix
-            let arg_type = arg.trim_end_matches(char::is_numeric);
-            match arg_type {
-                "-bg" | "-background" => {
+        // Reads defaults from the config file, which the command line overrides
+        let config_text = config_path("config.toml").and_then(|path| std::fs::read_to_string(path).ok());
+        for (key, value) in parse_toml(config_text.as_deref().unwrap_or(""))? {
+            match (key.as_str(), &value) {
+                ("profile", TomlValue::String(name)) => {
+                    profile = match Profile::parse(name) {
+                        Some(profile) => profile,
+                        None => return Err(Chip8Error::Usage("Unknown profile in the config file! Expected chip8, vip, schip, or xochip."))
+                    };
+                    is_profile_set = true;
+                }
+                ("clock", TomlValue::Integer(hz)) if (1..=MAX_CLOCK_HZ as i64).contains(hz) => clock_per_sec = Some(*hz as u32),
+                ("clock", TomlValue::String(name)) if name == "max" => is_clock_max = true,
+                ("clock", TomlValue::String(name)) if name == "cosmac" => is_cosmac_timing = true,
+                ("stack", TomlValue::Integer(levels)) if *levels > 0 => stack_depth = Some(*levels as usize),
+                ("reserved", TomlValue::Integer(size)) if *size >= 0 => reserved_size = Some(*size as usize),
+                ("start", TomlValue::Integer(address)) if (0..=0xFFFF).contains(address) => start_address = *address as u16,
+                ("permissive_memory", TomlValue::Boolean(value)) => is_permissive_memory = *value,
+                ("stack", TomlValue::String(name)) if name == "unlimited" => stack_depth = None,
+                ("background", _) => palette[0] = match toml_color(&value) {
+                    Some(color) => color,
+                    None => return Err(Chip8Error::Usage("Invalid background color in the config file!"))
+                },
+                ("foreground", _) => palette[1] = match toml_color(&value) {
+                    Some(color) => color,
+                    None => return Err(Chip8Error::Usage("Invalid foreground color in the config file!"))
+                },
+                // Selects a named palette or sets its entries from a list of colors
+                ("palette", TomlValue::String(name)) => palette = match named_palette(name) {
+                    Some(palette) => palette,
+                    None => return Err(Chip8Error::Usage("Unknown palette in the config file! Expected default, octo, gameboy, or amber."))
+                },
+                ("palette", TomlValue::Array(colors)) if colors.len() <= palette.len() => {
+                    for (entry, color) in palette.iter_mut().zip(colors) {
+                        *entry = match toml_color(color) {
+                            Some(color) => color,
+                            None => return Err(Chip8Error::Usage("Invalid palette color in the config file!"))
+                        };
+                    }
+                }
+                ("frontend", TomlValue::String(name)) => frontend = match FrontendKind::parse(name) {
+                    Some(frontend) => frontend,
+                    None => return Err(Chip8Error::Usage("Unknown frontend in the config file! Expected sdl or tui."))
+                },
+                ("backend", TomlValue::String(name)) => backend = match parse_backend(name) {
+                    Some(backend) => backend,
+                    None => return Err(Chip8Error::Usage("Unknown backend in the config file! Expected interp, or threaded in builds with the threaded feature."))
+                },
+                ("phosphor", TomlValue::Boolean(value)) => is_phosphor = *value,
+                ("rom_icon", TomlValue::Boolean(value)) => is_rom_icon = *value,
+                ("filter", TomlValue::String(name)) => display_filter = match DisplayFilter::parse(name) {
+                    Some(filter) => filter,
+                    None => return Err(Chip8Error::Usage("Unknown filter in the config file! Expected none, scanlines, or crt."))
+                },
+                ("scaling", TomlValue::String(name)) => scaling_mode = match ScalingMode::parse(name) {
+                    Some(mode) => mode,
+                    None => return Err(Chip8Error::Usage("Unknown scaling mode in the config file! Expected integer, fit, or stretch."))
+                },
+                ("smooth", TomlValue::Boolean(value)) => is_smooth = *value,
+                ("volume", TomlValue::Integer(value)) if (0..=100).contains(value) => volume = *value as u8,
+                ("tone", TomlValue::Integer(hz)) if (20..=20000).contains(hz) => tone_hz = *hz as f32,
+                ("wave", TomlValue::String(name)) => waveform = match Waveform::parse(name) {
+                    Some(waveform) => waveform,
+                    None => return Err(Chip8Error::Usage("Unknown wave in the config file! Expected square, triangle, sine, or noise."))
+                },
+                ("fullscreen", TomlValue::Boolean(value)) => fullscreen_mode = match value {
+                    true => FullscreenMode::Borderless,
+                    false => FullscreenMode::Off
+                },
+                ("fullscreen", TomlValue::String(name)) => fullscreen_mode = match FullscreenMode::parse(name) {
+                    Some(mode) => mode,
+                    None => return Err(Chip8Error::Usage("Unknown fullscreen mode in the config file! Expected off, borderless, or exclusive."))
+                },
+                ("display", TomlValue::Integer(number)) if *number > 0 => display_number = Some(*number as usize),
+                ("escape", TomlValue::String(name)) => escape_action = match EscapeAction::parse(name) {
+                    Some(action) => action,
+                    None => return Err(Chip8Error::Usage("Unknown escape action in the config file! Expected menu, quit, or fullscreen."))
+                },
+                ("confirm_quit", TomlValue::Boolean(value)) => is_confirm_quit = *value,
+                ("two_player", TomlValue::Boolean(value)) => is_two_player = *value,
+                ("autofire", TomlValue::Array(keys)) => for key in keys {
+                    match key {
+                        TomlValue::Integer(key) if (0..16).contains(key) => autofire_keys[*key as usize] = true,
+                        _ => return Err(Chip8Error::Usage("Autofire keys in the config file aren't chip8 keys from 0 to F!"))
+                    }
+                },
+                ("autofire_rate", TomlValue::Integer(hz)) if (1..=30).contains(hz) => autofire_hz = *hz as u32,
+                ("unfocused", TomlValue::String(name)) => focus_policy = match FocusPolicy::parse(name) {
+                    Some(policy) => policy,
+                    None => return Err(Chip8Error::Usage("Unknown unfocused behavior in the config file! Expected run, throttle, or pause."))
+                },
+                ("on_error", TomlValue::String(name)) => error_policy = match ErrorPolicy::parse(name) {
+                    Some(policy) => policy,
+                    None => return Err(Chip8Error::Usage("Unknown error policy in the config file! Expected terminate, debug, or skip."))
+                },
+                ("vsync", TomlValue::Boolean(value)) => is_vsync = *value,
+                ("scale_factor", TomlValue::Integer(scale)) if *scale > 0 => window_size = Some(Err(*scale as u32)),
+                ("window_size", TomlValue::Array(size)) => window_size = match size.as_slice() {
+                    [TomlValue::Integer(width), TomlValue::Integer(height)] if *width > 0 && *height > 0 => {
+                        Some(Ok((*width as u32, *height as u32)))
+                    }
+                    _ => return Err(Chip8Error::Usage("Invalid window size in the config file!"))
+                },
+
+                ("quirks.drawsync", TomlValue::Boolean(value)) => is_drawsync = Some(*value),
+                ("quirks.shift", TomlValue::Boolean(value)) => is_shift_quirk = Some(*value),
+                ("quirks.vf_reset", TomlValue::Boolean(value)) => is_vf_reset = Some(*value),
+                ("quirks.index_increment", TomlValue::Boolean(value)) => is_index_increment = Some(*value),
+                ("quirks.sprite_clipping", TomlValue::Boolean(value)) => is_sprite_clipping = Some(*value),
+                ("quirks.jump", TomlValue::Boolean(value)) => is_jump_quirk = Some(*value),
+                ("quirks.key_release", TomlValue::Boolean(value)) => is_key_release = Some(*value),
+                ("quirks.key_tone", TomlValue::Boolean(value)) => is_key_tone = Some(*value),
+
+                // Binds chip8 keys 0 to F to sdl key names, or arrays of them, where keymap2 binds the second machine's keys
+                (name, key_names) if name.starts_with("keymap.") || name.starts_with("keymap2.") => {
+                    let (map, chip8_key) = match name.strip_prefix("keymap2.") {
+                        Some(chip8_key) => (&mut key_map2, chip8_key),
+                        None => (&mut key_map, &name["keymap.".len()..])
+                    };
+                    let chip8_key = match u8::from_str_radix(chip8_key, 16) {
+                        Ok(key) if key < 16 => key as usize,
+                        _ => return Err(Chip8Error::Usage("Invalid chip8 key in the config file's keymap!"))
+                    };
+                    let key_names = match key_names {
+                        TomlValue::String(_) => std::slice::from_ref(key_names),
+                        TomlValue::Array(key_names) => key_names.as_slice(),
+                        _ => return Err(Chip8Error::Usage("Expected a key name or an array of them in the config file's keymap!"))
+                    };
+
+                    // Replaces the chip8 key's default bindings
+                    map.retain(|&(_, mapped_key)| mapped_key != chip8_key);
+                    for key_name in key_names {
+                        let host_key = match key_name {
+                            TomlValue::String(key_name) => host_key(key_name),
+                            _ => None
+                        };
+                        match host_key {
+                            Some(host_key) => map.push((host_key, chip8_key)),
+                            None => return Err(Chip8Error::Usage("Unknown key name in the config file's keymap!"))
+                        }
+                    }
+                }
+                // Binds sdl gamepad button names to chip8 keys, replacing their default bindings
+                #[cfg(feature = "sdl")]
+                (name, TomlValue::Integer(chip8_key)) if name.starts_with("gamepad.") => {
+                    let button = match Button::from_string(&name["gamepad.".len()..]) {
+                        Some(button) => button,
+                        None => return Err(Chip8Error::Usage("Unknown button name in the config file's gamepad map!"))
+                    };
+                    if !(0..16).contains(chip8_key) {
+                        return Err(Chip8Error::Usage("Invalid chip8 key in the config file's gamepad map!"))
+                    }
+                    gamepad_map.retain(|&(mapped_button, _)| mapped_button != button);
+                    gamepad_map.push((button, *chip8_key as usize));
+                }
+                // Builds without sdl have no gamepads
+                #[cfg(not(feature = "sdl"))]
+                (name, TomlValue::Integer(_)) if name.starts_with("gamepad.") => (),
+                _ => return Err(Chip8Error::Usage("Unknown or invalid setting in the config file!"))
+            }
+        }
+
+        for (name, values) in &arguments.options {
+            match *name {
+                "assemble" => assemble_path = Some(values[0].clone()),
+                "autofire" => for key in values[0].split(',') {
+                    match u8::from_str_radix(key.trim(), 16) {
+                        Ok(key) if key < 16 => autofire_keys[key as usize] = true,
+                        _ => return Err(Chip8Error::Usage("Autofire keys aren't chip8 keys from 0 to F separated by commas!"))
+                    }
+                },
+
+                "autofirerate" => match values[0].parse::<u32>() {
+                    Ok(hz) if (1..=30).contains(&hz) => autofire_hz = hz,
+                    _ => return Err(Chip8Error::Usage("Autofire rate is not a number of pulses from 1 to 30 a second!"))
+                },
+
+                "backend" => {
+                    // Reads how instructions are executed
+                    backend = match parse_backend(&values[0]) {
+                        Some(backend) => backend,
+                        None => return Err(Chip8Error::Usage("Unknown backend! Expected interp, or threaded in builds with the threaded feature."))
+                    };
+                }
+
+                "background" => {
                     // Parses background color
-                    match parse_color(&mut args, arg.as_str(), arg_type) {
-                        Ok(color) => background_color = color,
-                        Err(ParseColorError::Missing) => return Err("Background color is missing!"),
-                        Err(ParseColorError::MissingBlue) => return Err("Missing blue value for background!"),
-                        Err(ParseColorError::Invalid) => return Err("Background color is not a number!"),
-                        Err(ParseColorError::InvalidRgb) => return Err("Invalid rgb value for background!"),
-                        Err(ParseColorError::InvalidRed) => return Err("Invalid red value for background!"),
-                        Err(ParseColorError::InvalidGreen) => return Err("Invalid green value for background!"),
-                        Err(ParseColorError::InvalidBlue) => return Err("Invalid blue value for background!")
+                    match parse_color(values) {
+                        Ok(color) => palette[0] = color,
+                        Err(ParseColorError::Invalid) => return Err(Chip8Error::Usage("Background color is not a number!")),
+                        Err(ParseColorError::InvalidRgb) => return Err(Chip8Error::Usage("Invalid rgb value for background!")),
+                        Err(ParseColorError::InvalidRed) => return Err(Chip8Error::Usage("Invalid red value for background!")),
+                        Err(ParseColorError::InvalidGreen) => return Err(Chip8Error::Usage("Invalid green value for background!")),
+                        Err(ParseColorError::InvalidBlue) => return Err(Chip8Error::Usage("Invalid blue value for background!"))
                     };
                 }
 
-                "-c" | "-clock" => {
-                    // Reads clock speed argument with or without a space
-                    match parse_first_number(&mut args, arg.as_str(), arg_type) {
-                        Ok(hz) => clock_per_sec = hz,
-                        Err(ParseError::Missing) => return Err("Clock speed is missing!"),
-                        Err(ParseError::Invalid) => return Err("Clock speed is not a number!")
+                // The command line's clock replaces one from the config file
+                "clock" => {
+                    (clock_per_sec, is_clock_max, is_cosmac_timing) = (None, false, false);
+                    match values[0].as_str() {
+                        "max" => is_clock_max = true,
+                        "cosmac" => is_cosmac_timing = true,
+                        hz => match hz.parse::<u32>() {
+                            Ok(hz) if (1..=MAX_CLOCK_HZ).contains(&hz) => clock_per_sec = Some(hz),
+                            _ => return Err(Chip8Error::Usage("Clock speed is not a number from 1 to 10000000, max, or cosmac!"))
+                        }
                     }
                 }
 
-                "-fg" | "-foreground" => {
+                "compare" => compare_names = Some(values[0].clone()),
+                "confirmquit" => is_confirm_quit = true,
+                "coverage" => coverage_path = Some(values[0].clone()),
+                "debug" => is_debug = true,
+                "disassemble" => is_disassemble = true,
+
+                "display" => match values[0].parse::<usize>() {
+                    Ok(number) if number > 0 => display_number = Some(number),
+                    _ => return Err(Chip8Error::Usage("Display number isn't a whole number from 1!"))
+                },
+
+                "dump-audio" => audio_dump_path = Some(values[0].clone()),
+
+                "escape" => {
+                    // Reads what Escape does in the window
+                    escape_action = match EscapeAction::parse(&values[0]) {
+                        Some(action) => action,
+                        None => return Err(Chip8Error::Usage("Unknown escape action! Expected menu, quit, or fullscreen."))
+                    };
+                }
+
+                "filter" => {
+                    // Reads the name of a post processing filter
+                    display_filter = match DisplayFilter::parse(&values[0]) {
+                        Some(filter) => filter,
+                        None => return Err(Chip8Error::Usage("Unknown filter! Expected none, scanlines, or crt."))
+                    };
+                }
+
+                "foreground" => {
                     // Parses foreground color
-                     match parse_color(&mut args, arg.as_str(), arg_type) {
-                        Ok(color) => foreground_color = color,
-                        Err(ParseColorError::Missing) => return Err("Foreground color is missing!"),
-                        Err(ParseColorError::MissingBlue) => return Err("Missing blue value for foreground!"),
-                        Err(ParseColorError::Invalid) => return Err("Foreground color is not a number!"),
-                        Err(ParseColorError::InvalidRgb) => return Err("Invalid rgb value for foreground!"),
-                        Err(ParseColorError::InvalidRed) => return Err("Invalid red value for foreground!"),
-                        Err(ParseColorError::InvalidGreen) => return Err("Invalid green value for foreground!"),
-                        Err(ParseColorError::InvalidBlue) => return Err("Invalid blue value for foreground!")
+                    match parse_color(values) {
+                        Ok(color) => palette[1] = color,
+                        Err(ParseColorError::Invalid) => return Err(Chip8Error::Usage("Foreground color is not a number!")),
+                        Err(ParseColorError::InvalidRgb) => return Err(Chip8Error::Usage("Invalid rgb value for foreground!")),
+                        Err(ParseColorError::InvalidRed) => return Err(Chip8Error::Usage("Invalid red value for foreground!")),
+                        Err(ParseColorError::InvalidGreen) => return Err(Chip8Error::Usage("Invalid green value for foreground!")),
+                        Err(ParseColorError::InvalidBlue) => return Err(Chip8Error::Usage("Invalid blue value for foreground!"))
+                    };
+                }
+
+                "frontend" => {
+                    // Reads whether to play in a window or the terminal
+                    frontend = match FrontendKind::parse(&values[0]) {
+                        Some(frontend) => frontend,
+                        None => return Err(Chip8Error::Usage("Unknown frontend! Expected sdl or tui."))
+                    };
+                }
+
+                "fullscreen" => {
+                    // Reads whether fullscreen is borderless at the desktop's resolution or exclusive
+                    fullscreen_mode = match FullscreenMode::parse(&values[0]) {
+                        Some(mode) => mode,
+                        None => return Err(Chip8Error::Usage("Unknown fullscreen mode! Expected off, borderless, or exclusive."))
+                    };
+                }
+
+                "gdb" => match values[0].parse::<u16>() {
+                    Ok(port) => gdb_port = Some(port),
+                    Err(_) => return Err(Chip8Error::Usage("Gdb port is not a number from 0 to 65535!"))
+                },
+
+                "hooks" => hooks_path = Some(values[0].clone()),
+
+                "host" => match values[0].parse::<u16>() {
+                    Ok(port) => host_port = Some(port),
+                    Err(_) => return Err(Chip8Error::Usage("Netplay port is not a number from 0 to 65535!"))
+                },
+
+                "inputdelay" => match values[0].parse::<u8>() {
+                    Ok(frames) if frames <= 30 => input_delay = frames,
+                    _ => return Err(Chip8Error::Usage("Input delay is not a number of frames from 0 to 30!"))
+                },
+
+                "join" => join_address = Some(values[0].clone()),
+                "keypress" => is_key_release = Some(false),
+                "lockstep" => is_lockstep = true,
+
+                "movie" => movie_path = Some(values[0].clone()),
+                "nodrawsync" => is_drawsync = Some(false),
+                "novsync" => is_vsync = false,
+                "noyregshift" => is_shift_quirk = Some(false),
+
+                "onerror" => {
+                    // Reads what happens when the program fails
+                    error_policy = match ErrorPolicy::parse(&values[0]) {
+                        Some(policy) => policy,
+                        None => return Err(Chip8Error::Usage("Unknown error policy! Expected terminate, debug, or skip."))
                     };
                 }
 
-                "-fs" | "fullscreen" => is_fullscreen = true,
-
-                "-h" | "-help" => {
-                    print!("\
-                        chip8-emulator <Rom path> <Options>\n\
-                        Options:\n    \
-                        -bg -background    <RGB color> | <Red> <Green> <Blue>  (default: 0, 0, 0)\n    \
-                        -c  -clock         <Cycles per second>                 (default: 500 hz)\n    \
-                        -fg -foreground    <RGB color> | <Red> <Green> <Blue>  (default: 255, 255, 255)\n    \
-                        -fs -fullscreen    Turns on fullscreen mode\n    \
-                        -h  -help          Displays this help message\n        \
-                            -nodrawsync    Turns off the 60hz draw sync\n        \
-                            -noyregshift   Shifts from the x register\n        \
-                            -novsync       Turns off vertical sync\n    \
-                        -sf -scalefactor   <Scale factor of 64x32 screen>\n    \
-                        -w  -windowsize    <Pixel width> <Pixel height>\
-                    ");
-                    return Err("")
+                "profile" => {
+                    // Reads the name of a compatibility profile
+                    profile = match Profile::parse(&values[0]) {
+                        Some(profile) => profile,
+                        None => return Err(Chip8Error::Usage("Unknown profile! Expected chip8, vip, schip, or xochip."))
+                    };
+                    is_profile_set = true;
                 }
 
-                "-nodrawsync" => is_drawsync = false,
-                "-novsync" => is_vsync = false,
-                "-noyregshift" => is_shift_quirk = false,
-                
-                "-sf" | "-scalefactor" => {
-                    // Reads scale factor argument with or without a space
-                    match parse_first_number(&mut args, arg.as_str(), arg_type) {
-                        Ok(scale) => window_size = Some(Err(scale)),
-                        Err(ParseError::Missing) => return Err("Scale factor is missing!"),
-                        Err(ParseError::Invalid) => return Err("Scale factor is not a number!")
+                "profile-out" => profile_path = Some(values[0].clone()),
+
+                "palette" if !parse_palette(&values[0], &mut palette) => {
+                    return Err(Chip8Error::Usage("Invalid palette! Expected a name or up to 16 comma separated hex colors."))
+                }
+
+                "phosphor" => is_phosphor = true,
+                "playback" => playback_path = Some(values[0].clone()),
+                "record" => is_record = true,
+                "record-video" => video_path = Some(values[0].clone()),
+                "rnglog" => random_log_path = Some(values[0].clone()),
+                "rngreplay" => random_replay_path = Some(values[0].clone()),
+
+                "scaling" => {
+                    // Reads how the display is scaled to the window
+                    scaling_mode = match ScalingMode::parse(&values[0]) {
+                        Some(mode) => mode,
+                        None => return Err(Chip8Error::Usage("Unknown scaling mode! Expected integer, fit, or stretch."))
+                    };
+                }
+
+                "scalefactor" => match values[0].parse::<u32>() {
+                    Ok(scale) if scale > 0 => window_size = Some(Err(scale)),
+                    _ => return Err(Chip8Error::Usage("Scale factor is not a positive number!"))
+                },
+
+                "permissive" => is_permissive_memory = true,
+
+                "reserved" => reserved_size = match parse_number(&values[0]) {
+                    Some(size) => Some(size),
+                    None => return Err(Chip8Error::Usage("Reserved memory is not a number of bytes!"))
+                },
+
+                "rom2" => rom2_path = Some(values[0].clone()),
+                "romicon" => is_rom_icon = true,
+
+                "smooth" => is_smooth = true,
+
+                "start" => start_address = match parse_number(&values[0]).and_then(|address| u16::try_from(address).ok()) {
+                    Some(address) => address,
+                    None => return Err(Chip8Error::Usage("Start address is not an address from 0 to 0xFFFF!"))
+                },
+
+                "symbols" => symbols_path = Some(values[0].clone()),
+
+                "stack" => stack_depth = match values[0].as_str() {
+                    "unlimited" => None,
+                    levels => match levels.parse::<usize>() {
+                        Ok(levels) if levels > 0 => Some(levels),
+                        _ => return Err(Chip8Error::Usage("Stack depth is not a positive number or unlimited!"))
                     }
+                },
+
+                "tone" => match values[0].parse::<f32>() {
+                    Ok(hz) if (20.0..=20000.0).contains(&hz) => tone_hz = hz,
+                    _ => return Err(Chip8Error::Usage("Tone is not a frequency from 20 to 20000 hz!"))
+                },
+                "twoplayer" => is_two_player = true,
+
+                "unfocused" => {
+                    // Reads what happens while the window isn't focused
+                    focus_policy = match FocusPolicy::parse(&values[0]) {
+                        Some(policy) => policy,
+                        None => return Err(Chip8Error::Usage("Unknown unfocused behavior! Expected run, throttle, or pause."))
+                    };
                 }
 
-                "-w" | "-windowsize" => {
-                    // Reads window width argument with or without a space
-                    let mut size = (0, 0);
-                    size.0 = match parse_first_number(&mut args, arg.as_str(), arg_type) {
-                        Ok(width) => width,
-                        Err(ParseError::Missing) => return Err("Window width is missing!"),
-                        Err(ParseError::Invalid) => return Err("Window width is not a number!")
+                "volume" => match values[0].parse::<u8>() {
+                    Ok(value) if value <= 100 => volume = value,
+                    _ => return Err(Chip8Error::Usage("Volume is not a number from 0 to 100!"))
+                },
+
+                "wave" => {
+                    // Reads the shape of the buzzer's tone
+                    waveform = match Waveform::parse(&values[0]) {
+                        Some(waveform) => waveform,
+                        None => return Err(Chip8Error::Usage("Unknown wave! Expected square, triangle, sine, or noise."))
                     };
+                }
 
-                    // Parses the next argument as the window height
-                    size.1 = match parse_next_number(&mut args) {
+                "windowsize" => {
+                    // Reads window width and height
+                    let width = match values[0].parse::<u32>() {
+                        Ok(width) => width,
+                        Err(_) => return Err(Chip8Error::Usage("Window width is not a number!"))
+                    };
+                    let height = match values[1].parse::<u32>() {
                         Ok(height) => height,
-                        Err(ParseError::Missing) => return Err("Window height is missing!"),
-                        Err(ParseError::Invalid) => return Err("Window height is not a number!")
+                        Err(_) => return Err(Chip8Error::Usage("Window height is not a number!"))
                     };
-                    window_size = Some(Ok(size));
+                    window_size = Some(Ok((width, height)));
                 }
 
-                // Accepts at most one rom path
-                _ => match rom_path.as_str() {
-                    "" => rom_path = arg,
-                    _ => return Err("More than one rom paths found!")
-                }
+                "watch" => is_watch = true,
+
+                // Help and the test runner are handled before configuration
+                _ => ()
             }
         }
 
-        // Tries through a gui if the command line fails to find a rom path
-        if rom_path == "" {
-            // Initializes channels because file dialogs are asynchronous
-            let (sender, receiver) = mpsc::channel::<PathBuf>();
-
-            let dialog_callback: DialogCallback = Box::new(move |paths, _| {
-                // Reads the rom path from dialog if provided
-                let rom = match paths {
-                    Ok(paths) => paths[0].clone(),
-                    Err(_) => PathBuf::from("")
-                };
-
-                // Sends the rom path to the main thread
-                if sender.send(rom).is_err() {
-                    println!("Failed to send rom path from dialog!")
-                }
-            });
+        // Accepts at most one rom path
+        match arguments.positionals.as_slice() {
+            [] => (),
+            [path] => rom_path = path.clone(),
+            _ => return Err(Chip8Error::Usage("More than one rom paths found!"))
+        }
 
-            // Calls the asynchronous open file dialog
-            if show_open_file_dialog(&[], None::<&str>, false, Some(window), dialog_callback).is_err() {
-                return Err("Failed to open file dialog!")
+        if host_port.is_some() && join_address.is_some() {
+            return Err(Chip8Error::Usage("Netplay can't host and join at the same time!"))
+        }
+        if is_lockstep && host_port.is_none() && join_address.is_none() {
+            return Err(Chip8Error::Usage("Lockstep needs -host or -join!"))
+        }
+        if rom2_path.is_some() && compare_names.is_some() {
+            return Err(Chip8Error::Usage("Quirk comparison runs the first rom, so it can't be used with a second rom!"))
+        }
+        if rom2_path.is_some() || compare_names.is_some() {
+            if rom_path.is_empty() { return Err(Chip8Error::Usage("A second machine needs a rom path!")) }
+            if frontend == FrontendKind::Tui || host_port.is_some() || join_address.is_some() || movie_path.is_some() || playback_path.is_some() {
+                return Err(Chip8Error::Usage("A second rom can't be used with the terminal, netplay, or movies!"))
             }
+            if is_two_player { return Err(Chip8Error::Usage("The second player's keys belong to the second machine!")) }
+        }
 
-            // Receives the rom path from the open file dialog
-            rom_path = loop {
-                // Pumps events so the dialog can function
-                event_pump.pump_events();
-                match receiver.try_recv() {
-                    Ok(path) => match path.into_os_string().into_string() {
-                        Ok(path) => break path,
-                        Err(_) => return Err("Failed to receive rom path from dialog!")
-                    }
-                    // sleeping prevents a spin lock
-                    Err(_) => sleep(Duration::from_millis(1))
-                };
-            };
-
-            // Terminates without a rom path
-            if rom_path == "" {
-                return Err("Missing path to the rom!")
-            }
+        // The second player's keys also press the first machine's keys
+        if is_two_player { key_map.extend(key_map2.iter().copied()); }
+
+        // The budget follows the host's speed, so runs that must match another machine need a fixed clock
+        if is_clock_max && (frontend == FrontendKind::Tui || host_port.is_some() || join_address.is_some() || movie_path.is_some()
+            || playback_path.is_some() || rom2_path.is_some() || compare_names.is_some()) {
+            return Err(Chip8Error::Usage("The max clock can't be used with the terminal, netplay, movies, or a second machine!"))
         }
 
-        Ok(Chip8Configuration{rom_path, clock_hz: clock_per_sec, background_color, foreground_color, window_size,
-            is_fullscreen, is_drawsync, is_shift_quirk, is_vsync})
+        // Individual options override the quirks and clock speed of the profile
+        let is_platform_set = is_profile_set || clock_per_sec.is_some() || is_cosmac_timing || [is_drawsync, is_shift_quirk, is_vf_reset,
+            is_index_increment, is_sprite_clipping, is_jump_quirk, is_key_release, is_key_tone].iter().any(Option::is_some);
+        let mut quirks = profile.quirks();
+        quirks.is_drawsync = is_drawsync.unwrap_or(quirks.is_drawsync);
+        quirks.is_shift_quirk = is_shift_quirk.unwrap_or(quirks.is_shift_quirk);
+        quirks.is_vf_reset = is_vf_reset.unwrap_or(quirks.is_vf_reset);
+        quirks.is_index_increment = is_index_increment.unwrap_or(quirks.is_index_increment);
+        quirks.is_sprite_clipping = is_sprite_clipping.unwrap_or(quirks.is_sprite_clipping);
+        quirks.is_jump_quirk = is_jump_quirk.unwrap_or(quirks.is_jump_quirk);
+        quirks.is_key_release = is_key_release.unwrap_or(quirks.is_key_release);
+        quirks.is_key_tone = is_key_tone.unwrap_or(quirks.is_key_tone);
+        let clock_hz = clock_per_sec.unwrap_or(profile.clock_hz());
+
+        // Checks the compared quirk names before any window opens
+        if let Some(names) = &compare_names { flip_quirks(quirks, names)?; }
+
+        let is_reserved_set = reserved_size.is_some();
+        let options = Chip8Options{clock_hz, profile, quirks, is_cosmac_timing, stack_depth, reserved_size: reserved_size.unwrap_or(profile.reserved_size()),
+            start_address, is_permissive_memory, seed: None};
+        check_memory_layout(&options)?;
+        Ok(Chip8Configuration{rom_path, window_size, fullscreen_mode, display_number, is_vsync, is_debug, is_disassemble, assemble_path, frontend, backend, is_clock_max, escape_action, is_confirm_quit,
+            error_policy, focus_policy, is_watch, is_record, is_phosphor, is_rom_icon, display_filter, scaling_mode, is_smooth, volume, tone_hz, waveform, is_platform_set, is_reserved_set, gdb_port,
+            host_port, join_address, is_lockstep, input_delay, movie_path, playback_path, hooks_path, symbols_path, profile_path, coverage_path,
+            random_log_path, random_replay_path, audio_dump_path, video_path, palette, key_map, rom2_path, key_map2,
+            compare_names, #[cfg(feature = "sdl")] gamepad_map, autofire_keys, autofire_hz, options})
     }
 }
 
-enum ParseError {
-    Missing,
-    Invalid
+// Binds each chip8 key to one host key
+fn key_bindings(host_keys: [HostKey; 16]) -> Vec<(HostKey, usize)> {
+    host_keys.into_iter().enumerate().map(|(chip8_key, host_key)| (host_key, chip8_key)).collect()
 }
 
-fn parse_first_number<I: Iterator<Item = String>>(args: &mut I, arg: &str, arg_type: &str) -> Result<u32, ParseError> {
-    // Reads number with or without a space
-    let value = match arg.len() == arg_type.len() {
-        false => String::from(&arg[arg_type.len()..]),
-        true => match args.next() {
-            Some(arg) => arg,
-            None => return Err(ParseError::Missing)
+// Finds a host key by its sdl name, where builds without sdl only know the single characters a terminal types
+fn host_key(name: &str) -> Option<HostKey> {
+    #[cfg(feature = "sdl")]
+    return Keycode::from_name(name);
+    #[cfg(not(feature = "sdl"))]
+    {
+        let mut characters = name.chars();
+        match (characters.next(), characters.next()) {
+            (Some(character), None) => Some(character.to_ascii_lowercase()),
+            _ => None
         }
-    };
+    }
+}
+
+// The host key of a character typed into the terminal
+#[cfg(feature = "tui")]
+pub fn typed_key(character: char) -> Option<HostKey> {
+    #[cfg(feature = "sdl")]
+    return Keycode::from_i32(character.to_ascii_lowercase() as i32);
+    #[cfg(not(feature = "sdl"))]
+    Some(character.to_ascii_lowercase())
+}
+
+// Checks that the reserved end of the profile's ram and the start address leave room for a rom
+pub fn check_memory_layout(options: &Chip8Options) -> Result<(), Chip8Error> {
+    // Programs load at 0x200, so the reserved end of ram can't reach it
+    let memory_size = options.profile.memory_size();
+    if options.reserved_size > memory_size - 0x200 {
+        return Err(Chip8Error::Usage("Reserved memory leaves no room for a rom!"))
+    }
+    if options.start_address as usize >= memory_size - options.reserved_size {
+        return Err(Chip8Error::Usage("Start address is past the end of ram!"))
+    }
+    Ok(())
+}
+
+// Reads a decimal number, or a hexadecimal one starting with 0x
+fn parse_number(text: &str) -> Option<usize> {
+    match text.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse::<usize>().ok()
+    }
+}
 
-    // Converts from a string to a number
-    match value.parse::<u32>() {
-        Ok(arg) => Ok(arg),
-        Err(_) => return Err(ParseError::Invalid)
+// Reads a backend name, where the threaded backend only exists in builds with the threaded feature
+fn parse_backend(name: &str) -> Option<Backend> {
+    match name {
+        "interp" => Some(Backend::Interpreter),
+        #[cfg(feature = "threaded")]
+        "threaded" => Some(Backend::Threaded),
+        _ => None
     }
 }
 
-fn parse_next_number<I: Iterator<Item = String>>(args: &mut Peekable<I>) -> Result<u32, ParseError> {
-    match args.peek() {
-        Some(value) => match value.parse::<u32>() {
-            Ok(arg) => {
-                args.next();
-                Ok(arg)
+// Flips the quirks named in a comma separated list, for the second machine of a comparison
+pub fn flip_quirks(quirks: Quirks, names: &str) -> Result<Quirks, Chip8Error> {
+    let mut flipped = quirks;
+    for name in names.split(',') {
+        let quirk = match name.trim() {
+            "drawsync" => &mut flipped.is_drawsync,
+            "shift" => &mut flipped.is_shift_quirk,
+            "vf_reset" => &mut flipped.is_vf_reset,
+            "index_increment" => &mut flipped.is_index_increment,
+            "sprite_clipping" => &mut flipped.is_sprite_clipping,
+            "jump" => &mut flipped.is_jump_quirk,
+            "key_release" => &mut flipped.is_key_release,
+            "key_tone" => &mut flipped.is_key_tone,
+            _ => return Err(Chip8Error::Usage("Unknown quirk to compare! Expected drawsync, shift, vf_reset, index_increment, sprite_clipping, jump, \
+                key_release, or key_tone."))
+        };
+        *quirk = !*quirk;
+    }
+    Ok(flipped)
+}
+
+// Reads a color from a 0xRRGGBB integer or an array of red, green, and blue
+fn toml_color(value: &TomlValue) -> Option<u32> {
+    match value {
+        TomlValue::Integer(rgb) if (0..=0xFFFFFF).contains(rgb) => Some(0xFF000000 | *rgb as u32),
+        TomlValue::Array(hues) => match hues.as_slice() {
+            [TomlValue::Integer(red), TomlValue::Integer(green), TomlValue::Integer(blue)] => {
+                let hues = [u8::try_from(*blue).ok()?, u8::try_from(*green).ok()?, u8::try_from(*red).ok()?, 0xFF];
+                Some(u32::from_ne_bytes(hues))
             }
-            Err(_) => Err(ParseError::Invalid)
+            _ => None
         }
-        None => Err(ParseError::Missing)
+        _ => None
+    }
+}
+
+// Reads a named palette, or hex colors for the first entries of the palette such as "000000,FFCC00"
+fn parse_palette(text: &str, palette: &mut Palette) -> bool {
+    if let Some(named) = named_palette(text) {
+        *palette = named;
+        return true
+    }
+
+    let colors: Vec<&str> = text.split(',').collect();
+    if colors.len() > palette.len() { return false }
+    for (entry, color) in palette.iter_mut().zip(colors) {
+        let color = color.trim().trim_start_matches('#').trim_start_matches("0x");
+        *entry = match u32::from_str_radix(color, 16) {
+            Ok(rgb) if rgb <= 0xFFFFFF => 0xFF000000 | rgb,
+            _ => return false
+        };
     }
+    true
 }
 
 enum ParseColorError {
-    Missing,
-    MissingBlue,
     Invalid,
     InvalidRgb,
     InvalidRed,
@@ -226,36 +752,31 @@ enum ParseColorError {
     InvalidBlue,
 }
 
-fn parse_color<I: Iterator<Item = String>>(args: &mut Peekable<I>, arg: &str, arg_type: &str) -> Result<u32, ParseColorError> {
-    // Reads color argument with or without a space
-    let red = match parse_first_number(args, arg, arg_type) {
-        Ok(hue) => hue,
-        Err(ParseError::Missing) => return Err(ParseColorError::Missing),
-        Err(ParseError::Invalid) => return Err(ParseColorError::Invalid),
-    };
-
-    // Parses color arguments as the green and blue values
-    let green = parse_next_number(args);
-    let blue = parse_next_number(args);
-
-    // Sets color from an rgb value or 3 r, g, and b values
-    return match (green, blue) {
-        (Err(_), Err(_)) => {
-            // Terminates if the rgb value has an alpha value
-            if red > 0xFFFFFF { return Err(ParseColorError::InvalidRgb) }
-            Ok(0xFF000000 | red)
+// Reads a color from one rgb value, or from red, green, and blue values
+fn parse_color(values: &[String]) -> Result<u32, ParseColorError> {
+    let mut hues = Vec::with_capacity(values.len());
+    for value in values {
+        match value.parse::<u32>() {
+            Ok(hue) => hues.push(hue),
+            Err(_) => return Err(ParseColorError::Invalid)
         }
+    }
 
-        (Ok(_), Err(_)) => Err(ParseColorError::MissingBlue),
-        (Err(_), Ok(_)) => Ok(0),
+    match *hues.as_slice() {
+        // Terminates if the rgb value has an alpha value
+        [rgb] => match rgb {
+            0..=0xFFFFFF => Ok(0xFF000000 | rgb),
+            _ => Err(ParseColorError::InvalidRgb)
+        }
 
-        (Ok(green), Ok(blue)) => {
-            // Terminates if any color exceeds the byte limit
+        // Terminates if any color exceeds the byte limit
+        [red, green, blue] => {
             if red > 0xFF { return Err(ParseColorError::InvalidRed) }
             if green > 0xFF { return Err(ParseColorError::InvalidGreen) }
             if blue > 0xFF { return Err(ParseColorError::InvalidBlue) }
 
             Ok(u32::from_ne_bytes([blue as u8, green as u8, red as u8, 0xFF]))
         }
-    };
-}
\ No newline at end of file
+        _ => Err(ParseColorError::Invalid)
+    }
+}
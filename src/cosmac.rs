@@ -0,0 +1,58 @@
+use crate::decode::Instruction;
+
+// The VIP's 1802 runs at 1.76064 MHz with 8 clock cycles to a machine cycle, giving 3668 machine cycles a frame
+pub(crate) const MACHINE_CYCLE_NANOS: f32 = 8.0 * 1000000000.0 / 1760640.0;
+
+// Every frame, DMA reads 128 scanlines of 8 bytes and the interrupt routine decrements the timers
+pub(crate) const DISPLAY_CYCLES: u32 = 1024 + 46;
+
+// Skip instructions take a little longer when they skip
+pub(crate) const SKIP_CYCLES: u32 = 4;
+
+// The interpreter's loop fetches and dispatches every instruction before running it
+const FETCH_CYCLES: u32 = 40;
+
+// Machine cycles the VIP interpreter takes for an instruction with the registers before it runs, including the fetch
+// Instructions the VIP doesn't have cost only the fetch
+pub(crate) fn instruction_cycles(instruction: Instruction, registers: &[u8; 16], display_height: u32) -> u32 {
+    FETCH_CYCLES + match instruction {
+        Instruction::Cls => 3078,
+        Instruction::Ret => 10,
+        Instruction::Jp{..} => 12,
+        Instruction::Call{..} => 26,
+        Instruction::SeByte{..} | Instruction::SneByte{..} => 10,
+        Instruction::Se{..} | Instruction::Sne{..} | Instruction::Skp{..} | Instruction::Sknp{..} => 14,
+        Instruction::LdByte{..} => 6,
+        Instruction::AddByte{..} => 10,
+        Instruction::Ld{..} | Instruction::Or{..} | Instruction::And{..} | Instruction::Xor{..} | Instruction::Add{..}
+            | Instruction::Sub{..} | Instruction::Shr{..} | Instruction::Subn{..} | Instruction::Shl{..} => 44,
+        Instruction::LdIndex{..} => 12,
+
+        // Crossing into the next page carries into the high byte
+        Instruction::JpOffset{nnn, ..} => match (nnn & 0xFF) + registers[0] as u16 > 0xFF {
+            true => 24,
+            false => 22
+        },
+        Instruction::Rnd{..} => 36,
+
+        // Each row is shifted into place a bit at a time, and a sprite that straddles two bytes writes both
+        // Rows clipped at the bottom of the display aren't drawn
+        Instruction::Drw{x, y, n} => {
+            let shift = registers[x as usize] as u32 % 8;
+            let rows = (n as u32).min(display_height - registers[y as usize] as u32 % display_height);
+            let row_cycles = 34 + shift * 4 + match shift { 0 => 0, _ => 12 };
+            68 + rows * row_cycles
+        }
+        Instruction::LdDelay{..} | Instruction::SetDelay{..} | Instruction::SetSound{..} => 10,
+        Instruction::LdKey{..} => 19,
+        Instruction::AddIndex{..} | Instruction::LdFont{..} => 16,
+
+        // Digits are found by repeated subtraction, so larger digits take longer
+        Instruction::LdBcd{x} => {
+            let value = registers[x as usize] as u32;
+            80 + (value / 100 + value / 10 % 10 + value % 10) * 16
+        }
+        Instruction::Store{x} | Instruction::Load{x} => 14 + (x as u32 + 1) * 14,
+        Instruction::Exit | Instruction::Sys | Instruction::SaveFlags{..} | Instruction::LoadFlags{..} | Instruction::Unsupported => 0
+    }
+}
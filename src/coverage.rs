@@ -0,0 +1,54 @@
+use chip8_emulator::{Chip8, COVERAGE_EXECUTED, COVERAGE_READ, COVERAGE_WRITTEN};
+
+// Bytes shown on each line of a coverage map
+const MAP_ROW_LENGTH: usize = 64;
+
+// Writes a map of how each ram byte was used, covering the whole rom and any other rows the program touched
+pub fn write_map(chip8: &Chip8, path: &str) -> Result<(), &'static str> {
+    let coverage = chip8.coverage();
+    let rom = chip8.rom_range();
+    let mut text = String::from("# x executed, r read, w written, b read and written, * executed and used as data, . unused\n");
+    text += &format!("# {}\n", summary(chip8));
+    for row in (0..coverage.len()).step_by(MAP_ROW_LENGTH) {
+        let flags = &coverage[row..(row + MAP_ROW_LENGTH).min(coverage.len())];
+        let is_rom = row < rom.end && row + MAP_ROW_LENGTH > rom.start;
+        if !is_rom && flags.iter().all(|&flags| flags == 0) { continue }
+        let line: String = flags.iter().map(|&flags| flag_character(flags)).collect();
+        text += &format!("0x{row:04X} {line}\n");
+    }
+
+    match std::fs::write(path, text) {
+        Ok(_) => {
+            println!("Saved the coverage map to {path}");
+            Ok(())
+        }
+        Err(_) => Err("Failed to write the coverage map!")
+    }
+}
+
+// Counts how much of the rom ran as code or was used as data, and what the program touched outside it
+pub fn summary(chip8: &Chip8) -> String {
+    let coverage = chip8.coverage();
+    let rom = chip8.rom_range();
+    let count = |range: &[u8], flag: u8| range.iter().filter(|&&flags| flags & flag != 0).count();
+    let rom_flags = coverage.get(rom.clone()).unwrap_or(&[]);
+    let unused = rom_flags.iter().filter(|&&flags| flags == 0).count();
+    let outside: Vec<u8> = coverage.iter().enumerate().filter(|(address, _)| !rom.contains(address)).map(|(_, &flags)| flags).collect();
+    format!("Rom 0x{:03X}-0x{:03X}: {} bytes executed, {} read, {} written, {unused} unused. Outside the rom: {} read, {} written",
+        rom.start, rom.end.saturating_sub(1), count(rom_flags, COVERAGE_EXECUTED), count(rom_flags, COVERAGE_READ),
+        count(rom_flags, COVERAGE_WRITTEN), count(&outside, COVERAGE_READ), count(&outside, COVERAGE_WRITTEN))
+}
+
+fn flag_character(flags: u8) -> char {
+    let is_data = flags & (COVERAGE_READ | COVERAGE_WRITTEN) != 0;
+    match (flags & COVERAGE_EXECUTED != 0, is_data) {
+        (true, true) => '*',
+        (true, false) => 'x',
+        (false, false) => '.',
+        (false, true) => match flags & COVERAGE_WRITTEN != 0 {
+            true if flags & COVERAGE_READ != 0 => 'b',
+            true => 'w',
+            false => 'r'
+        }
+    }
+}
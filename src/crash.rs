@@ -0,0 +1,49 @@
+use chip8_emulator::{disassemble, Chip8, Chip8Error, Symbols};
+use crate::{debugger::format_state, timestamped_path};
+
+// Bytes of instructions shown on each side of the program counter
+const DISASSEMBLY_WINDOW: usize = 16;
+
+// Writes a crash report after the machine stops on an error, where the program counter is still on the failing instruction
+// Returns the error so it can be passed on
+pub fn report(chip8: &Chip8, error: Chip8Error) -> Chip8Error {
+    let memory = chip8.memory();
+    let pc = chip8.pc() as usize;
+    let opcode = match memory.get(pc..pc + 2) {
+        Some(bytes) => format!("0x{:02X}{:02X}", bytes[0], bytes[1]),
+        None => String::from("outside ram")
+    };
+
+    let mut text = format!("{error}\nOpcode {opcode} at 0x{pc:03X}, instruction count {}\n\n", chip8.instruction_count());
+    text += &format_state(chip8, &Symbols::default());
+
+    // Disassembles from a little before the program counter, marking the failing instruction
+    text += "\nDisassembly\n";
+    let mut address = pc.saturating_sub(DISASSEMBLY_WINDOW) & !1;
+    while address <= pc + DISASSEMBLY_WINDOW && address < memory.len() {
+        let (mnemonic, length) = disassemble(&memory[address..], chip8.profile());
+        let marker = match address == pc { true => ">", false => " " };
+        text += &format!("{marker} 0x{address:03X}  {mnemonic}\n");
+        address += length.max(1);
+    }
+
+    text += "\nRam\n";
+    for (row, bytes) in memory.chunks(16).enumerate() {
+        let bytes: Vec<String> = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+        text += &format!("0x{:04X} {}\n", row * 16, bytes.join(" "));
+    }
+
+    let path = timestamped_path("crash", "txt");
+    match std::fs::write(&path, text) {
+        Ok(_) => println!("Saved a crash report to {path}"),
+        Err(_) => println!("Failed to write the crash report!")
+    }
+    error
+}
+
+// Moves past the failing instruction so the program carries on, unless the program counter itself is invalid
+pub fn skip_instruction(chip8: &mut Chip8, error: &Chip8Error) -> bool {
+    if matches!(error, Chip8Error::InvalidProgramCounter{..}) { return false }
+    chip8.set_pc(chip8.pc().wrapping_add(2));
+    true
+}
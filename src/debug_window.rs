@@ -0,0 +1,458 @@
+use sdl3::{hint::names::RENDER_VSYNC, keyboard::Keycode, pixels::Color, render::{BlendMode, Canvas, FRect},
+    sys::render::SDL_LOGICAL_PRESENTATION_LETTERBOX, video::Window, VideoSubsystem};
+use chip8_emulator::{disassemble_with_symbols, Chip8, Chip8Error, Palette, Symbols, COVERAGE_EXECUTED, COVERAGE_READ, COVERAGE_WRITTEN};
+use crate::osd::{draw_text, FONT_PIXEL, GLYPH_WIDTH, LINE_HEIGHT};
+
+const COLUMN_PIXELS: u32 = 8; // Screen pixels in a column of text at the window's starting size
+const LISTED_INSTRUCTIONS: usize = 12;
+const MEMORY_ROW_LENGTH: usize = 16;
+const MEMORY_ROWS: usize = 16;
+const SPRITE_COLUMNS: usize = 8;
+const SPRITE_LINES: usize = 40; // Lines of text the sprite window's height fits
+const SPRITE_PIXEL: f32 = 3.0 * FONT_PIXEL;
+const SPRITE_CELL_WIDTH: f32 = 7.0 * GLYPH_WIDTH; // Fits an address label above each sprite
+const MAX_SPRITE_HEIGHT: usize = 16;
+const PALETTE_SWATCH_COLUMN: usize = 12;
+const PC_COLOR: Color = Color::RGBA(255, 160, 0, 120);
+const INDEX_COLOR: Color = Color::RGBA(0, 160, 255, 120);
+const COVERAGE_COLORS: [(u8, &str, Color); 3] = [      // Underlines bytes in thirds while coverage is recorded
+    (COVERAGE_EXECUTED, "EXEC", Color::RGB(0, 200, 0)),
+    (COVERAGE_READ, "READ", Color::RGB(0, 160, 255)),
+    (COVERAGE_WRITTEN, "WRITE", Color::RGB(255, 60, 60))
+];
+const SPRITE_BACKGROUND_COLOR: Color = Color::RGB(40, 40, 40); // Shows the bounds of each sprite
+
+// Views of the machine that open in their own windows beside the display
+#[derive(Clone, Copy, PartialEq)]
+pub enum DebugView {
+    Registers, // Registers, timers, the stack, and the instructions from the program counter
+    Memory,    // A page of ram scrolled with the arrow keys, page keys, and mouse wheel
+    Sprites,   // Ram drawn as 8xN sprites from a scrollable address, to find graphics data
+    Palette    // Colors of the 16 palette entries, edited a channel at a time and applied right away
+}
+
+impl DebugView {
+    fn title(self) -> &'static str {
+        match self {
+            DebugView::Registers => "chip8-emulator — Registers",
+            DebugView::Memory => "chip8-emulator — Memory",
+            DebugView::Sprites => "chip8-emulator — Sprites",
+            DebugView::Palette => "chip8-emulator — Palette"
+        }
+    }
+
+    // Columns and lines of text the window is sized for
+    fn text_size(self) -> (usize, usize) {
+        match self {
+            DebugView::Registers => (62, LISTED_INSTRUCTIONS + 5),
+            DebugView::Memory => (6 + MEMORY_ROW_LENGTH * 3, MEMORY_ROWS + 2),
+            DebugView::Sprites => (SPRITE_COLUMNS * 7, SPRITE_LINES),
+            DebugView::Palette => (PALETTE_SWATCH_COLUMN + 4, 19)
+        }
+    }
+
+    // Keys the view handles itself while its window is focused
+    fn handles_key(self, keycode: Keycode) -> bool {
+        match self {
+            DebugView::Registers => false,
+            DebugView::Memory => matches!(keycode, Keycode::Up | Keycode::Down | Keycode::PageUp | Keycode::PageDown
+                | Keycode::Home | Keycode::End),
+            DebugView::Sprites => matches!(keycode, Keycode::Up | Keycode::Down | Keycode::Left | Keycode::Right
+                | Keycode::PageUp | Keycode::PageDown | Keycode::Home | Keycode::LeftBracket | Keycode::RightBracket),
+            DebugView::Palette => matches!(keycode, Keycode::Up | Keycode::Down | Keycode::Left | Keycode::Right
+                | Keycode::PageUp | Keycode::PageDown | Keycode::LeftBracket | Keycode::RightBracket | Keycode::Return)
+        }
+    }
+}
+
+struct DebugWindow {
+    view: DebugView,
+    canvas: Canvas<Window>,
+    address: usize,      // First address shown by the memory and sprite views
+    sprite_height: usize, // Rows of each sprite in the sprite view
+    palette_entry: usize,
+    palette_channel: usize // Red, green, or blue of the selected entry
+}
+
+// Auxiliary windows with their own renderers, redrawn after the main display every frame
+pub struct DebugWindows {
+    video: VideoSubsystem,
+    windows: Vec<DebugWindow>
+}
+
+impl DebugWindows {
+    pub fn new(video: VideoSubsystem) -> DebugWindows {
+        DebugWindows{video, windows: Vec::new()}
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.windows.is_empty()
+    }
+
+    // Opens a view's window, or closes it when it's already open
+    pub fn toggle(&mut self, view: DebugView, chip8: &Chip8) -> Result<(), Chip8Error> {
+        if let Some(index) = self.windows.iter().position(|window| window.view == view) {
+            self.windows.remove(index);
+            return Ok(())
+        }
+
+        // Sizes the text in whole logical units, one per column
+        let (columns, lines) = view.text_size();
+        let logical_width = columns as u32 + 1;
+        let logical_height = (lines as f32 * LINE_HEIGHT + FONT_PIXEL).ceil() as u32;
+        let window = match self.video.window(view.title(), logical_width * COLUMN_PIXELS, logical_height * COLUMN_PIXELS)
+        .resizable().build() {
+            Ok(window) => window,
+            Err(error) => return Err(Chip8Error::sdl("Failed to initialize debug window!", error))
+        };
+
+        // Only the main window waits for vsync, so presenting the debug windows doesn't slow the frame rate
+        sdl3::hint::set(RENDER_VSYNC, "0");
+        let mut canvas = window.into_canvas();
+        if let Err(error) = canvas.set_logical_size(logical_width, logical_height, SDL_LOGICAL_PRESENTATION_LETTERBOX) {
+            return Err(Chip8Error::sdl("Failed to set logical size!", error))
+        }
+        // Memory starts at the program counter, and sprites at the index register where programs point at graphics
+        let address = match view {
+            DebugView::Sprites => chip8.index() as usize,
+            _ => clamp_memory_address(chip8, chip8.pc() as i64 & !0xF)
+        };
+        self.windows.push(DebugWindow{view, canvas, address, sprite_height: 8, palette_entry: 0, palette_channel: 0});
+        Ok(())
+    }
+
+    // Closes a debug window by its id, returning false when the id belongs to another window
+    pub fn close(&mut self, window_id: u32) -> bool {
+        let length = self.windows.len();
+        self.windows.retain(|window| window.canvas.window().id() != window_id);
+        self.windows.len() != length
+    }
+
+    // Whether a debug window handles a key instead of the machine and hotkeys
+    pub fn handles_key(&self, window_id: u32, keycode: Keycode) -> bool {
+        self.windows.iter().any(|window| window.canvas.window().id() == window_id && window.view.handles_key(keycode))
+    }
+
+    // Scrolls the memory view by rows or pages, or jumps to the program counter with Home and the index register with End
+    // The sprite view scrolls by rows of sprites, pages, or single bytes with Left and Right, jumps to the index register
+    // with Home, and changes the sprite height with the brackets
+    // The palette view selects an entry and channel with the arrow keys, changes the channel by 16 with the page keys
+    // or by 1 with the brackets, and prints the palette as a command line option and config line with Return
+    // Returns whether the palette changed
+    pub fn press(&mut self, window_id: u32, keycode: Keycode, chip8: &Chip8, palette: &mut Palette) -> bool {
+        let Some(window) = self.windows.iter_mut().find(|window| window.canvas.window().id() == window_id) else { return false };
+        let address = window.address as i64;
+        match window.view {
+            DebugView::Registers => (),
+            DebugView::Palette => {
+                let step = match keycode {
+                    Keycode::Up => {
+                        window.palette_entry = (window.palette_entry + palette.len() - 1) % palette.len();
+                        0
+                    }
+                    Keycode::Down => {
+                        window.palette_entry = (window.palette_entry + 1) % palette.len();
+                        0
+                    }
+                    Keycode::Left => {
+                        window.palette_channel = (window.palette_channel + 2) % 3;
+                        0
+                    }
+                    Keycode::Right => {
+                        window.palette_channel = (window.palette_channel + 1) % 3;
+                        0
+                    }
+                    Keycode::PageUp => 16,
+                    Keycode::PageDown => -16,
+                    Keycode::RightBracket => 1,
+                    Keycode::LeftBracket => -1,
+                    Keycode::Return => {
+                        print_palette(palette);
+                        0
+                    }
+                    _ => 0
+                };
+                if step == 0 { return false }
+
+                let shift = 16 - window.palette_channel * 8;
+                let color = &mut palette[window.palette_entry];
+                let channel = (*color >> shift & 0xFF) as i32;
+                *color = *color & !(0xFF << shift) | ((channel + step).clamp(0, 0xFF) as u32) << shift;
+                return true
+            }
+            DebugView::Memory => {
+                let page_length = (MEMORY_ROW_LENGTH * MEMORY_ROWS) as i64;
+                let address = match keycode {
+                    Keycode::Up => address - MEMORY_ROW_LENGTH as i64,
+                    Keycode::Down => address + MEMORY_ROW_LENGTH as i64,
+                    Keycode::PageUp => address - page_length,
+                    Keycode::PageDown => address + page_length,
+                    Keycode::Home => chip8.pc() as i64 & !0xF,
+                    Keycode::End => chip8.index() as i64 & !0xF,
+                    _ => return false
+                };
+                window.address = clamp_memory_address(chip8, address);
+            }
+            DebugView::Sprites => {
+                let row_length = (SPRITE_COLUMNS * window.sprite_height) as i64;
+                let page_length = row_length * sprite_rows(window.sprite_height) as i64;
+                let address = match keycode {
+                    Keycode::Up => address - row_length,
+                    Keycode::Down => address + row_length,
+                    Keycode::Left => address - 1,
+                    Keycode::Right => address + 1,
+                    Keycode::PageUp => address - page_length,
+                    Keycode::PageDown => address + page_length,
+                    Keycode::Home => chip8.index() as i64,
+                    Keycode::LeftBracket | Keycode::RightBracket => {
+                        window.sprite_height = match keycode {
+                            Keycode::LeftBracket => window.sprite_height - 1,
+                            _ => window.sprite_height + 1
+                        }.clamp(1, MAX_SPRITE_HEIGHT);
+                        address
+                    }
+                    _ => return false
+                };
+                window.address = address.clamp(0, chip8.memory().len() as i64 - 1) as usize;
+            }
+        }
+        false
+    }
+
+    // Scrolls the memory view by three rows for each notch of the mouse wheel, and the sprite view by a row of sprites
+    pub fn scroll(&mut self, window_id: u32, notches: f32, chip8: &Chip8) {
+        let Some(window) = self.windows.iter_mut().find(|window| window.canvas.window().id() == window_id) else { return };
+        let rows = (-notches).round() as i64;
+        window.address = match window.view {
+            DebugView::Registers | DebugView::Palette => return,
+            DebugView::Memory => clamp_memory_address(chip8, window.address as i64 + rows * 3 * MEMORY_ROW_LENGTH as i64),
+            DebugView::Sprites => (window.address as i64 + rows * (SPRITE_COLUMNS * window.sprite_height) as i64)
+                .clamp(0, chip8.memory().len() as i64 - 1) as usize
+        };
+    }
+
+    pub fn draw(&mut self, chip8: &Chip8, palette: &Palette, symbols: &Symbols) -> Result<(), &'static str> {
+        for window in &mut self.windows {
+            let canvas = &mut window.canvas;
+            canvas.set_draw_color(Color::BLACK);
+            canvas.clear();
+            match window.view {
+                DebugView::Registers => draw_text(canvas, &registers_text(chip8, symbols), 0.0, 0.0)?,
+                DebugView::Memory => draw_memory(canvas, chip8, window.address)?,
+                DebugView::Sprites => draw_sprites(canvas, chip8, window.address, window.sprite_height)?,
+                DebugView::Palette => draw_palette(canvas, palette, window.palette_entry, window.palette_channel)?
+            }
+            canvas.present();
+        }
+        Ok(())
+    }
+}
+
+// Keeps the memory view's page inside ram, on a row boundary
+fn clamp_memory_address(chip8: &Chip8, address: i64) -> usize {
+    let last_page = chip8.memory().len().saturating_sub(MEMORY_ROW_LENGTH * MEMORY_ROWS);
+    address.clamp(0, last_page as i64) as usize & !(MEMORY_ROW_LENGTH - 1)
+}
+
+// Lists the state in two rows of registers, then the instructions from the program counter with breakpoints marked by *
+// Addresses with symbols are followed by their names
+fn registers_text(chip8: &Chip8, symbols: &Symbols) -> String {
+    let mut text = format!("PC {}  I {}  DT {}  ST {}\n", symbols.describe(chip8.pc()), symbols.describe(chip8.index()),
+        chip8.delay_timer(), chip8.sound_timer());
+    for (half, registers) in chip8.registers().chunks(8).enumerate() {
+        let registers: Vec<String> = registers.iter().enumerate()
+            .map(|(i, value)| format!("V{:X} {value:02X}", half * 8 + i)).collect();
+        text += &format!("{}\n", registers.join("  "));
+    }
+
+    let stack: Vec<String> = chip8.stack_frames().iter().map(|address| format!("0x{address:03X}")).collect();
+    let depth = match chip8.stack_depth() {
+        Some(depth) => depth.to_string(),
+        None => String::from("unlimited")
+    };
+    text += &format!("Stack [{}] of {depth}\n\n", stack.join(", "));
+
+    let mut address = chip8.pc() as usize;
+    for _ in 0..LISTED_INSTRUCTIONS {
+        let Some(code) = chip8.memory().get(address..).filter(|code| !code.is_empty()) else { break };
+        let (mnemonic, length) = disassemble_with_symbols(code, chip8.profile(), symbols);
+        let pc_marker = match address == chip8.pc() as usize { true => '>', false => ' ' };
+        let breakpoint_marker = match chip8.breakpoints().contains(&(address as u16)) { true => '*', false => ' ' };
+        text += &format!("{pc_marker}{breakpoint_marker} {}  {mnemonic}\n", symbols.describe(address as u16));
+        address += length;
+    }
+    text
+}
+
+// Draws a page of ram as hexadecimal rows, tinting the instruction at the program counter orange and the index register blue
+// While coverage is recorded, bytes are underlined by whether they were executed, read, or written
+fn draw_memory(canvas: &mut Canvas<Window>, chip8: &Chip8, page_address: usize) -> Result<(), &'static str> {
+    let memory = chip8.memory();
+    let pc_label = format!("PC 0x{:03X}", chip8.pc());
+    let index_label = format!("I 0x{:03X}", chip8.index());
+    let mut text = format!("{pc_label}  {index_label}");
+    if chip8.is_tracking_coverage() {
+        for (_, name, _) in COVERAGE_COLORS {
+            text += &format!("  {name}");
+        }
+    }
+    text += "\n";
+    let page_end = (page_address + MEMORY_ROW_LENGTH * MEMORY_ROWS).min(memory.len());
+    for row in (page_address..page_end).step_by(MEMORY_ROW_LENGTH) {
+        text += &format!("0x{row:04X}");
+        for byte in &memory[row..(row + MEMORY_ROW_LENGTH).min(page_end)] {
+            text += &format!(" {byte:02X}");
+        }
+        text += "\n";
+    }
+    draw_text(canvas, &text, 0.0, 0.0)?;
+
+    // Tints the header's labels as a legend, then the bytes on this page
+    let cell = |column: usize, line: usize, length: usize| FRect::new(column as f32 * GLYPH_WIDTH, line as f32 * LINE_HEIGHT,
+        length as f32 * GLYPH_WIDTH + FONT_PIXEL, LINE_HEIGHT + FONT_PIXEL);
+    let byte_cells = |address: usize, length: usize| (address..address + length)
+        .filter(|address| (page_address..page_end).contains(address))
+        .map(|address| cell(7 + (address % MEMORY_ROW_LENGTH) * 3, 1 + (address - page_address) / MEMORY_ROW_LENGTH, 2))
+        .collect::<Vec<FRect>>();
+    let mut pc_cells = byte_cells(chip8.pc() as usize, 2);
+    pc_cells.push(cell(0, 0, pc_label.len()));
+    let mut index_cells = byte_cells(chip8.index() as usize, 1);
+    index_cells.push(cell(pc_label.len() + 2, 0, index_label.len()));
+
+    let draw_color = canvas.draw_color();
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(PC_COLOR);
+    let pc_result = canvas.fill_rects(&pc_cells);
+    canvas.set_draw_color(INDEX_COLOR);
+    let index_result = canvas.fill_rects(&index_cells);
+
+    // Underlines the legend's names, then each third of the bytes with that use, in the gap below their text
+    let mut coverage_result = Ok(());
+    if chip8.is_tracking_coverage() {
+        let underline = |column: f32, line: usize, width: f32| FRect::new(column * GLYPH_WIDTH, (line + 1) as f32 * LINE_HEIGHT,
+            width * GLYPH_WIDTH, FONT_PIXEL);
+        let mut legend_column = pc_label.len() + 2 + index_label.len();
+        for (third, (flag, name, color)) in COVERAGE_COLORS.into_iter().enumerate() {
+            legend_column += 2;
+            let mut underlines = vec![underline(legend_column as f32, 0, name.len() as f32)];
+            legend_column += name.len();
+            for address in (page_address..page_end).filter(|&address| chip8.coverage()[address] & flag != 0) {
+                let column = 7.0 + (address % MEMORY_ROW_LENGTH * 3) as f32 + third as f32 * 2.0 / 3.0;
+                underlines.push(underline(column, 1 + (address - page_address) / MEMORY_ROW_LENGTH, 2.0 / 3.0));
+            }
+            canvas.set_draw_color(color);
+            coverage_result = coverage_result.and(canvas.fill_rects(&underlines));
+        }
+    }
+    canvas.set_draw_color(draw_color);
+    canvas.set_blend_mode(BlendMode::None);
+    match pc_result.and(index_result).and(coverage_result) {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Failed to highlight the memory view!")
+    }
+}
+
+// Height of a row of sprites with the address labels above them
+fn sprite_row_height(sprite_height: usize) -> f32 {
+    LINE_HEIGHT + sprite_height as f32 * SPRITE_PIXEL + 2.0 * FONT_PIXEL
+}
+
+// Rows of sprites that fit below the sprite view's header
+fn sprite_rows(sprite_height: usize) -> usize {
+    ((SPRITE_LINES - 1) as f32 * LINE_HEIGHT / sprite_row_height(sprite_height)) as usize
+}
+
+// Draws ram as rows of 8xN sprites from an address, labelling each sprite with its address
+// The bytes a draw at the index register would read are tinted blue, even when I isn't at the start of a sprite
+fn draw_sprites(canvas: &mut Canvas<Window>, chip8: &Chip8, page_address: usize, sprite_height: usize) -> Result<(), &'static str> {
+    let memory = chip8.memory();
+    let index_label = format!("I 0x{:03X}", chip8.index());
+    draw_text(canvas, &format!("{index_label}  Height {sprite_height}"), 0.0, 0.0)?;
+
+    let mut labels = String::new();
+    let mut backgrounds = Vec::new();
+    let mut pixels = Vec::new();
+    let mut index_rows = vec![FRect::new(0.0, 0.0, index_label.len() as f32 * GLYPH_WIDTH + FONT_PIXEL, LINE_HEIGHT + FONT_PIXEL)];
+    let index = chip8.index() as usize..chip8.index() as usize + sprite_height;
+    for row in 0..sprite_rows(sprite_height) {
+        let row_y = LINE_HEIGHT + FONT_PIXEL + row as f32 * sprite_row_height(sprite_height);
+        let sprite_y = row_y + LINE_HEIGHT + FONT_PIXEL;
+        labels.clear();
+        for column in 0..SPRITE_COLUMNS {
+            let sprite_address = page_address + (row * SPRITE_COLUMNS + column) * sprite_height;
+            if sprite_address >= memory.len() { break }
+            labels += &format!("{:<7}", format!("0x{sprite_address:03X}"));
+
+            let sprite_x = column as f32 * SPRITE_CELL_WIDTH + FONT_PIXEL;
+            backgrounds.push(FRect::new(sprite_x, sprite_y, 8.0 * SPRITE_PIXEL, sprite_height as f32 * SPRITE_PIXEL));
+            for (line, &byte) in memory[sprite_address..].iter().take(sprite_height).enumerate() {
+                let line_y = sprite_y + line as f32 * SPRITE_PIXEL;
+                if index.contains(&(sprite_address + line)) {
+                    index_rows.push(FRect::new(sprite_x, line_y, 8.0 * SPRITE_PIXEL, SPRITE_PIXEL));
+                }
+                for bit in 0..8 {
+                    if byte & (0x80 >> bit) == 0 { continue }
+                    pixels.push(FRect::new(sprite_x + bit as f32 * SPRITE_PIXEL, line_y, SPRITE_PIXEL, SPRITE_PIXEL));
+                }
+            }
+        }
+        if !labels.is_empty() { draw_text(canvas, labels.trim_end(), 0.0, row_y)?; }
+    }
+
+    let draw_color = canvas.draw_color();
+    canvas.set_draw_color(SPRITE_BACKGROUND_COLOR);
+    let background_result = canvas.fill_rects(&backgrounds);
+    canvas.set_draw_color(Color::WHITE);
+    let pixel_result = canvas.fill_rects(&pixels);
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(INDEX_COLOR);
+    let index_result = canvas.fill_rects(&index_rows);
+    canvas.set_draw_color(draw_color);
+    canvas.set_blend_mode(BlendMode::None);
+    match background_result.and(pixel_result).and(index_result) {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Failed to draw the sprite view!")
+    }
+}
+
+// Lists the palette entries with their hex colors and a swatch, tinting the selected channel of the selected entry
+fn draw_palette(canvas: &mut Canvas<Window>, palette: &Palette, entry: usize, channel: usize) -> Result<(), &'static str> {
+    let channel_name = ["red", "green", "blue"][channel];
+    let mut text = format!("Entry {entry:X} {channel_name}\n\n");
+    for (i, color) in palette.iter().enumerate() {
+        let marker = match i == entry { true => '>', false => ' ' };
+        text += &format!("{marker} {i:X}  {:06X}\n", color & 0xFFFFFF);
+    }
+    draw_text(canvas, &text, 0.0, 0.0)?;
+
+    // Swatches sit right of the hex colors, with a border so the background entry shows on black
+    let swatch = |i: usize, inset: f32| FRect::new(PALETTE_SWATCH_COLUMN as f32 * GLYPH_WIDTH + inset,
+        (i + 2) as f32 * LINE_HEIGHT + FONT_PIXEL + inset, 3.0 * GLYPH_WIDTH - 2.0 * inset, LINE_HEIGHT - FONT_PIXEL - 2.0 * inset);
+    let draw_color = canvas.draw_color();
+    canvas.set_draw_color(SPRITE_BACKGROUND_COLOR);
+    let mut result = canvas.fill_rects(&(0..palette.len()).map(|i| swatch(i, 0.0)).collect::<Vec<FRect>>());
+    for (i, &color) in palette.iter().enumerate() {
+        canvas.set_draw_color(Color::RGB((color >> 16) as u8, (color >> 8) as u8, color as u8));
+        result = result.and(canvas.fill_rect(swatch(i, FONT_PIXEL)));
+    }
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(PC_COLOR);
+    let channel_x = (5 + channel * 2) as f32 * GLYPH_WIDTH;
+    result = result.and(canvas.fill_rect(FRect::new(channel_x, (entry + 2) as f32 * LINE_HEIGHT, 2.0 * GLYPH_WIDTH + FONT_PIXEL,
+        LINE_HEIGHT + FONT_PIXEL)));
+    canvas.set_draw_color(draw_color);
+    canvas.set_blend_mode(BlendMode::None);
+    match result {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Failed to draw the palette view!")
+    }
+}
+
+// Prints the palette as a command line option and a config file line, for keeping the colors chosen in the palette view
+fn print_palette(palette: &Palette) {
+    let option: Vec<String> = palette.iter().map(|color| format!("{:06X}", color & 0xFFFFFF)).collect();
+    let toml: Vec<String> = palette.iter().map(|color| format!("0x{:06X}", color & 0xFFFFFF)).collect();
+    println!("-palette {}", option.join(","));
+    println!("palette = [{}]", toml.join(", "));
+}
@@ -0,0 +1,376 @@
+use std::{io::stdin, sync::mpsc::{self, Receiver}, thread};
+use chip8_emulator::{disassemble_with_symbols, Checkpoint, Chip8, Chip8Error, Expression, Symbols, Watch};
+use crate::{coverage, profiler::print_report};
+
+// Console debugger reading commands on another thread so the window keeps responding
+pub struct Debugger {
+    commands: Receiver<String>,
+    was_paused: bool,
+    memory_address: usize,
+    history: Option<(Checkpoint, u64)> // Taken before the first step since pausing, with its instruction count
+}
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        // Sends each line of standard input to the main thread
+        let (sender, receiver) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for line in stdin().lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() { break }
+            }
+        });
+
+        println!("Debugger attached, enter \"h\" for a list of commands.");
+        Debugger{commands: receiver, was_paused: false, memory_address: 0x200, history: None}
+    }
+
+    // Executes pending commands and reports when a breakpoint pauses the machine
+    // Symbols name addresses in the output and can be used in place of them in commands
+    pub fn update(&mut self, chip8: &mut Chip8, symbols: &Symbols) -> Option<Chip8Error> {
+        while let Ok(command) = self.commands.try_recv() {
+            if let Some(error) = self.execute(chip8, &command, symbols) {
+                return Some(error)
+            }
+        }
+
+        if chip8.is_paused() && !self.was_paused {
+            if let Some(hit) = chip8.watch_hit() {
+                let access = match hit.is_write { true => "written", false => "read" };
+                println!("Watchpoint {} {access} by the instruction at {}", symbols.describe(hit.address), symbols.describe(hit.pc));
+            }
+            println!("Paused at {}", symbols.describe(chip8.pc()));
+            print_state(chip8, symbols);
+        }
+        self.was_paused = chip8.is_paused();
+
+        // Steps can only be reversed while paused, since running ticks the timers between instructions, and not past a
+        // reset, loaded state, or edit, since executing them again would lose it
+        let is_history_current = self.history.as_ref().is_some_and(|(checkpoint, _)| chip8.is_checkpoint_current(checkpoint));
+        if !chip8.is_paused() || !is_history_current { self.history = None; }
+        None
+    }
+
+    // Pauses and prints the state, or resumes execution
+    pub fn toggle_pause(&mut self, chip8: &mut Chip8) {
+        match chip8.is_paused() {
+            true => {
+                chip8.resume();
+                println!("Resumed");
+            }
+            false => chip8.pause()
+        }
+    }
+
+    // Executes instructions one at a time while paused
+    pub fn step(&mut self, chip8: &mut Chip8, count: u32, symbols: &Symbols) -> Option<Chip8Error> {
+        if !chip8.is_paused() {
+            println!("Pause the emulator before stepping!");
+            return None
+        }
+
+        if self.history.is_none() { self.history = Some((chip8.checkpoint(), chip8.instruction_count())); }
+        for _ in 0..count {
+            if let Err(error) = chip8.step() {
+                return Some(error)
+            }
+        }
+        print_state(chip8, symbols);
+        None
+    }
+
+    // Returns to an earlier instruction by restoring the checkpoint from before stepping, then executing the steps
+    // before it again
+    fn reverse_step(&mut self, chip8: &mut Chip8, count: u64, symbols: &Symbols) -> Option<Chip8Error> {
+        if !chip8.is_paused() {
+            println!("Pause the emulator before stepping!");
+            return None
+        }
+        let steps = self.history.as_ref().map_or(0, |(_, start_count)| chip8.instruction_count().saturating_sub(*start_count));
+        let Some((checkpoint, _)) = self.history.as_ref().filter(|(checkpoint, _)| steps > 0 && chip8.is_checkpoint_current(checkpoint)) else {
+            println!("No steps to reverse since the emulator paused or was changed!");
+            return None
+        };
+
+        if let Err(error) = chip8.replay(checkpoint, steps.saturating_sub(count)) {
+            return Some(error)
+        }
+        print_state(chip8, symbols);
+        None
+    }
+
+    // Runs a CALL until its subroutine returns, or steps any other instruction
+    fn step_over(&mut self, chip8: &mut Chip8, symbols: &Symbols) -> Option<Chip8Error> {
+        if !chip8.is_paused() {
+            println!("Pause the emulator before stepping!");
+            return None
+        }
+
+        let pc = chip8.pc() as usize;
+        match chip8.memory().get(pc) {
+            Some(high) if high & 0xF0 == 0x20 => {
+                chip8.resume_until_return(chip8.stack_frames().len());
+                println!("Running until the subroutine called at {} returns", symbols.describe(pc as u16));
+                None
+            }
+            _ => self.step(chip8, 1, symbols)
+        }
+    }
+
+    // Runs until the current subroutine returns to its caller
+    fn step_out(&mut self, chip8: &mut Chip8) {
+        match (chip8.is_paused(), chip8.stack_frames().len()) {
+            (false, _) => println!("Pause the emulator before stepping!"),
+            (true, 0) => println!("Not in a subroutine!"),
+            (true, depth) => {
+                chip8.resume_until_return(depth - 1);
+                println!("Running until the subroutine returns");
+            }
+        }
+    }
+
+    // Prints a page of ram, highlighting the instruction at the program counter and the index register
+    fn print_memory(&mut self, chip8: &Chip8) {
+        const ROW_LENGTH: usize = 16;
+        const PAGE_LENGTH: usize = ROW_LENGTH * 8;
+        let memory = chip8.memory();
+        if self.memory_address >= memory.len() { self.memory_address = 0; }
+
+        println!("\x1b[7mPC\x1b[0m \x1b[4mI\x1b[0m");
+        let page_end = (self.memory_address + PAGE_LENGTH).min(memory.len());
+        for row in (self.memory_address..page_end).step_by(ROW_LENGTH) {
+            let mut line = format!("0x{row:04X} ");
+            for (address, byte) in memory.iter().enumerate().take(row + ROW_LENGTH).skip(row) {
+                // Highlights with ansi escape codes for reversed and underlined text
+                let is_pc = address == chip8.pc() as usize || address == chip8.pc() as usize + 1;
+                let is_index = address == chip8.index() as usize;
+                line += match (is_pc, is_index) {
+                    (true, true) => " \x1b[7;4m",
+                    (true, false) => " \x1b[7m",
+                    (false, true) => " \x1b[4m",
+                    (false, false) => " "
+                };
+                line += &format!("{byte:02X}");
+                if is_pc || is_index { line += "\x1b[0m"; }
+            }
+            println!("{line}");
+        }
+        self.memory_address = page_end;
+    }
+
+    fn execute(&mut self, chip8: &mut Chip8, command: &str, symbols: &Symbols) -> Option<Chip8Error> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let argument = words.next();
+        let arguments = words;
+        let to_address = |text: &str| symbols.address(text).or_else(|| parse_address(text));
+
+        match name {
+            "b" | "break" => {
+                let Some(address) = argument.and_then(to_address) else {
+                    println!("Expected a hexadecimal address or a symbol!");
+                    return None
+                };
+
+                // Anything after the address, optionally after "if", is the breakpoint's condition
+                let condition: Vec<&str> = arguments.skip_while(|&word| word == "if").collect();
+                match condition.is_empty() {
+                    true => {
+                        chip8.set_breakpoint(address);
+                        println!("Breakpoint set at {}", symbols.describe(address));
+                    }
+                    false => match Expression::parse(&condition.join(" "), symbols) {
+                        Ok(condition) => {
+                            println!("Breakpoint set at {} if {condition}", symbols.describe(address));
+                            chip8.set_conditional_breakpoint(address, condition);
+                        }
+                        Err(error) => println!("{error}")
+                    }
+                }
+            }
+
+            "c" | "continue" => match chip8.is_paused() {
+                true => self.toggle_pause(chip8),
+                false => println!("The emulator isn't paused!")
+            }
+
+            "cv" | "coverage" => match chip8.is_tracking_coverage() {
+                true => println!("{}", coverage::summary(chip8)),
+                false => {
+                    chip8.set_coverage(true);
+                    println!("Recording how ram is used");
+                }
+            }
+
+            "d" | "delete" => match argument.and_then(to_address) {
+                Some(address) => match (chip8.remove_breakpoint(address), chip8.remove_watchpoint(address)) {
+                    (true, _) => println!("Breakpoint removed at {}", symbols.describe(address)),
+                    (false, true) => println!("Watchpoint removed at {}", symbols.describe(address)),
+                    (false, false) => println!("No breakpoint or watchpoint at {}!", symbols.describe(address))
+                }
+                None => println!("Expected a hexadecimal address or a symbol!")
+            }
+
+            "e" | "eval" => match Expression::parse(command.trim().split_once(char::is_whitespace).map_or("", |(_, text)| text), symbols) {
+                Ok(expression) => {
+                    let value = expression.evaluate(chip8);
+                    println!("{expression} = 0x{value:X} ({value})");
+                }
+                Err(error) => println!("{error}")
+            }
+
+            "f" | "finish" => self.step_out(chip8),
+
+            "h" | "help" => print!("\
+                b  break     <Address> [if <Condition>]  Sets a breakpoint, which only pauses when the condition is true\n\
+                c  continue             Resumes execution\n\
+                cv coverage             Prints how the rom's bytes were used, or starts recording it\n\
+                d  delete    <Address>  Removes a breakpoint or watchpoint\n\
+                e  eval      <Expression>  Prints the value of an expression, like \"V3 == 0x1F && I > 0x300\"\n\
+                f  finish               Runs until the current subroutine returns\n\
+                h  help                 Displays this help message\n\
+                l  list                 Lists the breakpoints and watchpoints\n\
+                m  memory    <Address>  Prints 128 bytes of ram, or the next 128 bytes without an address\n\
+                n  next                 Steps over a CALL by running until its subroutine returns\n\
+                p  pause                Pauses execution\n\
+                pr profile              Prints the hottest instructions, or starts counting them\n\
+                r  registers            Prints the machine state\n\
+                rs reverse   <Count>    Steps backwards through the instructions stepped since pausing (default: 1)\n\
+                s  step      <Count>    Executes instructions while paused (default: 1)\n\
+                w  write     <Address> <Bytes>  Writes hexadecimal bytes to ram\n\
+                wa watch     <Address> <r | w | rw>  Pauses after an instruction reads or writes ram (default: w)\n\
+                Addresses are hexadecimal, or the names of symbols\n\
+                Expressions use V0-VF, I, PC, DT, ST, SP, [<Address>] for ram, symbols, and numbers with 0x for hexadecimal\n\
+                Their operators are || && == != < <= > >= | ^ & + - and ! with parentheses\n\
+            "),
+
+            "l" | "list" => {
+                for &address in chip8.breakpoints() {
+                    match chip8.breakpoint_condition(address) {
+                        Some(condition) => println!("{} if {condition}", symbols.describe(address)),
+                        None => println!("{}", symbols.describe(address))
+                    }
+                }
+                for &(address, watch) in chip8.watchpoints() {
+                    println!("{} {}", symbols.describe(address), watch_name(watch));
+                }
+            }
+
+            "m" | "memory" => match argument.map(to_address) {
+                None => self.print_memory(chip8),
+                Some(Some(address)) => {
+                    self.memory_address = address as usize & !0xF;
+                    self.print_memory(chip8);
+                }
+                Some(None) => println!("Expected a hexadecimal address or a symbol!")
+            }
+
+            "n" | "next" => return self.step_over(chip8, symbols),
+            "p" | "pause" => chip8.pause(),
+            "pr" | "profile" => match chip8.is_profiling() {
+                true => print_report(chip8, 10),
+                false => {
+                    chip8.set_profiling(true);
+                    println!("Counting executed instructions");
+                }
+            }
+            "r" | "registers" => print_state(chip8, symbols),
+
+            "rs" | "reverse" => match argument.map(str::parse::<u64>) {
+                None => return self.reverse_step(chip8, 1, symbols),
+                Some(Ok(count)) => return self.reverse_step(chip8, count, symbols),
+                Some(Err(_)) => println!("Step count is not a number!")
+            }
+
+            "s" | "step" => match argument.map(str::parse::<u32>) {
+                None => return self.step(chip8, 1, symbols),
+                Some(Ok(count)) => return self.step(chip8, count, symbols),
+                Some(Err(_)) => println!("Step count is not a number!")
+            }
+
+            "wa" | "watch" => {
+                let watch = match arguments.clone().next().unwrap_or("w") {
+                    "r" => Watch::Read,
+                    "w" => Watch::Write,
+                    "rw" => Watch::Access,
+                    _ => {
+                        println!("Expected r, w, or rw!");
+                        return None
+                    }
+                };
+                match argument.and_then(to_address) {
+                    Some(address) => {
+                        chip8.set_watchpoint(address, watch);
+                        println!("Watchpoint set at {} {}", symbols.describe(address), watch_name(watch));
+                    }
+                    None => println!("Expected a hexadecimal address or a symbol!")
+                }
+            }
+
+            "w" | "write" => {
+                // Parses every byte before writing any of them
+                let Some(address) = argument.and_then(to_address) else {
+                    println!("Expected a hexadecimal address or a symbol!");
+                    return None
+                };
+                let bytes: Option<Vec<u8>> = arguments.map(|byte| u8::from_str_radix(byte, 16).ok()).collect();
+                let Some(bytes) = bytes.filter(|bytes| !bytes.is_empty()) else {
+                    println!("Expected hexadecimal bytes!");
+                    return None
+                };
+
+                match chip8.memory_mut().get_mut(address as usize..address as usize + bytes.len()) {
+                    Some(destination) => {
+                        destination.copy_from_slice(&bytes);
+                        println!("Wrote {} bytes at 0x{address:03X}", bytes.len());
+                    }
+                    None => println!("Write is outside of ram!")
+                }
+            }
+
+            "" => (),
+            _ => println!("Unknown command \"{name}\"!")
+        }
+        None
+    }
+}
+
+fn watch_name(watch: Watch) -> &'static str {
+    match watch {
+        Watch::Read => "on reads",
+        Watch::Write => "on writes",
+        Watch::Access => "on reads and writes"
+    }
+}
+
+// Parses a hexadecimal address with or without a 0x prefix
+pub fn parse_address(text: &str) -> Option<u16> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    u16::from_str_radix(digits, 16).ok()
+}
+
+// Prints the registers, timers, stack, and the next instruction
+pub fn print_state(chip8: &Chip8, symbols: &Symbols) {
+    print!("{}", format_state(chip8, symbols));
+}
+
+// Formats the machine state as lines for the console and crash reports, naming the addresses that have symbols
+pub fn format_state(chip8: &Chip8, symbols: &Symbols) -> String {
+    let code = chip8.memory().get(chip8.pc() as usize..).unwrap_or(&[]);
+    let (mnemonic, _) = disassemble_with_symbols(code, chip8.profile(), symbols);
+
+    let mut text = format!("PC {}  I {}  SP {}  DT {}  ST {}  {mnemonic}\n", symbols.describe(chip8.pc()), symbols.describe(chip8.index()),
+        chip8.stack_frames().len(), chip8.delay_timer(), chip8.sound_timer());
+
+    let registers: Vec<String> = chip8.registers().iter().enumerate()
+        .map(|(i, value)| format!("V{i:X} {value:02X}")).collect();
+    text += &format!("{}\n", registers.join("  "));
+
+    let stack: Vec<String> = chip8.stack_frames().iter().map(|address| format!("0x{address:03X}")).collect();
+    let depth = match chip8.stack_depth() {
+        Some(depth) => depth.to_string(),
+        None => String::from("unlimited")
+    };
+    text += &format!("Stack [{}] of {depth}\n", stack.join(", "));
+    text
+}
@@ -0,0 +1,109 @@
+use crate::chip8::Profile;
+
+// An opcode split into its operation and operands once, so running it again skips the nibble matching
+// Operands keep the names of the opcode's nibbles, where x and y are registers
+#[derive(Clone, Copy)]
+pub(crate) enum Instruction {
+    Cls,
+    Ret,
+    Exit,
+    Sys,
+    Jp{nnn: u16},
+    Call{nnn: u16},
+    SeByte{x: u8, kk: u8},
+    SneByte{x: u8, kk: u8},
+    Se{x: u8, y: u8},
+    LdByte{x: u8, kk: u8},
+    AddByte{x: u8, kk: u8},
+    Ld{x: u8, y: u8},
+    Or{x: u8, y: u8},
+    And{x: u8, y: u8},
+    Xor{x: u8, y: u8},
+    Add{x: u8, y: u8},
+    Sub{x: u8, y: u8},
+    Shr{x: u8, y: u8},
+    Subn{x: u8, y: u8},
+    Shl{x: u8, y: u8},
+    Sne{x: u8, y: u8},
+    LdIndex{nnn: u16},
+    JpOffset{x: u8, nnn: u16},
+    Rnd{x: u8, kk: u8},
+    Drw{x: u8, y: u8, n: u8},
+    Skp{x: u8},
+    Sknp{x: u8},
+    LdDelay{x: u8},
+    LdKey{x: u8},
+    SetDelay{x: u8},
+    SetSound{x: u8},
+    AddIndex{x: u8},
+    LdFont{x: u8},
+    LdBcd{x: u8},
+    Store{x: u8},
+    Load{x: u8},
+    SaveFlags{x: u8},
+    LoadFlags{x: u8},
+    Unsupported
+}
+
+// Decodes an opcode for a profile, where SCHIP instructions are unsupported by the original interpreter
+pub(crate) fn decode(opcode: u16, profile: Profile) -> Instruction {
+    let [high, low] = opcode.to_be_bytes();
+    let (op0, op1, op2, op3) = (high >> 4, high & 0xF, low >> 4, low & 0xF);
+    let (x, y, n) = (op1, op2, op3);
+    let kk = low;
+    let nnn = opcode & 0xFFF;
+    let is_schip = !matches!(profile, Profile::Chip8 | Profile::Vip);
+
+    match op0 {
+        0x0 => match nnn {
+            0x0E0 => Instruction::Cls,
+            0x0EE => Instruction::Ret,
+            0x0FD if is_schip => Instruction::Exit,
+            _ => Instruction::Sys
+        }
+        0x1 => Instruction::Jp{nnn},
+        0x2 => Instruction::Call{nnn},
+        0x3 => Instruction::SeByte{x, kk},
+        0x4 => Instruction::SneByte{x, kk},
+        0x5 if op3 == 0x0 => Instruction::Se{x, y},
+        0x6 => Instruction::LdByte{x, kk},
+        0x7 => Instruction::AddByte{x, kk},
+        0x8 => match op3 {
+            0x0 => Instruction::Ld{x, y},
+            0x1 => Instruction::Or{x, y},
+            0x2 => Instruction::And{x, y},
+            0x3 => Instruction::Xor{x, y},
+            0x4 => Instruction::Add{x, y},
+            0x5 => Instruction::Sub{x, y},
+            0x6 => Instruction::Shr{x, y},
+            0x7 => Instruction::Subn{x, y},
+            0xE => Instruction::Shl{x, y},
+            _ => Instruction::Unsupported
+        }
+        0x9 if op3 == 0x0 => Instruction::Sne{x, y},
+        0xA => Instruction::LdIndex{nnn},
+        0xB => Instruction::JpOffset{x, nnn},
+        0xC => Instruction::Rnd{x, kk},
+        0xD => Instruction::Drw{x, y, n},
+        0xE => match kk {
+            0x9E => Instruction::Skp{x},
+            0xA1 => Instruction::Sknp{x},
+            _ => Instruction::Unsupported
+        }
+        0xF => match kk {
+            0x07 => Instruction::LdDelay{x},
+            0x0A => Instruction::LdKey{x},
+            0x15 => Instruction::SetDelay{x},
+            0x18 => Instruction::SetSound{x},
+            0x1E => Instruction::AddIndex{x},
+            0x29 => Instruction::LdFont{x},
+            0x33 => Instruction::LdBcd{x},
+            0x55 => Instruction::Store{x},
+            0x65 => Instruction::Load{x},
+            0x75 if is_schip => Instruction::SaveFlags{x},
+            0x85 if is_schip => Instruction::LoadFlags{x},
+            _ => Instruction::Unsupported
+        }
+        _ => Instruction::Unsupported
+    }
+}
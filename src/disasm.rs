@@ -0,0 +1,208 @@
+use crate::{chip8::Profile, symbols::Symbols};
+
+// Instructions that need more than the original interpreter
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum InstructionSet {
+    Chip8,
+    Schip,
+    XoChip,
+    JumpQuirk,  // BXNN, which jumps by VX on SCHIP
+    NoJumpQuirk // BNNN, which jumps by V0 everywhere else
+}
+
+impl InstructionSet {
+    pub(crate) fn is_supported(self, profile: Profile) -> bool {
+        match self {
+            InstructionSet::Chip8 => true,
+            InstructionSet::Schip => !matches!(profile, Profile::Chip8 | Profile::Vip),
+            InstructionSet::XoChip => profile == Profile::XoChip,
+            InstructionSet::JumpQuirk => profile.quirks().is_jump_quirk,
+            InstructionSet::NoJumpQuirk => !profile.quirks().is_jump_quirk
+        }
+    }
+}
+
+// An instruction's opcode bits under its mask, and its mnemonic with the operands named after the opcode's nibbles
+// Vx and Vy are registers, kk is a byte, nnn is an address, n and x are nibbles written in decimal,
+// and long is the 16 bit address after XO-CHIP's F000
+pub(crate) struct OpcodeForm {
+    pub opcode: u16,
+    pub mask: u16,
+    pub pattern: &'static str,
+    pub set: InstructionSet
+}
+
+impl OpcodeForm {
+    pub(crate) fn length(&self) -> usize {
+        match self.pattern.contains("long") {
+            true => 4,
+            false => 2
+        }
+    }
+}
+
+const fn form(opcode: u16, mask: u16, pattern: &'static str, set: InstructionSet) -> OpcodeForm {
+    OpcodeForm{opcode, mask, pattern, set}
+}
+
+// Shared by the disassembler and the assembler, which both take the first form that matches
+pub(crate) const OPCODE_FORMS: &[OpcodeForm] = {
+    use InstructionSet::*;
+    &[
+        form(0x00E0, 0xFFFF, "CLS", Chip8),
+        form(0x00EE, 0xFFFF, "RET", Chip8),
+        form(0x00C0, 0xFFF0, "SCD n", Schip),
+        form(0x00D0, 0xFFF0, "SCU n", XoChip),
+        form(0x00FB, 0xFFFF, "SCR", Schip),
+        form(0x00FC, 0xFFFF, "SCL", Schip),
+        form(0x00FD, 0xFFFF, "EXIT", Schip),
+        form(0x00FE, 0xFFFF, "LOW", Schip),
+        form(0x00FF, 0xFFFF, "HIGH", Schip),
+        form(0x0000, 0xF000, "SYS nnn", Chip8),
+        form(0x1000, 0xF000, "JP nnn", Chip8),
+        form(0x2000, 0xF000, "CALL nnn", Chip8),
+        form(0x3000, 0xF000, "SE Vx, kk", Chip8),
+        form(0x4000, 0xF000, "SNE Vx, kk", Chip8),
+        form(0x5000, 0xF00F, "SE Vx, Vy", Chip8),
+        form(0x5002, 0xF00F, "SAVE Vx - Vy", XoChip),
+        form(0x5003, 0xF00F, "LOAD Vx - Vy", XoChip),
+        form(0x6000, 0xF000, "LD Vx, kk", Chip8),
+        form(0x7000, 0xF000, "ADD Vx, kk", Chip8),
+        form(0x8000, 0xF00F, "LD Vx, Vy", Chip8),
+        form(0x8001, 0xF00F, "OR Vx, Vy", Chip8),
+        form(0x8002, 0xF00F, "AND Vx, Vy", Chip8),
+        form(0x8003, 0xF00F, "XOR Vx, Vy", Chip8),
+        form(0x8004, 0xF00F, "ADD Vx, Vy", Chip8),
+        form(0x8005, 0xF00F, "SUB Vx, Vy", Chip8),
+        form(0x8006, 0xF00F, "SHR Vx, Vy", Chip8),
+        form(0x8007, 0xF00F, "SUBN Vx, Vy", Chip8),
+        form(0x800E, 0xF00F, "SHL Vx, Vy", Chip8),
+        form(0x9000, 0xF00F, "SNE Vx, Vy", Chip8),
+        form(0xA000, 0xF000, "LD I, nnn", Chip8),
+        form(0xB000, 0xF000, "JP V0, nnn", NoJumpQuirk),
+        form(0xB000, 0xF000, "JP Vx, nnn", JumpQuirk),
+        form(0xC000, 0xF000, "RND Vx, kk", Chip8),
+        form(0xD000, 0xF000, "DRW Vx, Vy, n", Chip8),
+        form(0xE09E, 0xF0FF, "SKP Vx", Chip8),
+        form(0xE0A1, 0xF0FF, "SKNP Vx", Chip8),
+        form(0xF000, 0xFFFF, "LD I, long", XoChip),
+        form(0xF001, 0xF0FF, "PLANE x", XoChip),
+        form(0xF002, 0xFFFF, "AUDIO", XoChip),
+        form(0xF007, 0xF0FF, "LD Vx, DT", Chip8),
+        form(0xF00A, 0xF0FF, "LD Vx, K", Chip8),
+        form(0xF015, 0xF0FF, "LD DT, Vx", Chip8),
+        form(0xF018, 0xF0FF, "LD ST, Vx", Chip8),
+        form(0xF01E, 0xF0FF, "ADD I, Vx", Chip8),
+        form(0xF029, 0xF0FF, "LD F, Vx", Chip8),
+        form(0xF030, 0xF0FF, "LD HF, Vx", Schip),
+        form(0xF033, 0xF0FF, "LD B, Vx", Chip8),
+        form(0xF03A, 0xF0FF, "PITCH Vx", XoChip),
+        form(0xF055, 0xF0FF, "LD [I], Vx", Chip8),
+        form(0xF065, 0xF0FF, "LD Vx, [I]", Chip8),
+        form(0xF075, 0xF0FF, "LD R, Vx", Schip),
+        form(0xF085, 0xF0FF, "LD Vx, R", Schip)
+    ]
+};
+
+// An opcode's nibbles and the operands made of them
+pub(crate) struct Fields {
+    pub x: u16,
+    pub y: u16,
+    pub n: u16,
+    pub kk: u16,
+    pub nnn: u16
+}
+
+impl Fields {
+    pub(crate) fn of(opcode: u16) -> Fields {
+        Fields{x: opcode >> 8 & 0xF, y: opcode >> 4 & 0xF, n: opcode & 0xF, kk: opcode & 0xFF, nnn: opcode & 0xFFF}
+    }
+}
+
+// Converts the instruction at the start of the code into a mnemonic and its length in bytes
+// SCHIP and XO-CHIP instructions are only recognized by their profiles
+pub fn disassemble(code: &[u8], profile: Profile) -> (String, usize) {
+    disassemble_with_symbols(code, profile, &Symbols::default())
+}
+
+// Disassembles like disassemble, naming addresses that have symbols
+pub fn disassemble_with_symbols(code: &[u8], profile: Profile, symbols: &Symbols) -> (String, usize) {
+    if code.len() < 2 {
+        return match code.first() {
+            Some(byte) => (format!("DB 0x{byte:02X}"), 1),
+            None => (String::new(), 0)
+        }
+    }
+
+    let opcode = u16::from_be_bytes([code[0], code[1]]);
+    let Some(form) = OPCODE_FORMS.iter().find(|form| opcode & form.mask == form.opcode && form.set.is_supported(profile)) else {
+        return data_word(code)
+    };
+
+    // XO-CHIP's long load reads its address from the next two bytes
+    let long_address = code.get(2..4).map(|address| u16::from_be_bytes([address[0], address[1]]));
+    if form.pattern.contains("long") && long_address.is_none() { return data_word(code) }
+
+    let fields = Fields::of(opcode);
+    let words: Vec<String> = form.pattern.split(' ').map(|word| {
+        let (operand, comma) = match word.strip_suffix(',') {
+            Some(operand) => (operand, ","),
+            None => (word, "")
+        };
+        let text = match operand {
+            "Vx" => format!("V{:X}", fields.x),
+            "Vy" => format!("V{:X}", fields.y),
+            "x" => fields.x.to_string(),
+            "n" => fields.n.to_string(),
+            "kk" => format!("0x{:02X}", fields.kk),
+            "nnn" => symbols.name(fields.nnn).map_or_else(|| format!("0x{:03X}", fields.nnn), String::from),
+            "long" => symbols.name(long_address.unwrap_or(0)).map_or_else(|| format!("0x{:04X}", long_address.unwrap_or(0)), String::from),
+            _ => String::from(operand)
+        };
+        text + comma
+    }).collect();
+    (words.join(" "), form.length())
+}
+
+// Unrecognized opcodes are shown as data
+fn data_word(code: &[u8]) -> (String, usize) {
+    (format!("DW 0x{:02X}{:02X}", code[0], code[1]), 2)
+}
+
+// Disassembles a rom loaded at the start address into lines of addresses, bytes, and mnemonics
+// Symbols name the addresses that instructions use and label the lines they start
+pub fn disassemble_rom(rom: &[u8], profile: Profile, start: u16, symbols: &Symbols) -> String {
+    let mut listing = String::new();
+    let mut offset = 0;
+    while offset < rom.len() {
+        if let Some(name) = symbols.name((start as usize + offset) as u16) { listing += &format!("{name}:\n"); }
+        let (mnemonic, length) = disassemble_with_symbols(&rom[offset..], profile, symbols);
+        let bytes: String = rom[offset..offset + length].iter().map(|byte| format!("{byte:02X}")).collect();
+        listing += &format!("0x{:03X}  {bytes:<8}  {mnemonic}\n", start as usize + offset);
+        offset += length;
+    }
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassembles_by_profile() {
+        assert_eq!(disassemble(&[0xD0, 0x15], Profile::Chip8), (String::from("DRW V0, V1, 5"), 2));
+        assert_eq!(disassemble(&[0x00, 0xFF], Profile::Schip), (String::from("HIGH"), 2));
+        assert_eq!(disassemble(&[0x00, 0xFF], Profile::Chip8), (String::from("SYS 0x0FF"), 2));
+        assert_eq!(disassemble(&[0x50, 0x01], Profile::Chip8), (String::from("DW 0x5001"), 2));
+        assert_eq!(disassemble(&[0xF0, 0x00, 0x12, 0x34], Profile::XoChip).1, 4);
+        assert_eq!(disassemble(&[0x12], Profile::Chip8), (String::from("DB 0x12"), 1));
+    }
+
+    #[test]
+    fn names_symbols() {
+        let mut symbols = Symbols::default();
+        symbols.insert(0x204, "loop");
+        let listing = disassemble_rom(&[0x00, 0xE0, 0x60, 0x01, 0x12, 0x04], Profile::Chip8, 0x200, &symbols);
+        assert_eq!(listing, "0x200  00E0      CLS\n0x202  6001      LD V0, 0x01\nloop:\n0x204  1204      JP loop\n");
+    }
+}
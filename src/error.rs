@@ -0,0 +1,61 @@
+use std::fmt;
+
+// Why the emulator stopped, where emulation errors carry the failing opcode and the address it was fetched from
+#[derive(Clone, PartialEq, Debug)]
+pub enum Chip8Error {
+    InvalidProgramCounter{address: u16},
+    UnsupportedOpcode{opcode: u16, address: u16},
+    StackOverflow{address: u16},
+    StackUnderflow{address: u16},
+    InvalidMemoryAccess{opcode: u16, address: u16, index: u16}, // The index register points outside the ram the instruction may use
+    RplFlagOverflow{opcode: u16, address: u16},                 // SCHIP's FX75 and FX85 with x above 7
+    RomTooLarge{size: usize, capacity: usize},
+    Assembly{line: usize, message: &'static str},               // Source the assembler couldn't read, by its line number
+    Rom(&'static str),                                          // A rom that couldn't be read
+    InvalidSaveState(&'static str),
+    Usage(&'static str),                                        // Invalid command line options or configuration
+    Sdl{action: &'static str, error: String},                   // A failed SDL call, with SDL's description of it
+    Other(&'static str)
+}
+
+impl Chip8Error {
+    pub fn sdl(action: &'static str, error: impl fmt::Display) -> Chip8Error {
+        Chip8Error::Sdl{action, error: error.to_string()}
+    }
+
+    // Whether the program being emulated failed, rather than the emulator or its input
+    pub fn is_crash(&self) -> bool {
+        matches!(self, Chip8Error::InvalidProgramCounter{..} | Chip8Error::UnsupportedOpcode{..} | Chip8Error::StackOverflow{..}
+            | Chip8Error::StackUnderflow{..} | Chip8Error::InvalidMemoryAccess{..} | Chip8Error::RplFlagOverflow{..})
+    }
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Chip8Error::InvalidProgramCounter{address} => write!(f, "Invalid program counter address 0x{address:03X}!"),
+            Chip8Error::UnsupportedOpcode{opcode, address} => write!(f, "Unsupported opcode 0x{opcode:04X} at 0x{address:03X}!"),
+            Chip8Error::StackOverflow{address} => write!(f, "Stack overflow on function call at 0x{address:03X}!"),
+            Chip8Error::StackUnderflow{address} => write!(f, "Stack underflow on function return at 0x{address:03X}!"),
+            Chip8Error::InvalidMemoryAccess{opcode, address, index} =>
+                write!(f, "Invalid memory access at 0x{index:03X} by opcode 0x{opcode:04X} at 0x{address:03X}!"),
+            Chip8Error::RplFlagOverflow{opcode, address} =>
+                write!(f, "SCHIP only has 8 RPL user flags, but opcode 0x{opcode:04X} at 0x{address:03X} used more!"),
+            Chip8Error::RomTooLarge{size, capacity} =>
+                write!(f, "The rom is too large for the ram! It has {size} bytes, but only {capacity} fit."),
+            Chip8Error::Assembly{line, message} => write!(f, "Line {line}: {message}"),
+            Chip8Error::Sdl{action, error} => write!(f, "{action} {error}"),
+            Chip8Error::Rom(message) | Chip8Error::InvalidSaveState(message) | Chip8Error::Usage(message) | Chip8Error::Other(message) =>
+                write!(f, "{message}")
+        }
+    }
+}
+
+impl std::error::Error for Chip8Error {}
+
+// Messages from the rest of the emulator, like reading files, keep their text
+impl From<&'static str> for Chip8Error {
+    fn from(message: &'static str) -> Chip8Error {
+        Chip8Error::Other(message)
+    }
+}
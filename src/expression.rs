@@ -0,0 +1,226 @@
+use std::fmt;
+use crate::{chip8::Chip8, error::Chip8Error, symbols::Symbols};
+
+// Expressions over the machine's state, like "V3 == 0x1F && I > 0x300", for conditional breakpoints and the debugger
+// Operands are numbers, V0 to VF, I, PC, DT, ST, SP, symbols, and [<Address>] for a byte of ram
+// Operators from loosest to tightest are ||, &&, comparisons, |, ^, &, + and -, then ! and - before an operand
+#[derive(Clone)]
+pub struct Expression {
+    text: String,
+    node: Node
+}
+
+#[derive(Clone)]
+enum Node {
+    Number(u32),
+    Register(usize),
+    Index,
+    ProgramCounter,
+    DelayTimer,
+    SoundTimer,
+    StackPointer,
+    Memory(Box<Node>),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>)
+}
+
+#[derive(Clone, Copy)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Add,
+    Subtract
+}
+
+impl Operator {
+    fn parse(token: &str) -> Option<Operator> {
+        Some(match token {
+            "||" => Operator::Or,
+            "&&" => Operator::And,
+            "==" => Operator::Equal,
+            "!=" => Operator::NotEqual,
+            "<" => Operator::Less,
+            "<=" => Operator::LessEqual,
+            ">" => Operator::Greater,
+            ">=" => Operator::GreaterEqual,
+            "|" => Operator::BitOr,
+            "^" => Operator::BitXor,
+            "&" => Operator::BitAnd,
+            "+" => Operator::Add,
+            "-" => Operator::Subtract,
+            _ => return None
+        })
+    }
+
+    // Higher precedences bind tighter
+    fn precedence(self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Equal | Operator::NotEqual | Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => 3,
+            Operator::BitOr => 4,
+            Operator::BitXor => 5,
+            Operator::BitAnd => 6,
+            Operator::Add | Operator::Subtract => 7
+        }
+    }
+
+    fn apply(self, left: u32, right: u32) -> u32 {
+        match self {
+            Operator::Or => (left != 0 || right != 0) as u32,
+            Operator::And => (left != 0 && right != 0) as u32,
+            Operator::Equal => (left == right) as u32,
+            Operator::NotEqual => (left != right) as u32,
+            Operator::Less => (left < right) as u32,
+            Operator::LessEqual => (left <= right) as u32,
+            Operator::Greater => (left > right) as u32,
+            Operator::GreaterEqual => (left >= right) as u32,
+            Operator::BitOr => left | right,
+            Operator::BitXor => left ^ right,
+            Operator::BitAnd => left & right,
+            Operator::Add => left.wrapping_add(right),
+            Operator::Subtract => left.wrapping_sub(right)
+        }
+    }
+}
+
+impl Expression {
+    // Reads an expression, where numbers are decimal or hexadecimal with 0x and symbols stand for their addresses
+    pub fn parse(text: &str, symbols: &Symbols) -> Result<Expression, Chip8Error> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser{tokens, position: 0, symbols};
+        let node = parser.binary(0)?;
+        if parser.position != parser.tokens.len() { return Err("Unexpected text after the expression!".into()) }
+        Ok(Expression{text: text.trim().to_string(), node})
+    }
+
+    pub fn evaluate(&self, chip8: &Chip8) -> u32 {
+        evaluate(&self.node, chip8)
+    }
+
+    // Any value other than zero is true
+    pub fn is_true(&self, chip8: &Chip8) -> bool {
+        self.evaluate(chip8) != 0
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+fn evaluate(node: &Node, chip8: &Chip8) -> u32 {
+    match node {
+        Node::Number(value) => *value,
+        Node::Register(register) => chip8.registers()[*register] as u32,
+        Node::Index => chip8.index() as u32,
+        Node::ProgramCounter => chip8.pc() as u32,
+        Node::DelayTimer => chip8.delay_timer() as u32,
+        Node::SoundTimer => chip8.sound_timer() as u32,
+        Node::StackPointer => chip8.stack_frames().len() as u32,
+        Node::Memory(address) => chip8.memory().get(evaluate(address, chip8) as usize).copied().unwrap_or(0) as u32,
+        Node::Not(operand) => (evaluate(operand, chip8) == 0) as u32,
+        Node::Negate(operand) => evaluate(operand, chip8).wrapping_neg(),
+        Node::Binary(operator, left, right) => operator.apply(evaluate(left, chip8), evaluate(right, chip8))
+    }
+}
+
+// Splits the text into names and numbers, and operators of one or two characters
+fn tokenize(text: &str) -> Result<Vec<&str>, Chip8Error> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(character) = rest.chars().next() {
+        let length = match character {
+            _ if character.is_ascii_alphanumeric() || character == '_' =>
+                rest.find(|character: char| !character.is_ascii_alphanumeric() && character != '_').unwrap_or(rest.len()),
+            _ if ["||", "&&", "==", "!=", "<=", ">="].iter().any(|operator| rest.starts_with(operator)) => 2,
+            '<' | '>' | '|' | '^' | '&' | '+' | '-' | '!' | '(' | ')' | '[' | ']' => 1,
+            _ => return Err("Unknown character in the expression!".into())
+        };
+        tokens.push(&rest[..length]);
+        rest = rest[length..].trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    position: usize,
+    symbols: &'a Symbols
+}
+
+impl Parser<'_> {
+    // Parses operands joined by operators binding tighter than the minimum precedence
+    fn binary(&mut self, minimum_precedence: u8) -> Result<Node, Chip8Error> {
+        let mut left = self.unary()?;
+        while let Some(operator) = self.tokens.get(self.position).and_then(|token| Operator::parse(token))
+        && operator.precedence() > minimum_precedence {
+            self.position += 1;
+            let right = self.binary(operator.precedence())?;
+            left = Node::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Node, Chip8Error> {
+        let Some(&token) = self.tokens.get(self.position) else { return Err("Expected an operand!".into()) };
+        self.position += 1;
+        match token {
+            "!" => Ok(Node::Not(Box::new(self.unary()?))),
+            "-" => Ok(Node::Negate(Box::new(self.unary()?))),
+            "(" => {
+                let node = self.binary(0)?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            "[" => {
+                let node = self.binary(0)?;
+                self.expect("]")?;
+                Ok(Node::Memory(Box::new(node)))
+            }
+            _ => operand(token, self.symbols).ok_or_else(|| "Unknown operand in the expression!".into())
+        }
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), Chip8Error> {
+        match self.tokens.get(self.position) {
+            Some(&token) if token == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err("Unclosed bracket in the expression!".into())
+        }
+    }
+}
+
+// Names of the machine's state are case insensitive, and symbols take precedence over numbers
+fn operand(token: &str, symbols: &Symbols) -> Option<Node> {
+    if let Some(address) = symbols.address(token) { return Some(Node::Number(address as u32)) }
+    let uppercase = token.to_ascii_uppercase();
+    match uppercase.as_str() {
+        "I" => return Some(Node::Index),
+        "PC" => return Some(Node::ProgramCounter),
+        "DT" => return Some(Node::DelayTimer),
+        "ST" => return Some(Node::SoundTimer),
+        "SP" => return Some(Node::StackPointer),
+        _ => ()
+    }
+    if let Some(digit) = uppercase.strip_prefix('V') && digit.len() == 1 {
+        return usize::from_str_radix(digit, 16).ok().map(Node::Register)
+    }
+    match uppercase.strip_prefix("0X") {
+        Some(digits) => u32::from_str_radix(digits, 16).ok(),
+        None => uppercase.parse().ok()
+    }.map(Node::Number)
+}
@@ -0,0 +1,67 @@
+#[cfg(feature = "sdl")]
+use sdl3::{pixels::{Color, PixelFormat}, render::{BlendMode, Canvas, FRect, ScaleMode, Texture, TextureCreator},
+    video::{Window, WindowContext}};
+
+// Resolution of the vignette, which is smoothed by linear scaling
+const VIGNETTE_WIDTH: u32 = 128;
+const VIGNETTE_HEIGHT: u32 = 64;
+
+// Post processing drawn over the chip8 texture before presenting
+#[derive(Clone, Copy, PartialEq)]
+pub enum DisplayFilter {
+    None,
+    Scanlines,
+    Crt // Scanlines with darkened edges
+}
+
+impl DisplayFilter {
+    pub fn parse(name: &str) -> Option<DisplayFilter> {
+        match name {
+            "none" => Some(DisplayFilter::None),
+            "scanlines" => Some(DisplayFilter::Scanlines),
+            "crt" => Some(DisplayFilter::Crt),
+            _ => None
+        }
+    }
+}
+
+// Darkens the lower half of every chip8 row, in logical coordinates so the lines scale with the picture
+#[cfg(feature = "sdl")]
+pub fn draw_scanlines(canvas: &mut Canvas<Window>, (width, height): (u32, u32)) -> Result<(), &'static str> {
+    let background_color = canvas.draw_color();
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 96));
+
+    let rows: Vec<FRect> = (0..height).map(|row| FRect::new(0.0, row as f32 + 0.5, width as f32, 0.5)).collect();
+    let result = canvas.fill_rects(&rows);
+
+    canvas.set_draw_color(background_color);
+    canvas.set_blend_mode(BlendMode::None);
+    match result {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Failed to draw scanlines!")
+    }
+}
+
+// Creates a texture that fades to black toward the corners, drawn over the whole screen by the crt filter
+#[cfg(feature = "sdl")]
+pub fn create_vignette(texture_creator: &TextureCreator<WindowContext>, pixel_format: PixelFormat) -> Option<Texture<'_>> {
+    let mut pixels = Vec::with_capacity((VIGNETTE_WIDTH * VIGNETTE_HEIGHT * 4) as usize);
+    for y in 0..VIGNETTE_HEIGHT {
+        for x in 0..VIGNETTE_WIDTH {
+            // Elliptical distance from the center, which is 1 at the middle of each edge
+            let dx = (x as f32 + 0.5) / VIGNETTE_WIDTH as f32 * 2.0 - 1.0;
+            let dy = (y as f32 + 0.5) / VIGNETTE_HEIGHT as f32 * 2.0 - 1.0;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            let alpha = ((distance - 0.6) / 0.8).clamp(0.0, 1.0) * 200.0;
+            pixels.extend_from_slice(&((alpha as u32) << 24).to_ne_bytes());
+        }
+    }
+
+    let mut vignette = texture_creator.create_texture_static(pixel_format, VIGNETTE_WIDTH, VIGNETTE_HEIGHT).ok()?;
+    vignette.update(None, &pixels, VIGNETTE_WIDTH as usize * 4).ok()?;
+    vignette.set_blend_mode(BlendMode::Blend);
+    vignette.set_scale_mode(ScaleMode::Linear);
+    Some(vignette)
+}
@@ -0,0 +1,51 @@
+use std::ops::Range;
+use crate::{chip8::Chip8, error::Chip8Error};
+
+// The display's palette indices row by row, where only the dirty rows changed since the last present
+pub struct FrameBuffer<'a> {
+    pub pixels: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+    pub dirty_rows: Range<u32> // Empty when nothing was drawn
+}
+
+// Chip8 keys 0 to F, true while held
+pub type Keys = [bool; 16];
+
+// Video, audio, and input of a host, so the SDL window, terminal, browser, and headless runs share one seam
+pub trait Frontend {
+    // Shows the frame, which is called every host frame so effects like phosphor fading keep animating
+    fn present(&mut self, frame_buffer: &FrameBuffer) -> Result<(), Chip8Error>;
+
+    // Plays or silences the buzzer, called every host frame with whether the sound timer is running
+    fn beep(&mut self, is_on: bool);
+
+    // Reads which chip8 keys are held on the host
+    fn poll_keys(&mut self) -> Keys;
+}
+
+impl Chip8 {
+    // Polls the frontend's keys, emulates the nanoseconds, and then beeps and presents
+    pub fn run_frontend(&mut self, frontend: &mut impl Frontend, nanos: f32) -> Option<Chip8Error> {
+        self.set_keys(frontend.poll_keys());
+        if let Some(error) = self.run(nanos) { return Some(error) }
+        self.present(frontend).err()
+    }
+
+    // Presses and releases only the keys that changed, since a release is an event FX0A waits for
+    pub fn set_keys(&mut self, keys: Keys) {
+        for (key, is_pressed) in keys.into_iter().enumerate() {
+            if self.keys()[key] != is_pressed { self.set_key(key, is_pressed) }
+        }
+    }
+
+    // Sends the buzzer state and the rows changed since the last present, for frontends that apply keys themselves
+    // Shows the last whole frame, except while paused, where stepping shows each draw as it happens
+    pub fn present(&mut self, frontend: &mut impl Frontend) -> Result<(), Chip8Error> {
+        if self.is_paused() { self.latch_frame(); }
+        frontend.beep(self.sound_timer() > 1);
+        let dirty_rows = self.take_dirty_rows().unwrap_or(0..0);
+        let (width, height) = self.display_mode().size();
+        frontend.present(&FrameBuffer{pixels: self.front_buffer(), width, height, dirty_rows})
+    }
+}
@@ -0,0 +1,70 @@
+use sdl3::{gamepad::{Axis, Button, Gamepad}, GamepadSubsystem};
+
+// Stick deflection before a direction counts as pressed
+const AXIS_DEADZONE: i16 = 16000;
+const AXIS_NEGATIVE_DEADZONE: i16 = -AXIS_DEADZONE;
+
+// Directions on the classic 2, 4, 6, 8 keypad cross with 5 as the action key
+pub const DEFAULT_GAMEPAD_MAP: [(Button, usize); 12] = [
+    (Button::DPadUp, 0x2), (Button::DPadLeft, 0x4), (Button::DPadRight, 0x6), (Button::DPadDown, 0x8),
+    (Button::South, 0x5), (Button::East, 0x6), (Button::West, 0x4), (Button::North, 0x0),
+    (Button::LeftShoulder, 0x1), (Button::RightShoulder, 0x3), (Button::Back, 0xE), (Button::Start, 0xF),
+];
+
+// Opened gamepads, which map buttons and the left stick to chip8 keys
+pub struct Gamepads {
+    subsystem: GamepadSubsystem,
+    opened: Vec<Gamepad>,
+    button_map: Vec<(Button, usize)>,
+    stick_directions: [Option<Button>; 2] // Horizontal and vertical d-pad buttons held by the left stick
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GamepadSubsystem, button_map: Vec<(Button, usize)>) -> Gamepads {
+        Gamepads{subsystem, opened: Vec::new(), button_map, stick_directions: [None; 2]}
+    }
+
+    // Opens a gamepad when it's plugged in, including those connected at startup
+    pub fn add(&mut self, id: u32) {
+        match self.subsystem.open(id) {
+            Ok(gamepad) => {
+                println!("Connected {}", gamepad.name().unwrap_or(String::from("gamepad")));
+                self.opened.push(gamepad);
+            }
+            Err(_) => println!("Failed to open gamepad!")
+        }
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.opened.retain(|gamepad| gamepad.id().ok() != Some(id));
+    }
+
+    // Passes the chip8 keys mapped to a button to set_key
+    pub fn press(&self, button: Button, is_pressed: bool, set_key: &mut impl FnMut(usize, bool)) {
+        for &(mapped_button, chip8_key) in &self.button_map {
+            if mapped_button == button { set_key(chip8_key, is_pressed); }
+        }
+    }
+
+    // Treats the left stick like the d-pad once it leaves the deadzone
+    pub fn move_axis(&mut self, axis: Axis, value: i16, set_key: &mut impl FnMut(usize, bool)) {
+        let (index, negative, positive) = match axis {
+            Axis::LeftX => (0, Button::DPadLeft, Button::DPadRight),
+            Axis::LeftY => (1, Button::DPadUp, Button::DPadDown),
+            _ => return
+        };
+
+        let direction = match value {
+            ..=AXIS_NEGATIVE_DEADZONE => Some(negative),
+            AXIS_DEADZONE.. => Some(positive),
+            _ => None
+        };
+
+        // Releases the previous direction before pressing the new one
+        if direction != self.stick_directions[index] {
+            if let Some(button) = self.stick_directions[index] { self.press(button, false, set_key); }
+            if let Some(button) = direction { self.press(button, true, set_key); }
+            self.stick_directions[index] = direction;
+        }
+    }
+}
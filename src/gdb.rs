@@ -0,0 +1,261 @@
+use std::{io::{BufReader, Read, Write}, net::{TcpListener, TcpStream}, ops::Range, sync::mpsc::{self, Receiver, Sender}, thread};
+use chip8_emulator::{Chip8, Chip8Error, Watch};
+
+// Messages from the connection thread to the main thread
+enum GdbMessage {
+    Connected(TcpStream),
+    Packet(String),
+    Interrupt,
+    Disconnected
+}
+
+// GDB remote serial protocol stub, where the main thread owns the chip8 and answers packets
+// Registers are V0 to VF, then I and PC as little endian words, then SP, DT, and ST
+pub struct GdbStub {
+    messages: Receiver<GdbMessage>,
+    connection: Option<TcpStream>,
+    is_running: bool
+}
+
+impl GdbStub {
+    // Accepts one debugger connection at a time on a separate thread
+    pub fn listen(port: u16) -> Option<GdbStub> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).ok()?;
+        let (sender, receiver) = mpsc::channel::<GdbMessage>();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let Ok(writer) = stream.try_clone() else { continue };
+                if sender.send(GdbMessage::Connected(writer)).is_err() { return }
+
+                read_packets(stream, &sender);
+                if sender.send(GdbMessage::Disconnected).is_err() { return }
+            }
+        });
+
+        println!("Listening for gdb on port {port}");
+        Some(GdbStub{messages: receiver, connection: None, is_running: false})
+    }
+
+    // Answers pending packets and reports when a continued machine stops
+    pub fn update(&mut self, chip8: &mut Chip8) -> Option<Chip8Error> {
+        while let Ok(message) = self.messages.try_recv() {
+            match message {
+                // Stops the machine so the debugger attaches to a halted target
+                GdbMessage::Connected(connection) => {
+                    println!("Gdb connected");
+                    chip8.pause();
+                    self.connection = Some(connection);
+                    self.is_running = false;
+                }
+
+                GdbMessage::Packet(packet) => if let Some(error) = self.execute(chip8, &packet) {
+                    return Some(error)
+                }
+
+                GdbMessage::Interrupt => chip8.pause(),
+
+                GdbMessage::Disconnected => {
+                    println!("Gdb disconnected");
+                    self.connection = None;
+                    if chip8.is_paused() { chip8.resume(); }
+                }
+            }
+        }
+
+        // Sends a stop reply once a continue hits a breakpoint, watchpoint, or an interrupt
+        if self.is_running && chip8.is_paused() {
+            self.is_running = false;
+            let reply = match chip8.watch_hit() {
+                Some(hit) => format!("T05{}:{:x};", match hit.is_write { true => "watch", false => "rwatch" }, hit.address),
+                None => String::from("S05")
+            };
+            self.reply(&reply);
+        }
+        None
+    }
+
+    fn execute(&mut self, chip8: &mut Chip8, packet: &str) -> Option<Chip8Error> {
+        // Splits after the first character, which can be a multibyte replacement for invalid UTF-8
+        let (command, arguments) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match command {
+            "?" => String::from("S05"),
+            "g" => encode_hex(&registers(chip8)),
+            "G" => match decode_hex(arguments) {
+                Some(bytes) if bytes.len() >= 20 => {
+                    chip8.registers_mut().copy_from_slice(&bytes[..16]);
+                    chip8.set_index(u16::from_le_bytes([bytes[16], bytes[17]]));
+                    chip8.set_pc(u16::from_le_bytes([bytes[18], bytes[19]]));
+                    String::from("OK")
+                }
+                _ => String::from("E01")
+            }
+
+            // Reads one register by its number
+            "p" => match usize::from_str_radix(arguments, 16) {
+                Ok(register) => match register {
+                    0..16 => encode_hex(&[chip8.registers()[register]]),
+                    16 => encode_hex(&chip8.index().to_le_bytes()),
+                    17 => encode_hex(&chip8.pc().to_le_bytes()),
+                    18..21 => encode_hex(&registers(chip8)[register + 2..register + 3]),
+                    _ => String::from("E01")
+                }
+                Err(_) => String::from("E01")
+            }
+
+            // Reads or writes memory at "address,length"
+            "m" => match parse_range(arguments).and_then(|range| chip8.memory().get(range)) {
+                Some(bytes) => encode_hex(bytes),
+                None => String::from("E01")
+            }
+            "M" => match arguments.split_once(':') {
+                Some((range, data)) => match (parse_range(range), decode_hex(data)) {
+                    (Some(range), Some(bytes)) if bytes.len() == range.len() => {
+                        match chip8.memory_mut().get_mut(range) {
+                            Some(destination) => {
+                                destination.copy_from_slice(&bytes);
+                                String::from("OK")
+                            }
+                            None => String::from("E01")
+                        }
+                    }
+                    _ => String::from("E01")
+                }
+                None => String::from("E01")
+            }
+
+            // Continues until a breakpoint, or executes one instruction
+            "c" => {
+                chip8.resume();
+                self.is_running = true;
+                return None
+            }
+            // A failed step stops with SIGILL or SIGSEGV, then returns the error for the emulator's error policy
+            "s" => {
+                let result = chip8.step();
+                chip8.pause();
+                match result {
+                    Ok(_) => String::from("S05"),
+                    Err(error) => {
+                        self.reply(match error {
+                            Chip8Error::UnsupportedOpcode{..} => "S04",
+                            _ => "S0b"
+                        });
+                        return Some(error)
+                    }
+                }
+            }
+
+            // Software and hardware breakpoints are both program counter breakpoints, and types 2 to 4 are watchpoints
+            "Z" | "z" => match arguments.split(',').nth(1).and_then(|address| u16::from_str_radix(address, 16).ok()) {
+                Some(address) if ('0'..='4').contains(&arguments.chars().next().unwrap_or(' ')) => {
+                    let watch = match arguments.chars().next() {
+                        Some('2') => Some(Watch::Write),
+                        Some('3') => Some(Watch::Read),
+                        Some('4') => Some(Watch::Access),
+                        _ => None
+                    };
+                    match (command, watch) {
+                        ("Z", None) => chip8.set_breakpoint(address),
+                        ("Z", Some(watch)) => chip8.set_watchpoint(address, watch),
+                        (_, None) => { chip8.remove_breakpoint(address); }
+                        (_, Some(_)) => { chip8.remove_watchpoint(address); }
+                    }
+                    String::from("OK")
+                }
+                _ => String::new()
+            }
+
+            // Detaches and lets the machine keep running
+            "D" | "k" => {
+                self.reply("OK");
+                self.connection = None;
+                if chip8.is_paused() { chip8.resume(); }
+                return None
+            }
+
+            "H" => String::from("OK"),
+            "q" if arguments.starts_with("Supported") => String::from("PacketSize=4000"),
+            "q" if arguments.starts_with("Attached") => String::from("1"),
+
+            // An empty reply tells gdb a packet is unsupported
+            _ => String::new()
+        };
+
+        self.reply(&reply);
+        None
+    }
+
+    // Sends a packet with its checksum
+    fn reply(&mut self, data: &str) {
+        let Some(connection) = &mut self.connection else { return };
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        if connection.write_all(format!("${data}#{checksum:02x}").as_bytes()).is_err() {
+            println!("Failed to reply to gdb!");
+        }
+    }
+}
+
+// Parses packets from the connection until it closes, acknowledging each one
+fn read_packets(stream: TcpStream, sender: &Sender<GdbMessage>) {
+    let Ok(mut acknowledger) = stream.try_clone() else { return };
+    let mut bytes = BufReader::new(stream).bytes();
+
+    while let Some(Ok(byte)) = bytes.next() {
+        match byte {
+            // Ctrl-C interrupts a running machine
+            0x03 if sender.send(GdbMessage::Interrupt).is_err() => return,
+
+            b'$' => {
+                // Reads packet data until the checksum
+                let mut data = Vec::new();
+                loop {
+                    match bytes.next() {
+                        Some(Ok(b'#')) => break,
+                        Some(Ok(byte)) => data.push(byte),
+                        _ => return
+                    }
+                }
+
+                let checksum: Vec<u8> = bytes.by_ref().take(2).filter_map(Result::ok).collect();
+                let expected = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+                let is_valid = decode_hex(&String::from_utf8_lossy(&checksum)) == Some(vec![expected]);
+
+                // Asks for a retransmission when the checksum is wrong
+                let ack: &[u8] = match is_valid { true => b"+", false => b"-" };
+                if acknowledger.write_all(ack).is_err() { return }
+                if is_valid && sender.send(GdbMessage::Packet(String::from_utf8_lossy(&data).into_owned())).is_err() {
+                    return
+                }
+            }
+
+            // Acknowledgements from gdb need no response
+            _ => ()
+        }
+    }
+}
+
+fn registers(chip8: &Chip8) -> Vec<u8> {
+    let mut bytes = chip8.registers().to_vec();
+    bytes.extend_from_slice(&chip8.index().to_le_bytes());
+    bytes.extend_from_slice(&chip8.pc().to_le_bytes());
+    bytes.extend_from_slice(&[chip8.stack_frames().len().min(u8::MAX as usize) as u8, chip8.delay_timer(), chip8.sound_timer()]);
+    bytes
+}
+
+// Parses "address,length" in hexadecimal, where a range that overflows the address is invalid
+fn parse_range(text: &str) -> Option<Range<usize>> {
+    let (address, length) = text.split_once(',')?;
+    let address = usize::from_str_radix(address, 16).ok()?;
+    Some(address..address.checked_add(usize::from_str_radix(length, 16).ok()?)?)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) { return None }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
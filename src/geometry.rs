@@ -0,0 +1,36 @@
+use sdl3::{VideoSubsystem, video::Window};
+use crate::toml::config_path;
+
+// Where the window was and its size, saved as "<X> <Y> <Width> <Height>" in the config directory so the next run opens there
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32
+}
+
+impl WindowGeometry {
+    // Reads the last geometry, unless its center is no longer on a display, like after a monitor is unplugged
+    pub fn load(video: &VideoSubsystem) -> Option<WindowGeometry> {
+        let text = std::fs::read_to_string(config_path("window.txt")?).ok()?;
+        let mut numbers = text.split_whitespace().map(str::parse::<i32>);
+        let mut next = || numbers.next()?.ok();
+        let (x, y, width, height) = (next()?, next()?, next()?, next()?);
+        if width <= 0 || height <= 0 { return None }
+
+        let center = (x + width / 2, y + height / 2);
+        let is_on_display = video.displays().ok()?.iter().any(|display| display.get_bounds().is_ok_and(|bounds| bounds.contains_point(center)));
+        is_on_display.then_some(WindowGeometry{x, y, width: width as u32, height: height as u32})
+    }
+
+    pub fn save(window: &Window) {
+        let (x, y) = window.position();
+        let (width, height) = window.size();
+        if let Some(path) = config_path("window.txt") {
+            if let Some(directory) = path.parent() { let _ = std::fs::create_dir_all(directory); }
+            if std::fs::write(path, format!("{x} {y} {width} {height}\n")).is_err() {
+                println!("Failed to save the window's position!");
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::ops::Range;
+use chip8_emulator::{Chip8, Observer, Symbols};
+use crate::debugger::{parse_address, print_state};
+
+// Hooks read from a file for cheats, trainers, and tracing, one per line as "<Event>: <Action>; <Action>"
+// Events are "frame", "exec <Address>", "read <Address>", and "write <Address>", where numbers are hexadecimal
+// and addresses may be the names of symbols
+// Actions are "set V<X> <Byte>", "set I <Address>", "poke <Address> <Byte>", "print", and "pause"
+// This is a fixed list of hooks rather than an embedded scripting engine, with no variables, conditions, or loops
+pub struct HookFile {
+    hooks: Vec<(Event, Vec<Action>)>,
+    symbols: Symbols // Names the addresses that print shows
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Event {
+    Frame,
+    Execute(u16),
+    Read(u16),
+    Write(u16)
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    SetRegister(usize, u8),
+    SetIndex(u16),
+    Poke(u16, u8),
+    Print,
+    Pause
+}
+
+impl HookFile {
+    pub fn load(path: &str, symbols: &Symbols) -> Result<HookFile, &'static str> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return Err("Path to the hook file is invalid!")
+        };
+
+        let mut hooks = Vec::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue }
+
+            let Some((event, actions)) = line.split_once(':') else {
+                return Err("Hook file lines need an event and actions separated by a colon!")
+            };
+            let address = |text: &str| symbols.address(text).or_else(|| parse_address(text)).ok_or("Invalid address in the hook file!");
+            let event = match event.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["frame"] => Event::Frame,
+                ["exec", text] => Event::Execute(address(text)?),
+                ["read", text] => Event::Read(address(text)?),
+                ["write", text] => Event::Write(address(text)?),
+                _ => return Err("Unknown event in the hook file! Expected frame, exec, read, or write.")
+            };
+            let actions = actions.split(';').map(|action| parse_action(action, symbols)).collect::<Result<Vec<_>, _>>()?;
+            hooks.push((event, actions));
+        }
+
+        println!("Loaded {} hooks from {path}", hooks.len());
+        Ok(HookFile{hooks, symbols: symbols.clone()})
+    }
+
+    // Runs the actions of every hook on the event
+    fn run(&self, chip8: &mut Chip8, is_event: impl Fn(Event) -> bool) {
+        for (event, actions) in &self.hooks {
+            if !is_event(*event) { continue }
+            for action in actions {
+                match *action {
+                    Action::SetRegister(register, value) => chip8.registers_mut()[register] = value,
+                    Action::SetIndex(address) => chip8.set_index(address),
+                    Action::Poke(address, value) => if let Err(error) = chip8.poke(address, value) { println!("{error}") },
+                    Action::Print => {
+                        println!("Hook at {}", self.symbols.describe(chip8.pc()));
+                        print_state(chip8, &self.symbols);
+                    }
+                    Action::Pause => chip8.pause()
+                }
+            }
+        }
+    }
+}
+
+impl Observer for HookFile {
+    fn frame_start(&mut self, chip8: &mut Chip8) {
+        self.run(chip8, |event| event == Event::Frame);
+    }
+
+    fn execute(&mut self, chip8: &mut Chip8, address: u16) {
+        self.run(chip8, |event| event == Event::Execute(address));
+    }
+
+    fn memory_read(&mut self, chip8: &mut Chip8, addresses: Range<usize>) {
+        self.run(chip8, |event| matches!(event, Event::Read(address) if addresses.contains(&(address as usize))));
+    }
+
+    fn memory_write(&mut self, chip8: &mut Chip8, addresses: Range<usize>) {
+        self.run(chip8, |event| matches!(event, Event::Write(address) if addresses.contains(&(address as usize))));
+    }
+}
+
+fn parse_action(text: &str, symbols: &Symbols) -> Result<Action, &'static str> {
+    let byte = |text: &str| u8::from_str_radix(text.strip_prefix("0x").unwrap_or(text), 16).ok();
+    let address = |text: &str| symbols.address(text).or_else(|| parse_address(text));
+    match text.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["set", "I" | "i", text] => Ok(Action::SetIndex(address(text).ok_or("Invalid address in the hook file!")?)),
+        ["set", register, value] => {
+            let register = register.strip_prefix(['V', 'v']).and_then(|digit| usize::from_str_radix(digit, 16).ok());
+            match (register, byte(value)) {
+                (Some(register), Some(value)) if register < 16 => Ok(Action::SetRegister(register, value)),
+                _ => Err("Invalid register or value in the hook file!")
+            }
+        }
+        ["poke", text, value] => match (address(text), byte(value)) {
+            (Some(address), Some(value)) => Ok(Action::Poke(address, value)),
+            _ => Err("Invalid address or value in the hook file!")
+        },
+        ["print"] => Ok(Action::Print),
+        ["pause"] => Ok(Action::Pause),
+        _ => Err("Unknown action in the hook file! Expected set, poke, print, or pause.")
+    }
+}
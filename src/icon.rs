@@ -0,0 +1,70 @@
+use chip8_emulator::Palette;
+use sdl3::{pixels::PixelFormat, surface::Surface, video::Window};
+
+// Side of the square icons in pixels, where each bit of the program's icon covers two by two
+const ICON_SIZE: usize = 32;
+
+// A chip with "C8" on it, one row a line with the leftmost pixel in the high bit
+const PROGRAM_ICON: [u16; 16] = [
+    0b0001001001001000,
+    0b0001001001001000,
+    0b0111111111111110,
+    0b0100000000000010,
+    0b0100000000000010,
+    0b0100111001110010,
+    0b0100100001010010,
+    0b0100100001110010,
+    0b0100100001010010,
+    0b0100111001110010,
+    0b0100000000000010,
+    0b0100000000000010,
+    0b0111111111111110,
+    0b0001001001001000,
+    0b0001001001001000,
+    0b0000000000000000
+];
+
+// The program's icon in the palette's colors, with the chip's body filled in the background and transparent around it
+pub fn program_icon(palette: &Palette) -> Vec<u32> {
+    let mut pixels = vec![0; ICON_SIZE * ICON_SIZE];
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let (row, column) = (i / ICON_SIZE / 2, i % ICON_SIZE / 2);
+        let is_lit = PROGRAM_ICON[row] & (0x8000 >> column) != 0;
+        let is_body = (3..12).contains(&row) && (2..14).contains(&column);
+        *pixel = match (is_lit, is_body) {
+            (true, _) => palette[1] | 0xFF000000,
+            (false, true) => palette[0] | 0xFF000000,
+            (false, false) => 0
+        };
+    }
+    pixels
+}
+
+// Fits the displayed frame into an icon, centered with transparent bars on the short sides
+pub fn rom_icon(frame: &[u32], (width, height): (u32, u32)) -> Vec<u32> {
+    let (width, height) = (width as usize, height as usize);
+    let scale = (ICON_SIZE as f32 / width as f32).min(ICON_SIZE as f32 / height as f32);
+    let (scaled_width, scaled_height) = ((width as f32 * scale) as usize, (height as f32 * scale) as usize);
+    let (left, top) = ((ICON_SIZE - scaled_width) / 2, (ICON_SIZE - scaled_height) / 2);
+
+    let mut pixels = vec![0; ICON_SIZE * ICON_SIZE];
+    for y in 0..scaled_height {
+        for x in 0..scaled_width {
+            pixels[(top + y) * ICON_SIZE + left + x] = frame[y * height / scaled_height * width + x * width / scaled_width] | 0xFF000000;
+        }
+    }
+    pixels
+}
+
+// Sets the window's icon from ARGB8888 pixels made by program_icon or rom_icon
+pub fn set_window_icon(window: &mut Window, pixels: &[u32], pixel_format: PixelFormat) -> Result<(), &'static str> {
+    let mut bytes: Vec<u8> = pixels.iter().flat_map(|pixel| pixel.to_ne_bytes()).collect();
+    let surface = match Surface::from_data(&mut bytes, ICON_SIZE as u32, ICON_SIZE as u32, ICON_SIZE as u32 * 4, pixel_format) {
+        Ok(surface) => surface,
+        Err(_) => return Err("Failed to create the icon surface!")
+    };
+    match window.set_icon(surface) {
+        true => Ok(()),
+        false => Err("Failed to set the window icon!")
+    }
+}
@@ -0,0 +1,18 @@
+// Frontend independent chip8 emulation core
+// The SDL3 binary in main.rs is one consumer, so other frontends can embed the core without SDL3
+mod asm;
+mod chip8;
+mod cosmac;
+mod decode;
+mod disasm;
+mod error;
+mod expression;
+mod frontend;
+#[cfg(feature = "threaded")]
+mod threaded;
+mod octo;
+mod state;
+mod symbols;
+#[cfg(feature = "wasm")]
+mod wasm;
+pub use crate::{asm::*, chip8::*, disasm::*, error::*, expression::*, frontend::*, octo::*, symbols::*};
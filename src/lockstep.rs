@@ -0,0 +1,143 @@
+use std::{collections::VecDeque, io::{BufReader, Read, Write}, net::{TcpListener, TcpStream}, sync::mpsc::{self, Receiver}, thread};
+use chip8_emulator::{Chip8, Chip8Error, Chip8Options, Keys};
+use crate::{crash, movie::MOVIE_FRAME_NANOS};
+
+const LOCKSTEP_MAGIC: &[u8; 4] = b"C8LS";
+
+// Both players emulate the same machine from the host's seed, and each frame runs once both players' keys for it arrive
+// Keys are sent as a little endian frame number and a bitmask of the held chip8 keys, a number of frames ahead
+pub struct Lockstep {
+    connection: TcpStream,
+    remote_inputs: Receiver<(u32, u16)>,
+    local_queue: VecDeque<u16>,
+    remote_queue: VecDeque<u16>,
+    input_delay: u32,
+    frame: u32,
+    is_sent: bool // The local keys for this frame's delayed frame were sent
+}
+
+impl Lockstep {
+    // Waits for one player, then sends the seed, input delay, and a hash of the rom and options they must match
+    pub fn host(port: u16, input_delay: u8, rom: &[u8], options: &Chip8Options) -> Result<(Lockstep, u64), &'static str> {
+        let listener = match TcpListener::bind(("0.0.0.0", port)) {
+            Ok(listener) => listener,
+            Err(_) => return Err("Failed to listen for netplay connections!")
+        };
+        println!("Waiting for a lockstep player on port {port}");
+        let mut connection = match listener.accept() {
+            Ok((connection, _)) => connection,
+            Err(_) => return Err("Failed to accept the lockstep player!")
+        };
+
+        let seed = rand::random::<u64>();
+        let mut handshake = LOCKSTEP_MAGIC.to_vec();
+        handshake.extend_from_slice(&seed.to_le_bytes());
+        handshake.push(input_delay);
+        handshake.extend_from_slice(&machine_hash(rom, options).to_le_bytes());
+        if connection.write_all(&handshake).is_err() {
+            return Err("Failed to send the lockstep handshake!")
+        }
+
+        println!("Lockstep player joined");
+        Ok((Lockstep::new(connection, input_delay)?, seed))
+    }
+
+    // Joins a host and reads its seed, failing when the rom or options differ
+    pub fn join(address: &str, rom: &[u8], options: &Chip8Options) -> Result<(Lockstep, u64), &'static str> {
+        let mut connection = match TcpStream::connect(address) {
+            Ok(connection) => connection,
+            Err(_) => return Err("Failed to join the netplay host!")
+        };
+
+        let mut handshake = [0; 21];
+        if connection.read_exact(&mut handshake).is_err() || !handshake.starts_with(LOCKSTEP_MAGIC) {
+            return Err("The netplay host isn't running lockstep!")
+        }
+        let seed = u64::from_le_bytes(handshake[4..12].try_into().unwrap());
+        let input_delay = handshake[12];
+        if u64::from_le_bytes(handshake[13..21].try_into().unwrap()) != machine_hash(rom, options) {
+            return Err("Lockstep needs the same rom, profile, clock, and quirks as the host!")
+        }
+
+        println!("Joined lockstep at {address}");
+        Ok((Lockstep::new(connection, input_delay)?, seed))
+    }
+
+    fn new(connection: TcpStream, input_delay: u8) -> Result<Lockstep, &'static str> {
+        let _ = connection.set_nodelay(true);
+        let reader = match connection.try_clone() {
+            Ok(reader) => reader,
+            Err(_) => return Err("Failed to read from the lockstep player!")
+        };
+
+        // Reads the other player's keys until the connection closes
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut input = [0; 6];
+            while reader.read_exact(&mut input).is_ok() {
+                let frame = u32::from_le_bytes([input[0], input[1], input[2], input[3]]);
+                if sender.send((frame, u16::from_le_bytes([input[4], input[5]]))).is_err() { return }
+            }
+        });
+
+        // Frames before the first delayed input run without keys on both sides
+        let delay_frames = vec![0; input_delay as usize];
+        Ok(Lockstep{connection, remote_inputs: receiver, local_queue: VecDeque::from(delay_frames.clone()),
+            remote_queue: VecDeque::from(delay_frames), input_delay: input_delay as u32, frame: 0, is_sent: false})
+    }
+
+    // Emulates one frame with both players' keys, or returns false while waiting for the other player
+    // Frames don't pass while paused, which stalls the other player too
+    pub fn run_frame(&mut self, chip8: &mut Chip8, local_keys: &Keys) -> Result<bool, Chip8Error> {
+        if chip8.is_paused() { return Ok(false) }
+
+        // Sends the local keys once for the frame they apply to
+        if !self.is_sent {
+            let keys = local_keys.iter().enumerate().fold(0, |mask, (key, &is_pressed)| mask | (is_pressed as u16) << key);
+            let mut input = (self.frame + self.input_delay).to_le_bytes().to_vec();
+            input.extend_from_slice(&keys.to_le_bytes());
+            if self.connection.write_all(&input).is_err() {
+                return Err("The lockstep player disconnected!".into())
+            }
+            self.local_queue.push_back(keys);
+            self.is_sent = true;
+        }
+
+        loop {
+            match self.remote_inputs.try_recv() {
+                Ok((frame, keys)) if frame == self.frame + self.remote_queue.len() as u32 => self.remote_queue.push_back(keys),
+                Ok(_) => return Err("The lockstep player sent keys out of order!".into()),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return Err("The lockstep player disconnected!".into())
+            }
+        }
+        let Some(remote_keys) = self.remote_queue.pop_front() else { return Ok(false) };
+        let local_keys = self.local_queue.pop_front().unwrap_or(0);
+
+        // Either player holding a key presses it
+        let keys = local_keys | remote_keys;
+        chip8.set_keys(std::array::from_fn(|key| keys >> key & 1 != 0));
+        if let Some(error) = chip8.run(MOVIE_FRAME_NANOS as f32) { return Err(crash::report(chip8, error)) }
+
+        self.frame += 1;
+        self.is_sent = false;
+        Ok(true)
+    }
+}
+
+// FNV-1a over the rom and the options that change emulation, so mismatched players fail to join instead of desyncing
+// The seed is left out, since the host sends its own
+fn machine_hash(rom: &[u8], options: &Chip8Options) -> u64 {
+    let quirks = &options.quirks;
+    let mut bytes = rom.to_vec();
+    bytes.extend_from_slice(&options.clock_hz.to_le_bytes());
+    bytes.extend_from_slice(&[options.profile as u8, quirks.is_drawsync as u8, quirks.is_shift_quirk as u8, quirks.is_vf_reset as u8,
+        quirks.is_index_increment as u8, quirks.is_sprite_clipping as u8, quirks.is_jump_quirk as u8,
+        quirks.is_key_release as u8, quirks.is_key_tone as u8, options.is_cosmac_timing as u8, options.is_permissive_memory as u8]);
+    bytes.extend_from_slice(&(options.profile.memory_size() as u64).to_le_bytes());
+    bytes.extend_from_slice(&(options.reserved_size as u64).to_le_bytes());
+    bytes.extend_from_slice(&options.start_address.to_le_bytes());
+    bytes.extend_from_slice(&options.stack_depth.map_or(u64::MAX, |depth| depth as u64).to_le_bytes()); // Unlimited as the largest depth
+    bytes.iter().fold(0xCBF29CE484222325, |hash: u64, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001B3))
+}
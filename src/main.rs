@@ -1,153 +1,690 @@
+// Release builds on windows don't open a console when double clicked, and attach to the one they were started from instead
+#![cfg_attr(all(windows, not(debug_assertions)), windows_subsystem = "windows")]
+// Terminal only builds leave out the window, which is the only user of much of the shared code
+#![cfg_attr(not(feature = "sdl"), allow(dead_code))]
+
 // Namespace imports
-use std::{slice::from_raw_parts, sync::{Arc, atomic::{AtomicI32, Ordering}}, thread::{sleep, yield_now}, time::{Duration, Instant}};
+use std::{cell::{Cell, RefCell}, path::Path, rc::Rc, process::ExitCode, sync::{Arc, atomic::{AtomicU64, Ordering}}, time::{Duration, SystemTime, UNIX_EPOCH}};
+#[cfg(feature = "sdl")]
+use std::{collections::VecDeque, sync::atomic::AtomicI32, thread::yield_now, time::Instant};
 
+#[cfg(feature = "sdl")]
 use sdl3::{
     audio::{AudioCallback, AudioFormat, AudioSpec, AudioStream},
     event::{DisplayEvent, Event, WindowEvent},
-    hint::names::RENDER_VSYNC, keyboard::Keycode,
-    pixels::{Color, PixelFormat, PixelMasks},
-    render::ScaleMode, sys::{render::SDL_LOGICAL_PRESENTATION_INTEGER_SCALE},
-    video::{Display, FullscreenType, WindowPos}
+    hint::names::{AUDIO_DEVICE_SAMPLE_FRAMES, RENDER_VSYNC}, keyboard::{Keycode, Mod}, messagebox::{show_simple_message_box, MessageBoxFlag},
+    pixels::{Color, PixelFormat, PixelMasks}, rect::Rect,
+    render::{Canvas, ScaleMode, Texture, TextureCreator}, sys::render::{SDL_RendererLogicalPresentation, SDL_LOGICAL_PRESENTATION_INTEGER_SCALE,
+        SDL_LOGICAL_PRESENTATION_LETTERBOX, SDL_LOGICAL_PRESENTATION_STRETCH}, sys::video::SDL_WINDOWPOS_CENTERED_MASK,
+    video::{Display, FullscreenType, Window, WindowContext, WindowPos}
 };
 
-// #![windows_subsystem = "windows"]
-mod chip8;
+mod args;
+mod autofire;
 mod config;
-use crate::{chip8::Chip8, config::Chip8Configuration};
+mod coverage;
+mod crash;
+#[cfg(feature = "sdl")]
+mod debug_window;
+mod debugger;
+mod filter;
+#[cfg(feature = "sdl")]
+mod gamepad;
+mod gdb;
+#[cfg(feature = "sdl")]
+mod geometry;
+mod hooks;
+#[cfg(feature = "sdl")]
+mod icon;
+mod lockstep;
+#[cfg(feature = "sdl")]
+mod menu;
+mod movie;
+mod netplay;
+#[cfg(feature = "sdl")]
+mod osd;
+mod phosphor;
+mod profiler;
+mod png;
+mod random_log;
+mod recent;
+mod recording;
+mod rom;
+mod romdb;
+mod rpl;
+#[cfg(feature = "sdl")]
+mod sdl_frontend;
+#[cfg(feature = "sdl")]
+mod split;
+mod test_runner;
+mod timing;
+mod toml;
+#[cfg(feature = "tui")]
+mod tui;
+mod video;
+mod watch;
+mod wav;
+mod zip;
+use crate::{args::{help, parse_args}, config::{check_memory_layout, Chip8Configuration}, hooks::HookFile, lockstep::Lockstep, movie::Movie, recent::RecentRoms, rom::{is_octo_source, read_program, read_symbols, rom_name}, wav::BeepTrack};
+use chip8_emulator::{assemble, disassemble_rom, AudioEvent, AudioSink, Chip8, Chip8Error, Keys, Symbols};
+#[cfg(feature = "sdl")]
+use crate::{autofire::Autofire, config::flip_quirks, debug_window::{DebugView, DebugWindows}, debugger::Debugger, filter::{create_vignette, draw_scanlines, DisplayFilter}, gamepad::Gamepads, gdb::GdbStub, geometry::WindowGeometry, icon::{program_icon, rom_icon, set_window_icon}, menu::{confirm_quit, PauseMenu}, movie::MOVIE_FRAME_NANOS, netplay::{NetplayClient, NetplayHost}, osd::{draw_text, KeypadOverlay, Osd, SpeedOverlay}, png::encode_png, recording::Recording, rom::{open_rom_dialog, reload_rom, state_path}, rpl::RplFlags, sdl_frontend::SdlFrontend, split::SplitMachine, timing::PreciseSleep, video::VideoRecording, watch::RomWatcher};
+#[cfg(feature = "sdl")]
+use chip8_emulator::{Chip8Options, DisplayMode};
+#[cfg(feature = "sdl")]
 extern crate sdl3;
 
 // Constants
-const SDL3_CHIP8_KEY_MAP: [Keycode; 16] = [
-    Keycode::X, Keycode::_1, Keycode::_2, Keycode::_3, Keycode::Q, Keycode::W, Keycode::E, Keycode::A,
-    Keycode::S, Keycode::D, Keycode::Z, Keycode::C, Keycode::_4, Keycode::R, Keycode::F, Keycode::V,
-];
-    
 const NANOS_IN_SECOND: u64 = 1000000000;
 const CONSOLE_MESSAGES: bool = false;
+const FAST_FORWARD_SPEED: u64 = 8;
+const MAX_FRAME_INSTRUCTIONS: u64 = 10000000; // Bounds the uncapped clock, so one slow frame can't stall the window
+const MAX_VOLUME: i16 = 1024; // Square wave amplitude at a volume of 100
+const VOLUME_STEP: u8 = 10;
+const AUDIO_BUFFER_SAMPLES: &str = "256"; // 5.3 ms at 48000 hz
+const IDLE_WAIT_MILLIS: u32 = 100; // Longest wait for an event while idle, so status messages and the console debugger still update
+const THROTTLE_FRAME_NANOS: u64 = NANOS_IN_SECOND / 10;
+const DISPLAY_QUERY_PERIOD: Duration = Duration::from_secs(1);
+const VSYNC_AHEAD_FRAMES: i64 = 4; // Frames vsync can run ahead of the refresh rate before frames are paced by sleeping
+const VSYNC_RETRY_DELAY: Duration = Duration::from_secs(5); // Doubles each time vsync runs ahead again, up to a minute
+const VSYNC_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const ROM_ICON_FRAMES: u64 = 60; // The rom runs a second before its screen becomes the window icon
 
-// Allows convenient error handling by returning a message
-fn main() {
-    if let Some(message) = app_main() {
-        println!("{message}");
+// Scales the emulated time of each frame, where pausing is handled by the chip8 context
+#[derive(Clone, Copy, PartialEq)]
+enum EmulationSpeed {
+    Normal,
+    FastForward,
+    FrameAdvance // Runs a single 60hz frame before pausing again
+}
+
+// How the chip8 display fills a window that isn't a multiple of its resolution
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScalingMode {
+    Integer, // Largest whole multiple with borders
+    Fit,     // Largest size keeping the aspect ratio with borders
+    Stretch  // Fills the window, distorting the aspect ratio
+}
+
+impl ScalingMode {
+    pub fn parse(name: &str) -> Option<ScalingMode> {
+        match name {
+            "integer" => Some(ScalingMode::Integer),
+            "fit" => Some(ScalingMode::Fit),
+            "stretch" => Some(ScalingMode::Stretch),
+            _ => None
+        }
+    }
+
+    #[cfg(feature = "sdl")]
+    fn presentation(self) -> SDL_RendererLogicalPresentation {
+        match self {
+            ScalingMode::Integer => SDL_LOGICAL_PRESENTATION_INTEGER_SCALE,
+            ScalingMode::Fit => SDL_LOGICAL_PRESENTATION_LETTERBOX,
+            ScalingMode::Stretch => SDL_LOGICAL_PRESENTATION_STRETCH
+        }
+    }
+}
+
+// Where the machine is shown and played
+#[derive(Clone, Copy, PartialEq)]
+pub enum FrontendKind {
+    Sdl, // Window with audio and gamepads
+    Tui  // Terminal with half block characters
+}
+
+impl FrontendKind {
+    pub fn parse(name: &str) -> Option<FrontendKind> {
+        match name {
+            "sdl" => Some(FrontendKind::Sdl),
+            "tui" => Some(FrontendKind::Tui),
+            _ => None
+        }
+    }
+}
+
+// What Escape does in the window
+#[derive(Clone, Copy, PartialEq)]
+pub enum EscapeAction {
+    Menu,      // Pauses and opens the pause menu
+    Quit,
+    Fullscreen // Leaves fullscreen, or opens the pause menu in a window
+}
+
+impl EscapeAction {
+    pub fn parse(name: &str) -> Option<EscapeAction> {
+        match name {
+            "menu" => Some(EscapeAction::Menu),
+            "quit" => Some(EscapeAction::Quit),
+            "fullscreen" => Some(EscapeAction::Fullscreen),
+            _ => None
+        }
+    }
+}
+
+// How the window fills its display
+#[derive(Clone, Copy, PartialEq)]
+pub enum FullscreenMode {
+    Off,
+    Borderless, // Covers the display at the desktop's resolution
+    Exclusive   // Takes over the display with a video mode of its own
+}
+
+impl FullscreenMode {
+    pub fn parse(name: &str) -> Option<FullscreenMode> {
+        match name {
+            "off" => Some(FullscreenMode::Off),
+            "borderless" => Some(FullscreenMode::Borderless),
+            "exclusive" => Some(FullscreenMode::Exclusive),
+            _ => None
+        }
+    }
+}
+
+// What the emulator does while its window isn't focused
+#[derive(Clone, Copy, PartialEq)]
+pub enum FocusPolicy {
+    Run,
+    Throttle, // Draws 10 frames a second while still emulating in real time
+    Pause     // Pauses and silences the buzzer until the window is focused again
+}
+
+impl FocusPolicy {
+    pub fn parse(name: &str) -> Option<FocusPolicy> {
+        match name {
+            "run" => Some(FocusPolicy::Run),
+            "throttle" => Some(FocusPolicy::Throttle),
+            "pause" => Some(FocusPolicy::Pause),
+            _ => None
+        }
+    }
+}
+
+// What happens when the program fails on an unsupported opcode or a memory violation
+#[derive(Clone, Copy, PartialEq)]
+pub enum ErrorPolicy {
+    Terminate, // Writes a crash report and exits
+    Debug,     // Pauses with the console debugger at the failing instruction
+    Skip       // Prints the error and continues after the failing instruction
+}
+
+impl ErrorPolicy {
+    pub fn parse(name: &str) -> Option<ErrorPolicy> {
+        match name {
+            "terminate" => Some(ErrorPolicy::Terminate),
+            "debug" => Some(ErrorPolicy::Debug),
+            "skip" => Some(ErrorPolicy::Skip),
+            _ => None
+        }
+    }
+}
+
+// Shape of the buzzer's tone
+#[derive(Clone, Copy, PartialEq)]
+pub enum Waveform {
+    Square,
+    Triangle,
+    Sine,
+    Noise // Random levels held for each period of the tone
+}
+
+impl Waveform {
+    pub fn parse(name: &str) -> Option<Waveform> {
+        match name {
+            "square" => Some(Waveform::Square),
+            "triangle" => Some(Waveform::Triangle),
+            "sine" => Some(Waveform::Sine),
+            "noise" => Some(Waveform::Noise),
+            _ => None
+        }
+    }
+}
+
+// Exit codes that let scripts and test harnesses tell failures apart, where any other failure exits with 1
+const EXIT_USAGE: u8 = 2;
+const EXIT_ROM: u8 = 3;
+const EXIT_SDL: u8 = 4;
+const EXIT_CRASH: u8 = 5;
+
+// How recently a save state must have been made to quit without confirmation
+const CONFIRM_QUIT_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+// Allows convenient error handling by returning an error
+fn main() -> ExitCode {
+    let is_console = attach_console();
+    let Some(error) = app_main() else { return ExitCode::SUCCESS };
+    present_error(&error, is_console);
+    ExitCode::from(match error {
+        Chip8Error::Usage(_) => EXIT_USAGE,
+        Chip8Error::Rom(_) | Chip8Error::RomTooLarge{..} | Chip8Error::Assembly{..} => EXIT_ROM,
+        Chip8Error::Sdl{..} => EXIT_SDL,
+        _ if error.is_crash() => EXIT_CRASH,
+        _ => 1
+    })
+}
+
+// Prints a fatal error, and also shows it in a message box when there's no console to read it from
+fn present_error(error: &Chip8Error, is_console: bool) {
+    let message = match error {
+        Chip8Error::Usage(_) => format!("{error}\nRun \"chip8-emulator -h\" for more information."),
+        _ => error.to_string()
+    };
+    println!("{message}");
+    #[cfg(feature = "sdl")]
+    if !is_console {
+        let _ = show_simple_message_box(MessageBoxFlag::ERROR, "Chip8 emulator", &message, None);
+    }
+    // Terminal only builds have no message boxes
+    #[cfg(not(feature = "sdl"))]
+    let _ = is_console;
+}
+
+// Attaches to the console of the process that started the emulator, returning false when nothing reads the output
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_console() -> bool {
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    unsafe extern "system" {
+        fn GetStdHandle(std_handle: u32) -> *mut std::ffi::c_void;
+        fn AttachConsole(process_id: u32) -> i32;
     }
+
+    // Output redirected to a file or pipe is already read
+    unsafe { !GetStdHandle(STD_OUTPUT_HANDLE).is_null() || AttachConsole(ATTACH_PARENT_PROCESS) != 0 }
+}
+
+// Other builds always have a console or a launcher that logs the output
+#[cfg(not(all(windows, not(debug_assertions))))]
+fn attach_console() -> bool {
+    true
 }
 
-fn app_main() -> Option<&'static str> {
+fn app_main() -> Option<Chip8Error> {
+    // Parses the command line before SDL3, so help and the test runner don't open a window
+    let arguments = match parse_args(std::env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(msg) => return Some(Chip8Error::Usage(msg))
+    };
+
+    if arguments.has("help") {
+        print!("{}", help());
+        return None
+    }
+
+    // Runs a test manifest headlessly
+    if let Some(manifest_path) = arguments.value("test") {
+        return test_runner::run_manifest(manifest_path).map(Chip8Error::from)
+    }
+
+    // Gets configuration for this emulator
+    let mut chip8_configuration = match Chip8Configuration::parse(&arguments) {
+        Ok(config) => config,
+        Err(error) => return Some(error)
+    };
+
+    // Runs the community test suite headlessly with the configured profile and quirks
+    if let Some(directory) = arguments.value("selftest") {
+        return test_runner::run_selftest(directory, &chip8_configuration.options).map(Chip8Error::from)
+    }
+
+    // Reads the rom from a file, standard input, or a url, where streaming netplay clients show the host's machine instead
+    // Octo sources are assembled as they're read, and their labels or the rom's symbol file name addresses for debugging
+    let (profile, start_address) = (chip8_configuration.options.profile, chip8_configuration.options.start_address);
+    let (rom, mut symbols) = match &chip8_configuration.join_address {
+        Some(address) if !chip8_configuration.is_lockstep => {
+            chip8_configuration.rom_path = address.clone();
+            (Vec::new(), Symbols::default())
+        }
+        _ => match read_program(&chip8_configuration.rom_path, profile, start_address) {
+            Ok(program) => program,
+            Err(error) => return Some(error)
+        }
+    };
+    if let Some(path) = &chip8_configuration.symbols_path {
+        match read_symbols(path) {
+            Ok(file_symbols) => symbols = file_symbols,
+            Err(error) => return Some(error)
+        }
+    }
+
+    // Assembles the source at the rom path into a rom instead of emulating it, writing its labels to a symbol file beside it
+    if let Some(path) = &chip8_configuration.assemble_path {
+        // Octo sources were assembled as they were read
+        let assembled = match is_octo_source(&chip8_configuration.rom_path) {
+            true => Ok((rom, symbols)),
+            false => match String::from_utf8(rom) {
+                Ok(source) => assemble(&source, profile, start_address),
+                Err(_) => Err(Chip8Error::Rom("The source isn't UTF-8 text!"))
+            }
+        };
+        let (assembled, symbols) = match assembled {
+            Ok(program) => program,
+            Err(error) => return Some(error)
+        };
+        if std::fs::write(path, &assembled).is_err() { return Some("Failed to write the assembled rom!".into()) }
+        println!("Assembled {} bytes into {path}", assembled.len());
+
+        if symbols.is_empty() { return None }
+        let symbols_path = Path::new(path).with_extension("sym");
+        return match std::fs::write(&symbols_path, symbols.to_text()) {
+            Ok(_) => {
+                println!("Wrote the labels to {}", symbols_path.display());
+                None
+            }
+            Err(_) => Some("Failed to write the symbol file!".into())
+        }
+    }
+
+    // Applies the settings recommended by the rom database, unless others were chosen
+    if let Some(info) = romdb::lookup(&rom) {
+        println!("{} by {}", info.title, info.author);
+        if !chip8_configuration.is_platform_set {
+            let options = &mut chip8_configuration.options;
+            options.profile = info.profile;
+            options.quirks = info.profile.quirks();
+            options.clock_hz = info.clock_hz.unwrap_or(info.profile.clock_hz());
+            if !chip8_configuration.is_reserved_set { options.reserved_size = info.profile.reserved_size(); }
+            if let Err(error) = check_memory_layout(options) { return Some(error) }
+        }
+    }
+
+    // Prints the rom's instructions instead of emulating it
+    if chip8_configuration.is_disassemble {
+        print!("{}", disassemble_rom(&rom, chip8_configuration.options.profile, chip8_configuration.options.start_address, &symbols));
+        return None
+    }
+
+    // Writes a number of frames as pngs headlessly instead of opening a window
+    if let Some(directory) = arguments.value("dump-frames") {
+        let Some(Ok(frames)) = arguments.value("frames").map(str::parse::<u32>) else {
+            return Some(Chip8Error::Usage("Dumping frames needs a frame count from -frames!"))
+        };
+        return test_runner::dump_frames(&rom, &chip8_configuration.options, &chip8_configuration.palette, directory, frames)
+    }
+
+    // Records or plays back inputs, where the movie seeds the random number generator
+    let mut movie = None;
+    let movie_result = match (&chip8_configuration.movie_path, &chip8_configuration.playback_path) {
+        (_, Some(path)) => Some(Movie::play(path)),
+        (Some(path), None) => Some(Movie::record(path)),
+        (None, None) => None
+    };
+    if let Some(result) = movie_result {
+        match result {
+            Ok((new_movie, seed)) => {
+                movie = Some(new_movie);
+                chip8_configuration.options.seed = Some(seed);
+            }
+            Err(msg) => return Some(msg.into())
+        }
+    }
+
+    // Lockstep netplay shares the host's seed, so both machines make the same random numbers
+    let mut lockstep = None;
+    if chip8_configuration.is_lockstep {
+        if movie.is_some() { return Some(Chip8Error::Usage("Movies can't be used with lockstep netplay!")) }
+        let options = &chip8_configuration.options;
+        let lockstep_result = match (chip8_configuration.host_port, &chip8_configuration.join_address) {
+            (Some(port), _) => Lockstep::host(port, chip8_configuration.input_delay, &rom, options),
+            (None, Some(address)) => Lockstep::join(address, &rom, options),
+            (None, None) => return Some(Chip8Error::Usage("Lockstep needs -host or -join!"))
+        };
+        match lockstep_result {
+            Ok((new_lockstep, seed)) => {
+                lockstep = Some(new_lockstep);
+                chip8_configuration.options.seed = Some(seed);
+            }
+            Err(msg) => return Some(msg.into())
+        }
+    }
+    let is_streaming_client = chip8_configuration.join_address.is_some() && lockstep.is_none();
+
+    // Quirk comparisons share a seed, so random numbers don't make the displays differ
+    if chip8_configuration.compare_names.is_some() && chip8_configuration.options.seed.is_none() {
+        chip8_configuration.options.seed = Some(rand::random());
+    }
+
+    // Initializes the chip8 emulation context
+    let mut chip8_context =  match Chip8::init(&rom, &chip8_configuration.options) {
+        Ok(context) => context,
+        Err(msg) => return Some(msg)
+    };
+
+    chip8_context.set_backend(chip8_configuration.backend);
+
+    // Hooks cheats and traces from a hook file into the machine
+    if let Some(path) = &chip8_configuration.hooks_path {
+        match HookFile::load(path, &symbols) {
+            Ok(hook_file) => chip8_context.set_observer(Box::new(hook_file)),
+            Err(msg) => return Some(msg.into())
+        }
+    }
+
+    // Counts executed instructions for the profile written on exit, and records ram use for the coverage map
+    if chip8_configuration.profile_path.is_some() { chip8_context.set_profiling(true); }
+    if chip8_configuration.coverage_path.is_some() { chip8_context.set_coverage(true); }
+
+    // Logs random rolls for the log written on exit, and replays a previous session's rolls
+    if chip8_configuration.random_log_path.is_some() { chip8_context.set_random_logging(true); }
+    if let Some(path) = &chip8_configuration.random_replay_path {
+        match random_log::read_log(path) {
+            Ok(rolls) => chip8_context.replay_random(rolls),
+            Err(msg) => return Some(msg.into())
+        }
+    }
+
+    // Remembers the rom for the recent roms menu
+    let mut recent_roms = RecentRoms::load();
+    if !is_streaming_client { recent_roms.add(&chip8_configuration.rom_path); }
+
+    // Runs in the terminal instead of a window, before SDL3 so it works without a display
+    if chip8_configuration.frontend == FrontendKind::Tui {
+        #[cfg(feature = "tui")]
+        {
+            let result = tui::run(&mut chip8_context, &chip8_configuration);
+            save_reports(&chip8_context, &chip8_configuration);
+            return result
+        }
+        #[cfg(not(feature = "tui"))]
+        return Some(Chip8Error::Usage("The tui frontend requires building with \"cargo build --features tui\"!"));
+    }
+
+    #[cfg(feature = "sdl")]
+    return run_window(chip8_configuration, rom, symbols, movie, lockstep, chip8_context, recent_roms);
+    #[cfg(not(feature = "sdl"))]
+    Some(Chip8Error::Usage("The window requires building with the sdl feature! Use \"-frontend tui\" instead."))
+}
+
+// Opens the window and runs the machine in it until it's closed
+#[cfg(feature = "sdl")]
+fn run_window(mut chip8_configuration: Chip8Configuration, rom: Vec<u8>, mut symbols: Symbols, mut movie: Option<Movie>,
+    mut lockstep: Option<Lockstep>, mut chip8_context: Chip8, mut recent_roms: RecentRoms) -> Option<Chip8Error> {
+    let is_streaming_client = chip8_configuration.join_address.is_some() && lockstep.is_none();
+    let mut pause_menu = PauseMenu::new();
+    let mut last_save_time: Option<Instant> = None;
+
     // Batches sdl3 objects out of a struct
     let sdl_context = match sdl3::init() {
         Ok(context) => context,
-        Err(_) => return Some("Failed to initialize SDL3!")
+        Err(error) => return Some(Chip8Error::sdl("Failed to initialize SDL3!", error))
     };
 
     // Initializes SDL3 subsystems
     let sdl_audio_subsystem = match sdl_context.audio() {
         Ok(audio) => audio,
-        Err(_) => return Some("Failed to initialize audio subsystem!")
+        Err(error) => return Some(Chip8Error::sdl("Failed to initialize audio subsystem!", error))
     };
     
+    let sdl_gamepad_subsystem = match sdl_context.gamepad() {
+        Ok(gamepad) => gamepad,
+        Err(error) => return Some(Chip8Error::sdl("Failed to initialize gamepad subsystem!", error))
+    };
+
     let mut sdl_event_pump = match sdl_context.event_pump() {
         Ok(pump) => pump,
-        Err(_) => return Some("Failed to initialize event pump!")
+        Err(error) => return Some(Chip8Error::sdl("Failed to initialize event pump!", error))
     };
 
     let sdl_video_subsystem = match sdl_context.video() {
         Ok(video) => video,
-        Err(_) => return Some("Failed to initialize video subsystem!")
+        Err(error) => return Some(Chip8Error::sdl("Failed to initialize video subsystem!", error))
     };
 
     // Initializes window
     let mut sdl_window = match sdl_video_subsystem.window("chip8-emulator", 0, 0)
     .hidden().resizable().build() {
         Ok(window) => window,
-        Err(_) => return Some("Failed to initialize window!")
+        Err(error) => return Some(Chip8Error::sdl("Failed to initialize window!", error))
     };
 
-    // Initializes the primary display to get its resolution and refresh rate
-    let mut sdl_display = match sdl_video_subsystem.get_primary_display() {
-        Ok(display) => display,
-        Err(_) => return Some("Failed to get primary display!")
-    };
-
-    // Gets configuration for this emulator
-    let chip8_configuration = match Chip8Configuration::parse(&sdl_window, &mut sdl_event_pump) {
-        Ok(config) => config,
-        Err(msg) => match msg {
-            "" => return Some(msg),
-            _ => {
-                println!("{msg}");
-                return Some("Run \"chip8-emulator -h\" for more information.")
+    // Initializes the configured or primary display to get its resolution and refresh rate
+    let mut sdl_display = match chip8_configuration.display_number {
+        Some(number) => match sdl_video_subsystem.displays() {
+            Ok(displays) => match displays.get(number - 1) {
+                Some(&display) => display,
+                None => return Some(Chip8Error::Usage("There's no display with that number!"))
             }
+            Err(error) => return Some(Chip8Error::sdl("Failed to get displays!", error))
+        }
+        None => match sdl_video_subsystem.get_primary_display() {
+            Ok(display) => display,
+            Err(error) => return Some(Chip8Error::sdl("Failed to get primary display!", error))
         }
     };
 
-    // Initializes the chip8 emulation context
-    let mut chip8_context =  match Chip8::init(&chip8_configuration) {
-        Ok(context) => context,
-        Err(msg) => return Some(msg)
-    };
-
-    // Sets fullscreen mode from configuration
-    if sdl_window.set_fullscreen(chip8_configuration.is_fullscreen).is_err() {
-        return Some("Failed to set fullscreen mode!");
-    }
-
     // Enables vsync based on configuration
     if chip8_configuration.is_vsync { sdl3::hint::set(RENDER_VSYNC, "1"); }
 
     // Calculates window size based on scale factor, pixel dimensions, or half the monitor resolution
+    // A second machine doubles the width, with the displays side by side
+    let display_columns = match chip8_configuration.rom2_path.is_some() || chip8_configuration.compare_names.is_some() {
+        true => 2,
+        false => 1
+    };
+    let (display_width, display_height) = chip8_configuration.options.profile.display_size();
+    let display_width = display_width * display_columns;
+
+    // Reopens where the last run's window was, unless a size or display was chosen or a second machine changes the shape
+    let is_geometry_remembered = chip8_configuration.window_size.is_none() && chip8_configuration.display_number.is_none()
+        && display_columns == 1;
+    let window_geometry = match is_geometry_remembered {
+        true => WindowGeometry::load(&sdl_video_subsystem),
+        false => None
+    };
+
     let (window_width, window_height) = match chip8_configuration.window_size {
+        None if let Some(geometry) = &window_geometry => (geometry.width, geometry.height),
         None => match sdl_display.get_mode() {
             // Sets the window size to half the highest integer scale
-            Ok(mode) => (mode.w as u32 / 64 * 32, mode.h as u32 / 32 * 16),
-            Err(_) => return Some("Failed to get display mode!")
+            Ok(mode) => (mode.w as u32 / display_width * display_width / 2, mode.h as u32 / display_height * display_height / 2),
+            Err(error) => return Some(Chip8Error::sdl("Failed to get display mode!", error))
         }
         Some(size) => match size {
-            // Calculates window size from an integer scale chip8 (64x32) resolution
-            Err(scale) => (64 * scale, 32 * scale),
+            // Calculates window size from an integer scale of the profile's resolution
+            Err(scale) => (display_width * scale, display_height * scale),
             Ok(size) => size,
         }
     };
 
-    // Sets window size, centers it, and shows it
-    if sdl_window.set_size(window_width, window_height).is_err() {
-        return Some("Failed to set window size!")
+    // Sets window size, places it where it was or centers it on the display, sets the configured fullscreen mode, and shows it
+    if let Err(error) = sdl_window.set_size(window_width, window_height) {
+        return Some(Chip8Error::sdl("Failed to set window size!", error))
+    }
+    let centered = WindowPos::Positioned((SDL_WINDOWPOS_CENTERED_MASK | sdl_display.to_ll()) as i32);
+    let (x, y) = match &window_geometry {
+        Some(geometry) => (WindowPos::Positioned(geometry.x), WindowPos::Positioned(geometry.y)),
+        None => (centered, centered)
+    };
+    sdl_window.set_position(x, y);
+    if let Err(error) = set_fullscreen(&mut sdl_window, chip8_configuration.fullscreen_mode, sdl_display) {
+        return Some(error)
     }
-    sdl_window.set_position(WindowPos::Centered, WindowPos::Centered);
     sdl_window.show();
 
-    // Sets rendering size to 64x32
+    // F11 switches to the configured fullscreen mode, or borderless when starting in a window
+    let fullscreen_mode = match chip8_configuration.fullscreen_mode {
+        FullscreenMode::Off => FullscreenMode::Borderless,
+        mode => mode
+    };
+
     let mut sdl_canvas = sdl_window.into_canvas();
-    if sdl_canvas.set_logical_size(64, 32, SDL_LOGICAL_PRESENTATION_INTEGER_SCALE).is_err() {
-        return Some("Failed to set logical size!")
-    }
 
     // Sets the rendering background color
     let agrb8888 = PixelMasks{bpp: 32, rmask: 0x00FF0000, gmask: 0x0000FF00, bmask: 0x000000FF, amask: 0xFF000000};
     let pixel_format = PixelFormat::from_masks(agrb8888);
-    sdl_canvas.set_draw_color(Color::from_u32(&pixel_format, chip8_configuration.background_color));
+    sdl_canvas.set_draw_color(Color::from_u32(&pixel_format, chip8_configuration.palette[0]));
+
+    // Shows the program's icon in the palette's colors, which the rom's screen replaces when requested
+    if let Err(message) = set_window_icon(sdl_canvas.window_mut(), &program_icon(&chip8_configuration.palette), pixel_format) {
+        println!("{message}");
+    }
+    let mut rom_icon_frame = chip8_configuration.is_rom_icon.then(|| chip8_context.frame_count() + ROM_ICON_FRAMES);
 
-    // Initializes audio stream with callback
+    // Initializes audio stream with callback, which shares the beep's samples with the chip8 context
+    let beep_samples = Arc::new(AtomicU64::new(0));
+    let volume = Arc::new(AtomicI32::new(chip8_configuration.volume as i32));
+    let mut is_muted = false;
+    let is_beep_tracked = chip8_configuration.audio_dump_path.is_some() || chip8_configuration.video_path.is_some();
+    let beep_track = is_beep_tracked.then(|| Rc::new(RefCell::new(BeepTrack::default())));
+    let frame_offset = Rc::new(Cell::new(0.0));
+    chip8_context.set_audio_sink(Box::new(SampleCounter{beep_samples: beep_samples.clone(), beep_track: beep_track.clone(),
+        frame_offset: frame_offset.clone()}));
+    let netplay_beep_samples = beep_samples.clone();
+
+    // Asks for small device buffers, so a beep starts within a few milliseconds of the sound timer being set
+    sdl3::hint::set(AUDIO_DEVICE_SAMPLE_FRAMES, AUDIO_BUFFER_SAMPLES);
     let audio_spec = AudioSpec{freq: Some(48000), channels: Some(1), format: Some(AudioFormat::s16_sys())};
     let sdl_audio_stream = match sdl_audio_subsystem.default_playback_device()
-    .open_playback_stream_with_callback(&audio_spec, AudioState{buffer: Vec::new(), phase: 0, previous: 0,
-        remaining_samples: chip8_context.remaining_samples.clone()}) {
+    .open_playback_stream_with_callback(&audio_spec, AudioState{buffer: Vec::new(),
+        oscillator: Oscillator::new(chip8_configuration.tone_hz, chip8_configuration.waveform), beep_samples, volume: volume.clone()}) {
         Ok(stream) => stream,
-        Err(_) => return Some("Failed to initialize audio stream!")
+        Err(error) => return Some(Chip8Error::sdl("Failed to initialize audio stream!", error))
     };
 
     // Starts audio steam
-    if sdl_audio_stream.resume().is_err() {
-        return Some("Failed to resume audio stream!")
+    if let Err(error) = sdl_audio_stream.resume() {
+        return Some(Chip8Error::sdl("Failed to resume audio stream!", error))
     }
 
-    // Initializes texture on the gpu to blit to
+    // Initializes texture on the gpu to blit to, at the resolution of the chip8 display
     let texture_creator = sdl_canvas.texture_creator();
-    let mut sdl_texture = match texture_creator.create_texture_streaming(pixel_format,
-         chip8::FRAME_BUFFER_WIDTH as u32, chip8::FRAME_BUFFER_HEIGHT as u32) {
+    let mut display_size = chip8_context.display_mode().size();
+    let sdl_texture = match create_display_texture(&mut sdl_canvas, &texture_creator, pixel_format, display_size,
+        display_columns, &chip8_configuration) {
         Ok(texture) => texture,
-        Err(_) => return Some("Failed to initialize texture!")
+        Err(msg) => return Some(msg)
     };
-    sdl_texture.set_scale_mode(ScaleMode::Nearest);
+    let mut sdl_frontend = SdlFrontend::new(sdl_texture, chip8_configuration.palette, chip8_configuration.is_phosphor);
+
+    // Runs the second rom beside the first with the same options, or the same rom with the compared quirks flipped
+    let split_rom = match (&chip8_configuration.rom2_path, &chip8_configuration.compare_names) {
+        (Some(path), _) => match read_program(path, chip8_configuration.options.profile, chip8_configuration.options.start_address) {
+            Ok((rom2, _)) => Some((rom2, chip8_configuration.options)),
+            Err(error) => return Some(error)
+        }
+        (None, Some(names)) => match flip_quirks(chip8_configuration.options.quirks, names) {
+            Ok(quirks) => Some((rom.clone(), Chip8Options{quirks, ..chip8_configuration.options})),
+            Err(error) => return Some(error)
+        }
+        (None, None) => None
+    };
+    let mut split_machine = None;
+    if let Some((split_rom, options)) = split_rom {
+        let is_compare = chip8_configuration.compare_names.is_some();
+        split_machine = match SplitMachine::new(&split_rom, &options, is_compare, &texture_creator, pixel_format, &chip8_configuration) {
+            Ok(machine) => Some(machine),
+            Err(error) => return Some(error)
+        };
+    }
+
+    // Initializes the darkened edges of the crt filter
+    let mut vignette = None;
+    if chip8_configuration.display_filter == DisplayFilter::Crt {
+        vignette = match create_vignette(&texture_creator, pixel_format) {
+            Some(texture) => Some(texture),
+            None => return Some("Failed to initialize vignette texture!".into())
+        };
+    }
 
     // Gets refresh rate from primary display
     let mut refresh_time_nanos = match sdl3_get_refresh_time(sdl_display) {
@@ -155,64 +692,432 @@ fn app_main() -> Option<&'static str> {
         None => return None
     };
 
+    // Gamepads are opened as they connect
+    let mut gamepads = Gamepads::new(sdl_gamepad_subsystem, chip8_configuration.gamepad_map.clone());
+    let mut autofire = Autofire::new(chip8_configuration.autofire_keys, chip8_configuration.autofire_hz);
+
+    // Attaches the console debugger and pauses before the first instruction
+    let mut debugger = None;
+    if chip8_configuration.is_debug {
+        chip8_context.pause();
+        debugger = Some(Debugger::new());
+    }
+
+    // Starts the gdb stub, which pauses the machine when gdb connects
+    let mut gdb_stub = None;
+    if let Some(port) = chip8_configuration.gdb_port {
+        gdb_stub = match GdbStub::listen(port) {
+            Some(stub) => Some(stub),
+            None => return Some("Failed to listen for gdb connections!".into())
+        };
+    }
+
+    // Hosts or joins netplay, where a client shows the host's display and sends it the local keys
+    let mut netplay_host = None;
+    if let Some(port) = chip8_configuration.host_port && lockstep.is_none() {
+        netplay_host = match NetplayHost::listen(port) {
+            Some(host) => Some(host),
+            None => return Some("Failed to listen for netplay connections!".into())
+        };
+    }
+    let mut netplay_client = None;
+    if let Some(address) = &chip8_configuration.join_address && is_streaming_client {
+        netplay_client = match NetplayClient::join(address) {
+            Ok(client) => Some(client),
+            Err(msg) => return Some(msg.into())
+        };
+    }
+
+    // Chip8 keys held by the netplay client, which merge with the local keys
+    let mut remote_keys = [false; 16];
+
+    // Watches the rom for new builds when requested
+    let mut rom_watcher = match chip8_configuration.is_watch {
+        true => Some(RomWatcher::new(&chip8_configuration.rom_path)),
+        false => None
+    };
+
+    // Records from the first frame when requested
+    let mut recording = None;
+    if chip8_configuration.is_record {
+        recording = Some(Recording::new(display_size.0, display_size.1));
+    }
+    let mut video_recording = None;
+    if let Some(path) = &chip8_configuration.video_path {
+        video_recording = match VideoRecording::start(path, (window_width, window_height), chip8_context.frame_count()) {
+            Ok(video_recording) => Some(video_recording),
+            Err(msg) => return Some(msg.into())
+        };
+    }
+
+    // Save states are stored next to the rom with a .state extension
+    let mut state_path = state_path(&chip8_configuration.rom_path);
+
+    // SCHIP's RPL user flags are stored next to the rom with a .flags extension
+    let mut rpl_flags = RplFlags::load(&mut chip8_context, state_path.with_extension("flags"));
+
     // Frame timing variables
+    let mut emulation_speed = EmulationSpeed::Normal;
     let mut is_vsync = chip8_configuration.is_vsync;
     let mut start_time = Instant::now();
     let mut frame_delta = 0;
     let mut frame_delta_buffer = 0;
+    let mut vsync_retry_delay = VSYNC_RETRY_DELAY;
+    let mut vsync_retry_time = None; // When sleeping tries vsync again
+    let mut vsync_on_time = Instant::now();
+    let mut display_query_time = Instant::now();
+    let mut is_display_query_failing = false;
+    let precise_sleep = PreciseSleep::new();
+    let mut movie_buffer = 0;
+    let mut lockstep_buffer = 0;
+    let mut is_divergence_reported = false;
+
+    // The uncapped clock runs with -clock max or while the turbo key is held, with a budget fitted to the frame time
+    let mut is_turbo = false;
+    let mut frame_instructions = chip8_configuration.options.clock_hz as u64 / 60;
 
     let mut average_total = 0;
     let mut average_count = 0;
 
-    loop {
-        // Event loop
-        for event in sdl_event_pump.poll_iter() {
+    // Shows status messages over the display, starting with the clock speed
+    let mut osd = Osd::new();
+    osd.show(&clock_name(chip8_configuration.is_clock_max, chip8_context.is_cosmac_timing(), chip8_context.clock_hz()));
+    let mut speed_overlay = SpeedOverlay::new();
+    let mut keypad_overlay = KeypadOverlay::new();
+    let mut debug_windows = DebugWindows::new(sdl_video_subsystem.clone());
+
+    // Roms chosen while running, and the file dialog opened from the splash rom
+    let mut next_rom_path = None;
+    let mut rom_dialog = None;
+
+    let mut deferred_events: VecDeque<(Event, bool)> = VecDeque::new();
+    let mut is_unfocused = false;
+    let mut is_focus_paused = false;
+
+    let result = 'frame: loop {
+        // Waits for an event instead of drawing frames while nothing can change without one, like on FX0A or while paused
+        // Netplay, debugging over gdb, movies, recordings, autofire, and a second machine keep running every frame
+        let is_idle = (chip8_context.is_paused() || chip8_context.is_exited()
+            || chip8_context.is_waiting_for_key() && chip8_context.delay_timer() == 0 && chip8_context.sound_timer() == 0)
+            && emulation_speed == EmulationSpeed::Normal && !chip8_configuration.is_clock_max && !is_turbo && movie.is_none()
+            && recording.is_none() && split_machine.is_none() && gdb_stub.is_none() && netplay_host.is_none()
+            && netplay_client.is_none() && lockstep.is_none() && rom_dialog.is_none() && !autofire.is_pulsing();
+        if is_idle {
+            if let Some(event) = sdl_event_pump.wait_event_timeout(IDLE_WAIT_MILLIS) { deferred_events.push_back((event, false)); }
+            start_time = Instant::now();
+        }
+
+        // Event loop, where pause menu choices are queued as the hotkey events they stand for and skip the open menu
+        // Events read during the last frame's emulation come first
+        let mut events = std::mem::take(&mut deferred_events);
+        events.extend(sdl_event_pump.poll_iter().map(|event| (event, false)));
+        while let Some((event, is_menu_choice)) = events.pop_front() {
             match event {
                 // Quits application and reads keyboard
-                Event::Quit {..} => return None,
+                Event::Quit {..} => {
+                    // Asks first when the state wasn't saved in the last few minutes
+                    let is_saved = last_save_time.is_some_and(|time| time.elapsed() < CONFIRM_QUIT_WINDOW);
+                    if chip8_configuration.is_confirm_quit && !is_saved && !confirm_quit(sdl_canvas.window()) { continue }
+                    if let Some(recording) = recording.take() { recording.save(&timestamped_path("recording", "apng")); }
+                    break 'frame None
+                }
+
+                // Scrolls the debug window the key was pressed in, or edits the palette with immediate effect
+                Event::KeyDown{window_id, keycode: Some(sdl_key), ..} if debug_windows.handles_key(window_id, sdl_key) => {
+                    let is_palette_changed = debug_windows.press(window_id, sdl_key, &chip8_context, &mut chip8_configuration.palette);
+                    if is_palette_changed {
+                        let palette = chip8_configuration.palette;
+                        sdl_frontend.set_palette(palette);
+                        if let Some(split_machine) = &mut split_machine { split_machine.set_palette(palette); }
+                        sdl_canvas.set_draw_color(Color::from_u32(&pixel_format, palette[0]));
+                    }
+                }
+                Event::MouseWheel{window_id, y, ..} => debug_windows.scroll(window_id, y, &chip8_context),
+
+                // Moves through the pause menu while it's open
+                Event::KeyDown{keycode: Some(sdl_key), ..} if pause_menu.is_open && !is_menu_choice => {
+                    if let Some(choice) = pause_menu.press(&mut chip8_context, sdl_key) { events.push_back((choice, true)); }
+                }
+
+                // Moves through the recent roms menu while it's open, and switches to the chosen rom
+                Event::KeyDown{keycode: Some(sdl_key), ..} if recent_roms.is_open => match sdl_key {
+                    Keycode::Up => recent_roms.move_selection(false),
+                    Keycode::Down => recent_roms.move_selection(true),
+                    Keycode::Escape | Keycode::F1 => recent_roms.is_open = false,
+                    Keycode::Return => next_rom_path = recent_roms.choose(),
+                    _ => ()
+                },
+
+                // Reloads the rom from disk and restarts, instead of pressing chip8 key D
+                Event::KeyDown{keycode: Some(Keycode::R), keymod, ..} if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    match reload_rom(&mut chip8_context, &chip8_configuration.rom_path, &rom, &symbols) {
+                        // A symbol file chosen on the command line stays in use
+                        Ok(new_symbols) => {
+                            if chip8_configuration.symbols_path.is_none() { symbols = new_symbols; }
+                            osd.show("Reloaded");
+                        }
+                        Err(error) => osd.show(&error.to_string())
+                    }
+                }
+
+                // Opens a rom from the file dialog
+                Event::KeyDown{keycode: Some(Keycode::O), keymod, ..}
+                if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) && rom_dialog.is_none() => {
+                    rom_dialog = match open_rom_dialog(sdl_canvas.window()) {
+                        Ok(receiver) => Some(receiver),
+                        Err(msg) => break 'frame Some(msg.into())
+                    };
+                }
+
+                // Opens or closes the registers, memory, sprite, and palette windows
+                Event::KeyDown{keycode: Some(sdl_key @ (Keycode::_1 | Keycode::_2 | Keycode::_3 | Keycode::_4)), keymod, ..}
+                if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    let view = match sdl_key {
+                        Keycode::_1 => DebugView::Registers,
+                        Keycode::_2 => DebugView::Memory,
+                        Keycode::_3 => DebugView::Sprites,
+                        _ => DebugView::Palette
+                    };
+                    if let Err(error) = debug_windows.toggle(view, &chip8_context) {
+                        break 'frame Some(error)
+                    }
+                }
 
                 Event::KeyDown{keycode: Some(sdl_key), ..} => match sdl_key {
-                    // Terminates emulator
-                    Keycode::Escape => return None,
+                    // Opens the pause menu, quits, or leaves fullscreen, as configured
+                    Keycode::Escape => match chip8_configuration.escape_action {
+                        EscapeAction::Menu => pause_menu.open(&mut chip8_context),
+                        EscapeAction::Quit => events.push_back((Event::Quit{timestamp: 0}, true)),
+                        EscapeAction::Fullscreen => match sdl_canvas.window().fullscreen_state() {
+                            FullscreenType::Off => pause_menu.open(&mut chip8_context),
+                            _ => if let Err(error) = set_fullscreen(sdl_canvas.window_mut(), FullscreenMode::Off, sdl_display) {
+                                break 'frame Some(error);
+                            }
+                        }
+                    }
+
+                    // Pauses or resumes emulation
+                    Keycode::Space => match chip8_context.is_paused() {
+                        true => {
+                            chip8_context.resume();
+                            osd.show("Resumed");
+                        }
+                        false => {
+                            chip8_context.pause();
+                            osd.show("Paused");
+                        }
+                    }
+
+                    // Advances exactly one frame while paused
+                    Keycode::Period => if chip8_context.is_paused() {
+                        emulation_speed = EmulationSpeed::FrameAdvance;
+                    }
 
-                    // Reverses the full screen state
+                    // Toggles fast forward
+                    Keycode::Tab => emulation_speed = match emulation_speed {
+                        EmulationSpeed::FastForward => {
+                            osd.show("Normal speed");
+                            EmulationSpeed::Normal
+                        }
+                        _ => {
+                            osd.show(&format!("Fast forward {FAST_FORWARD_SPEED}x"));
+                            EmulationSpeed::FastForward
+                        }
+                    },
+
+                    // Runs uncapped while held, except where frames must match a movie, netplay, or the second machine
+                    Keycode::Grave if !is_turbo && movie.is_none() && netplay_client.is_none() && lockstep.is_none() && split_machine.is_none() => {
+                        is_turbo = true;
+                        osd.show("Turbo");
+                    }
+
+                    // Opens the recent roms menu
+                    Keycode::F1 => recent_roms.toggle(),
+
+                    // Mutes or unmutes the buzzer
+                    Keycode::M => {
+                        is_muted = !is_muted;
+                        match is_muted {
+                            true => {
+                                volume.store(0, Ordering::Release);
+                                osd.show("Muted");
+                            }
+                            false => {
+                                volume.store(chip8_configuration.volume as i32, Ordering::Release);
+                                osd.show("Unmuted");
+                            }
+                        }
+                    }
+
+                    // Lowers or raises the buzzer volume, which unmutes it
+                    Keycode::Minus | Keycode::Equals => {
+                        chip8_configuration.volume = match sdl_key {
+                            Keycode::Minus => chip8_configuration.volume.saturating_sub(VOLUME_STEP),
+                            _ => (chip8_configuration.volume + VOLUME_STEP).min(100)
+                        };
+                        is_muted = false;
+                        volume.store(chip8_configuration.volume as i32, Ordering::Release);
+                        osd.show(&format!("Volume {}%", chip8_configuration.volume));
+                    }
+
+                    // Shows or hides the speed overlay
+                    Keycode::F3 => speed_overlay.is_visible = !speed_overlay.is_visible,
+                    Keycode::F4 => keypad_overlay.is_visible = !keypad_overlay.is_visible,
+
+                    // Restarts without reloading the rom
+                    Keycode::F2 => {
+                        chip8_context.reset();
+                        if let Some(split_machine) = &mut split_machine { split_machine.reset(); }
+                        osd.show("Reset");
+                    }
+
+                    // Saves the machine state next to the rom
+                    Keycode::F5 => match std::fs::write(&state_path, chip8_context.save_state()) {
+                        Ok(_) => {
+                            last_save_time = Some(Instant::now());
+                            osd.show("State saved");
+                        }
+                        Err(_) => osd.show("Failed to write save state!")
+                    }
+
+                    // Loads the machine state next to the rom
+                    Keycode::F7 => match std::fs::read(&state_path) {
+                        Ok(state) => match chip8_context.load_state(&state) {
+                            Ok(_) => osd.show("State loaded"),
+                            Err(error) => osd.show(&error.to_string())
+                        }
+                        Err(_) => osd.show("Failed to read save state!")
+                    }
+
+                    // Pauses or resumes with the debugger, attaching it if needed
+                    Keycode::F8 => debugger.get_or_insert_with(Debugger::new).toggle_pause(&mut chip8_context),
+
+                    // Starts recording, or stops and saves the animation in the working directory
+                    Keycode::F9 => match recording.take() {
+                        Some(recording) => {
+                            recording.save(&timestamped_path("recording", "apng"));
+                            osd.show("Recording saved");
+                        }
+                        None => {
+                            recording = Some(Recording::new(display_size.0, display_size.1));
+                            osd.show("Recording");
+                        }
+                    }
+
+                    // Executes one instruction while paused
+                    Keycode::F10 => if let Some(message) = debugger.as_mut().and_then(|debugger| debugger.step(&mut chip8_context, 1, &symbols))
+                    && let Some(message) = handle_error(&mut chip8_context, message, chip8_configuration.error_policy, &mut debugger, &mut osd) {
+                        break 'frame Some(message)
+                    }
+
+                    // Reverses the full screen state, filling the display the window is on
                     Keycode::F11 => {
-                        let old_state = sdl_canvas.window().fullscreen_state();
-                        if sdl_canvas.window_mut().set_fullscreen(old_state == FullscreenType::Off).is_err() {
-                            return Some("Failed to set fullscreen mode!");
+                        let new_mode = match sdl_canvas.window().fullscreen_state() {
+                            FullscreenType::Off => fullscreen_mode,
+                            _ => FullscreenMode::Off
+                        };
+                        if let Err(error) = set_fullscreen(sdl_canvas.window_mut(), new_mode, sdl_display) {
+                            break 'frame Some(error);
                         }
                     }
 
-                    // Handles chip8 key press
-                    _ => for chip8_key in 0..SDL3_CHIP8_KEY_MAP.len() {
-                        if sdl_key == SDL3_CHIP8_KEY_MAP[chip8_key] {
-                            chip8_context.keyboard[chip8_key] = true;
+                    // Saves a screenshot in the working directory with the configured palette
+                    Keycode::F12 => {
+                        let screenshot_path = timestamped_path("screenshot", "png");
+                        let png = encode_png(&sdl_frontend.pixels, display_size.0, display_size.1);
+                        match std::fs::write(&screenshot_path, png) {
+                            Ok(_) => osd.show("Screenshot saved"),
+                            Err(_) => osd.show("Failed to write screenshot!")
+                        }
+                    }
+
+                    // Opens a rom from the file dialog when a key is pressed on the splash rom
+                    _ if chip8_configuration.rom_path.is_empty() => if rom_dialog.is_none() {
+                        rom_dialog = match open_rom_dialog(sdl_canvas.window()) {
+                            Ok(receiver) => Some(receiver),
+                            Err(msg) => break 'frame Some(msg.into())
+                        };
+                    }
+
+                    // Handles chip8 key press, where the second machine has its own keys
+                    _ => {
+                        for &(keycode, chip8_key) in &chip8_configuration.key_map {
+                            if sdl_key == keycode && autofire.press(chip8_key, true) {
+                                set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut sdl_frontend.keys, &remote_keys, chip8_key, true);
+                            }
                         }
+                        if let Some(split_machine) = &mut split_machine { split_machine.set_key(sdl_key, true); }
                     }
                 },
 
                 Event::KeyUp{keycode: Some(sdl_key), ..} => {
+                    if sdl_key == Keycode::Grave && is_turbo {
+                        is_turbo = false;
+                        osd.show("Normal clock");
+                    }
+
                     // Handles chip8 key release
-                    for chip8_key in 0..SDL3_CHIP8_KEY_MAP.len() {
-                        if sdl_key == SDL3_CHIP8_KEY_MAP[chip8_key] {
-                            chip8_context.keyboard[chip8_key] = false;
-                            chip8_context.key_released[chip8_key] = true;
+                    for &(keycode, chip8_key) in &chip8_configuration.key_map {
+                        if sdl_key == keycode && autofire.press(chip8_key, false) {
+                            set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut sdl_frontend.keys, &remote_keys, chip8_key, false);
                         }
                     }
+                    if let Some(split_machine) = &mut split_machine { split_machine.set_key(sdl_key, false); }
                 },
 
-                // Changes display and recalculates refresh rate when moved
-                Event::Window {win_event, ..} => {
-                    if let WindowEvent::Moved(..) = win_event {
+                // Handles gamepad hotplugging and maps its buttons and left stick to chip8 keys
+                Event::ControllerDeviceAdded{which, ..} => gamepads.add(which),
+                Event::ControllerDeviceRemoved{which, ..} => gamepads.remove(which),
+                Event::ControllerButtonDown{button, ..} => gamepads.press(button, true,
+                    &mut |key, is_pressed| if autofire.press(key, is_pressed) {
+                        set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut sdl_frontend.keys, &remote_keys, key, is_pressed)
+                    }),
+                Event::ControllerButtonUp{button, ..} => gamepads.press(button, false,
+                    &mut |key, is_pressed| if autofire.press(key, is_pressed) {
+                        set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut sdl_frontend.keys, &remote_keys, key, is_pressed)
+                    }),
+                Event::ControllerAxisMotion{axis, value, ..} => gamepads.move_axis(axis, value,
+                    &mut |key, is_pressed| if autofire.press(key, is_pressed) {
+                        set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut sdl_frontend.keys, &remote_keys, key, is_pressed)
+                    }),
+
+                // Changes display and recalculates refresh rate when the main window is moved
+                Event::Window {window_id, win_event, ..} => match win_event {
+                    WindowEvent::Moved(..) if window_id == sdl_canvas.window().id() => {
                         sdl_display = match sdl_canvas.window().get_display() {
                             Ok(display) => display,
-                            Err(_) => return Some("Failed to get window's display!")
+                            Err(error) => break 'frame Some(Chip8Error::sdl("Failed to get window's display!", error))
                         };
                         refresh_time_nanos = match sdl3_get_refresh_time(sdl_display) {
                             Some(time) => time,
-                            None => return None
+                            None => break 'frame None
                         };
                     }
+
+                    // Closes a debug window, where closing the main window quits even though SDL only quits after the last one
+                    WindowEvent::CloseRequested if !debug_windows.close(window_id) && debug_windows.is_open() => {
+                        events.push_back((Event::Quit{timestamp: 0}, true));
+                    }
+
+                    // Pauses or throttles in the background by the focus policy, resuming only what losing focus paused
+                    // Focus moving to a debug window is lost by the main window, then gained by the debug window
+                    WindowEvent::FocusLost => {
+                        is_unfocused = true;
+                        if chip8_configuration.focus_policy == FocusPolicy::Pause && !chip8_context.is_paused() {
+                            chip8_context.pause();
+                            volume.store(0, Ordering::Release);
+                            is_focus_paused = true;
+                        }
+                    }
+                    WindowEvent::FocusGained => {
+                        is_unfocused = false;
+                        if is_focus_paused {
+                            chip8_context.resume();
+                            if !is_muted { volume.store(chip8_configuration.volume as i32, Ordering::Release); }
+                            is_focus_paused = false;
+                        }
+                    }
+                    _ => ()
                 },
 
                 // Recalculates refresh rate when display mode changes
@@ -220,7 +1125,7 @@ fn app_main() -> Option<&'static str> {
                     if display == sdl_display && display_event == DisplayEvent::CurrentModeChanged {
                         refresh_time_nanos = match sdl3_get_refresh_time(sdl_display) {
                             Some(time) => time,
-                            None => return None
+                            None => break 'frame None
                         };
                     }
                 }
@@ -228,10 +1133,238 @@ fn app_main() -> Option<&'static str> {
             }
         }
 
+        // Queries the window's display every second, since some platforms don't send an event when it or its mode changes
+        // A failed query keeps the last refresh rate and tries again next second, reporting only the first failure in a row
+        if display_query_time.elapsed() >= DISPLAY_QUERY_PERIOD {
+            display_query_time = Instant::now();
+            if let Ok(display) = sdl_canvas.window().get_display() { sdl_display = display; }
+            match query_refresh_time(sdl_display) {
+                Some(time) => {
+                    is_display_query_failing = false;
+                    if time != refresh_time_nanos {
+                        refresh_time_nanos = time;
+                        frame_delta_buffer = 0;
+                    }
+                }
+                None if !is_display_query_failing => {
+                    is_display_query_failing = true;
+                    println!("Failed to get display mode!");
+                }
+                None => ()
+            }
+        }
+
+        // Handles debugger commands from the console
+        if let Some(message) = debugger.as_mut().and_then(|debugger| debugger.update(&mut chip8_context, &symbols))
+        && let Some(message) = handle_error(&mut chip8_context, message, chip8_configuration.error_policy, &mut debugger, &mut osd) {
+            break 'frame Some(message)
+        }
+
+        // Answers gdb packets, where an instruction that fails to step follows the error policy like a running one
+        if let Some(gdb_stub) = &mut gdb_stub && let Some(message) = gdb_stub.update(&mut chip8_context)
+        && let Some(message) = handle_error(&mut chip8_context, message, chip8_configuration.error_policy, &mut debugger, &mut osd) {
+            break 'frame Some(message)
+        }
+
+        // Applies the netplay client's keys, or receives the host's display and sends it the local keys
+        if let Some(netplay_host) = &mut netplay_host {
+            netplay_host.update(&mut |key, is_pressed| set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut remote_keys,
+                &sdl_frontend.keys, key, is_pressed));
+        }
+        if let Some(netplay_client) = &mut netplay_client {
+            if let Err(message) = netplay_client.update(&sdl_frontend.keys) {
+                break 'frame Some(message.into())
+            }
+            if let Some(is_beeping) = netplay_client.take_beep() {
+                netplay_beep_samples.store(match is_beeping { true => pack_beep(0, u32::MAX), false => 0 }, Ordering::Release);
+            }
+        }
+
+        // Reloads and restarts when the rom changes on disk
+        if let Some(rom_watcher) = &mut rom_watcher && rom_watcher.is_modified() {
+            match reload_rom(&mut chip8_context, &chip8_configuration.rom_path, &rom, &symbols) {
+                // A symbol file chosen on the command line stays in use
+                Ok(new_symbols) => {
+                    if chip8_configuration.symbols_path.is_none() { symbols = new_symbols; }
+                    osd.show("Reloaded");
+                }
+                Err(error) => osd.show(&error.to_string())
+            }
+        }
+
+        // Receives the rom chosen from the file dialog, staying on the splash rom if it's canceled
+        if let Some(receiver) = &rom_dialog && let Ok(path) = receiver.try_recv() {
+            rom_dialog = None;
+            match path.into_os_string().into_string() {
+                Ok(path) if path.is_empty() => (),
+                Ok(path) => next_rom_path = Some(path),
+                Err(_) => osd.show("Failed to receive rom path from dialog!")
+            }
+        }
+
+        // Switches to a rom chosen from the recent roms menu or the file dialog
+        if let Some(path) = next_rom_path.take() {
+            let program = read_program(&path, chip8_context.profile(), chip8_context.start_address());
+            match program.and_then(|(new_rom, new_symbols)| chip8_context.load_rom(&new_rom).map(|_| new_symbols)) {
+                Ok(new_symbols) => {
+                    symbols = new_symbols;
+                    // Movies and watchers belong to the previous rom
+                    movie = None;
+                    if chip8_configuration.is_watch { rom_watcher = Some(RomWatcher::new(&path)); }
+                    state_path = rom::state_path(&path);
+                    rpl_flags = RplFlags::load(&mut chip8_context, state_path.with_extension("flags"));
+                    recent_roms.add(&path);
+                    osd.show(&rom_name(&path));
+                    if chip8_configuration.is_rom_icon { rom_icon_frame = Some(chip8_context.frame_count() + ROM_ICON_FRAMES); }
+                    chip8_configuration.rom_path = path;
+                }
+                Err(error) => osd.show(&error.to_string())
+            }
+        }
+
+        // Shows the rom and whether it's paused in the window title
+        let is_uncapped = chip8_configuration.is_clock_max || is_turbo;
+        let clock = clock_name(is_uncapped, chip8_context.is_cosmac_timing(), chip8_context.clock_hz());
+        let title = window_title(&chip8_configuration.rom_path, &clock, chip8_context.is_paused());
+        if title != sdl_canvas.window().title() && let Err(error) = sdl_canvas.window_mut().set_title(&title) {
+            break 'frame Some(Chip8Error::sdl("Failed to set window title!", error))
+        }
+
         // Emulates chip8 for the frame time
         let emulation_start = std::time::Instant::now();
-        if let Some(message) = chip8_context.run(frame_delta as f32) {
-            return Some(message)
+        let emulation_delta = match emulation_speed {
+            EmulationSpeed::Normal => frame_delta,
+            EmulationSpeed::FastForward => frame_delta * FAST_FORWARD_SPEED,
+            EmulationSpeed::FrameAdvance => {
+                chip8_context.resume();
+                NANOS_IN_SECOND / 60
+            }
+        };
+
+        // Pulses held autofire keys for the time about to be emulated
+        if !chip8_context.is_paused() {
+            autofire.update(emulation_delta,
+                &mut |key, is_pressed| set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut sdl_frontend.keys, &remote_keys, key, is_pressed));
+        }
+
+        // Lockstep emulates whole frames once both players' keys arrive, and keeps a few frames of time while waiting
+        if let Some(lockstep) = &mut lockstep {
+            lockstep_buffer = (lockstep_buffer + emulation_delta).min(MOVIE_FRAME_NANOS * 4);
+            while lockstep_buffer >= MOVIE_FRAME_NANOS {
+                match lockstep.run_frame(&mut chip8_context, &sdl_frontend.keys) {
+                    Ok(true) => lockstep_buffer -= MOVIE_FRAME_NANOS,
+                    Ok(false) => break,
+                    Err(msg) => break 'frame Some(msg)
+                }
+            }
+        }
+
+        match &mut movie {
+            _ if netplay_client.is_some() || lockstep.is_some() => (),
+            _ if split_machine.as_ref().is_some_and(SplitMachine::is_compare) => (), // Runs both machines below
+            None if is_uncapped => {
+                let instruction_count = chip8_context.instruction_count();
+                if let Some(message) = chip8_context.run_uncapped(emulation_delta as f32, frame_instructions)
+                && let Some(message) = handle_error(&mut chip8_context, message, chip8_configuration.error_policy, &mut debugger, &mut osd) {
+                    break 'frame Some(message)
+                }
+
+                // Fits the budget to three quarters of the refresh time, from the speed of frames that used most of it
+                let executed = chip8_context.instruction_count() - instruction_count;
+                if executed * 2 >= frame_instructions {
+                    let elapsed = emulation_start.elapsed().as_nanos().max(1) as u64;
+                    let target = executed as u128 * refresh_time_nanos as u128 * 3 / 4 / elapsed as u128;
+                    frame_instructions = (target as u64).clamp(chip8_configuration.options.clock_hz as u64 / 60, MAX_FRAME_INSTRUCTIONS)
+                        .min(frame_instructions * 2);
+                }
+            }
+            None => {
+                // Runs a frame longer than a 60hz tick one tick at a time, pressing keys that arrived since the last tick
+                // The events are kept for the next frame's loop, whose hotkeys and repeated key changes still apply
+                // Each tick's beeps are offset by the ticks before it, since the frame's audio starts with the first tick
+                let mut remaining_delta = emulation_delta;
+                loop {
+                    let tick_delta = remaining_delta.min(NANOS_IN_SECOND / 60);
+                    frame_offset.set((emulation_delta - remaining_delta) as f32);
+                    remaining_delta -= tick_delta;
+                    let error = chip8_context.run(tick_delta as f32);
+                    frame_offset.set(0.0);
+                    if let Some(message) = error
+                    && let Some(message) = handle_error(&mut chip8_context, message, chip8_configuration.error_policy, &mut debugger, &mut osd) {
+                        break 'frame Some(message)
+                    }
+                    if remaining_delta == 0 { break }
+
+                    let is_playing = !pause_menu.is_open && !recent_roms.is_open && !chip8_configuration.rom_path.is_empty();
+                    for event in sdl_event_pump.poll_iter() {
+                        let (sdl_key, keymod, is_pressed) = match &event {
+                            Event::KeyDown{window_id, keycode: Some(sdl_key), keymod, ..}
+                            if !debug_windows.handles_key(*window_id, *sdl_key) => (*sdl_key, *keymod, true),
+                            Event::KeyUp{keycode: Some(sdl_key), keymod, ..} => (*sdl_key, *keymod, false),
+                            _ => {
+                                deferred_events.push_back((event, false));
+                                continue
+                            }
+                        };
+                        if is_playing && !keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) {
+                            for &(keycode, chip8_key) in &chip8_configuration.key_map {
+                                if sdl_key == keycode && autofire.press(chip8_key, is_pressed) {
+                                    set_key(&mut chip8_context, &mut movie, lockstep.is_some(), &mut sdl_frontend.keys, &remote_keys, chip8_key, is_pressed);
+                                }
+                            }
+                        }
+                        deferred_events.push_back((event, false));
+                    }
+                }
+            }
+
+            // Movies emulate whole frames so inputs land on the same frame when played back
+            Some(active_movie) => {
+                movie_buffer += emulation_delta;
+                while movie_buffer >= MOVIE_FRAME_NANOS {
+                    movie_buffer -= MOVIE_FRAME_NANOS;
+                    if let Some(message) = active_movie.run_frame(&mut chip8_context) {
+                        break 'frame Some(crash::report(&chip8_context, message))
+                    }
+                }
+
+                if active_movie.is_finished() {
+                    osd.show("Playback finished");
+                    movie = None;
+                }
+            }
+        }
+
+        // Runs the second machine for the same time while the first isn't paused
+        if let Some(split_machine) = &mut split_machine && !chip8_context.is_paused() {
+            match split_machine.run(&mut chip8_context, emulation_delta, &texture_creator, pixel_format, chip8_configuration.is_smooth) {
+                Ok(Some(message)) => {
+                    println!("{message}");
+                    osd.show(&message);
+                }
+                Ok(None) => (),
+                Err(error) => break 'frame Some(error)
+            }
+        }
+
+        // Reports the first replayed roll taken by another instruction, where the program stopped matching the log
+        if !is_divergence_reported && let Some(divergence) = chip8_context.random_divergence() {
+            println!("{}", random_log::describe_divergence(&divergence));
+            osd.show("Random rolls diverged from the log");
+            is_divergence_reported = true;
+        }
+
+        rpl_flags.update(&chip8_context);
+
+        // Returns to the splash rom when a SCHIP program exits, where a key opens another rom
+        if chip8_context.is_exited() && !chip8_configuration.rom_path.is_empty() {
+            next_rom_path = Some(String::new());
+        }
+
+        // Pauses again after advancing a frame
+        if emulation_speed == EmulationSpeed::FrameAdvance {
+            chip8_context.pause();
+            emulation_speed = EmulationSpeed::Normal;
         }
 
         // Displays the average emulation time every 1024 frames
@@ -245,20 +1378,101 @@ fn app_main() -> Option<&'static str> {
             }
         }
 
-        let frame_buffer = chip8_context.frame_buffer.as_slice();
-        let pixel_data= unsafe { from_raw_parts(frame_buffer.as_ptr().cast(), chip8::FRAME_BUFFER_SIZE * 4) };
-        if sdl_texture.update(None, pixel_data, chip8::FRAME_BUFFER_WIDTH as usize * 4).is_err() {
-            return Some("Failed to update texture!")
+        // Follows the display to a new resolution, which ends a recording at the old one
+        let new_display_size = match &mut netplay_client {
+            Some(netplay_client) => netplay_client.take_resize(),
+            None => chip8_context.take_display_mode_change().map(DisplayMode::size)
+        };
+        if let Some(new_display_size) = new_display_size {
+            display_size = new_display_size;
+            sdl_frontend.texture = match create_display_texture(&mut sdl_canvas, &texture_creator, pixel_format, display_size,
+                display_columns, &chip8_configuration) {
+                Ok(texture) => texture,
+                Err(msg) => break 'frame Some(msg)
+            };
+            if let Some(recording) = recording.take() {
+                recording.save(&timestamped_path("recording", "apng"));
+                osd.show("Recording saved");
+            }
+        }
+
+        // Colors and uploads the rows drawn since the last frame
+        sdl_frontend.frame_nanos = frame_delta;
+        let present_result = match &mut netplay_client {
+            Some(netplay_client) => netplay_client.present(&mut sdl_frontend),
+            None => chip8_context.present(&mut sdl_frontend)
+        };
+        if let Err(message) = present_result {
+            break 'frame Some(message)
         }
+        if let Some(netplay_host) = &mut netplay_host { netplay_host.send(&chip8_context); }
+        if let Some(recording) = &mut recording { recording.capture(&sdl_frontend.pixels, frame_delta); }
 
-        // Clear background and copies texture to renderer
+        // Turns the rom's screen into the window icon once it has drawn something after booting
+        if let Some(frame) = rom_icon_frame && chip8_context.frame_count() >= frame
+        && sdl_frontend.pixels.iter().any(|&pixel| pixel != chip8_configuration.palette[0]) {
+            rom_icon_frame = None;
+            let icon = rom_icon(&sdl_frontend.pixels, display_size);
+            if let Err(message) = set_window_icon(sdl_canvas.window_mut(), &icon, pixel_format) { println!("{message}"); }
+        }
+        if let Some(video) = &mut video_recording
+        && let Err(message) = video.capture(&sdl_frontend.pixels, display_size, chip8_context.frame_count()) {
+            println!("{message}");
+            osd.show(message);
+            video_recording = None;
+        }
+
+        // Clear background and copies texture to renderer, with the second machine's display to the right
         sdl_canvas.clear();
-        if sdl_canvas.copy(&mut sdl_texture, None, None).is_err() {
-            return Some("Failed to copy texture!")
+        let display_rect = Rect::new(0, 0, display_size.0, display_size.1);
+        if let Err(error) = sdl_canvas.copy(&sdl_frontend.texture, None, display_rect) {
+            break 'frame Some(Chip8Error::sdl("Failed to copy texture!", error))
         };
+        if let Some(split_machine) = &mut split_machine
+        && let Err(message) = split_machine.draw(&mut sdl_canvas, frame_delta, display_size) {
+            break 'frame Some(message)
+        }
+
+        // Draws post processing over the chip8 display
+        if chip8_configuration.display_filter != DisplayFilter::None
+        && let Err(message) = draw_scanlines(&mut sdl_canvas, (display_size.0 * display_columns, display_size.1)) {
+            break 'frame Some(message.into())
+        }
+        if let Some(vignette) = &vignette && let Err(error) = sdl_canvas.copy(vignette, None, None) {
+            break 'frame Some(Chip8Error::sdl("Failed to copy vignette texture!", error))
+        }
+
+        // Draws the overlays and status messages over everything else
+        speed_overlay.update(chip8_context.instruction_count(), is_vsync);
+        if let Err(message) = speed_overlay.draw(&mut sdl_canvas) {
+            break 'frame Some(message.into())
+        }
+        if let Err(message) = keypad_overlay.draw(&mut sdl_canvas, chip8_context.keys(), &chip8_configuration.key_map,
+            (display_size.0 * display_columns) as f32) {
+            break 'frame Some(message.into())
+        }
+        if let Err(message) = recent_roms.draw(&mut sdl_canvas) {
+            break 'frame Some(message.into())
+        }
+        if let Err(message) = pause_menu.draw(&mut sdl_canvas) {
+            break 'frame Some(message.into())
+        }
+        if chip8_configuration.rom_path.is_empty()
+        && let Err(message) = draw_text(&mut sdl_canvas, "Press a key to open a rom", 0.0, 0.0) {
+            break 'frame Some(message.into())
+        }
+        if let Err(message) = osd.draw(&mut sdl_canvas, display_size.1 as f32) {
+            break 'frame Some(message.into())
+        }
+
+        // Redraws the debug windows, which present right away without waiting for vsync
+        if let Err(message) = debug_windows.draw(&chip8_context, &chip8_configuration.palette, &symbols) {
+            break 'frame Some(message.into())
+        }
 
         // Sets frame delta to the next vsync interval or sleeps remaining frame time
-        frame_delta = match is_vsync {
+        let is_throttled = is_unfocused && chip8_configuration.focus_policy == FocusPolicy::Throttle;
+        frame_delta = match is_vsync && !is_throttled {
             true => {
                 // Presents frame to gpu and gets frame time
                 sdl_canvas.present();
@@ -269,13 +1483,18 @@ fn app_main() -> Option<&'static str> {
                 // https://frankforce.com/frame-rate-delta-buffering/
                 frame_delta_buffer += elapsed_time as i64;
                 let delta = match frame_delta_buffer / refresh_time_nanos as i64 {
-                    ..-1 => {
-                        // Turns off vsync if updating more than one frame ahead
-                        if CONSOLE_MESSAGES { println!("Turning off vsync"); }
+                    frames if frames <= -VSYNC_AHEAD_FRAMES => {
+                        // Turns off vsync if updating several frames ahead, and tries it again later in case it was a hitch
+                        // The delay backs off while vsync keeps failing, and starts over once it held for a while
+                        osd.show("Vsync off");
                         is_vsync = false;
+                        frame_delta_buffer = elapsed_time as i64;
+                        if vsync_on_time.elapsed() >= VSYNC_MAX_RETRY_DELAY { vsync_retry_delay = VSYNC_RETRY_DELAY; }
+                        vsync_retry_time = Some(Instant::now() + vsync_retry_delay);
+                        vsync_retry_delay = (vsync_retry_delay * 2).min(VSYNC_MAX_RETRY_DELAY);
                         elapsed_time
                     }
-                    -1 | 0 => refresh_time_nanos,
+                    ..=0 => refresh_time_nanos,
                     frames => {
                         // Missed at least one frame
                         if CONSOLE_MESSAGES {
@@ -289,32 +1508,43 @@ fn app_main() -> Option<&'static str> {
                 frame_delta_buffer -= delta as i64;
                 delta
             } false => {
+                // Goes back to vsync once the retry delay passes, if it was turned on
+                if !is_throttled && vsync_retry_time.is_some_and(|time| Instant::now() >= time) {
+                    osd.show("Vsync on");
+                    is_vsync = true;
+                    vsync_retry_time = None;
+                    vsync_on_time = Instant::now();
+                    frame_delta_buffer = 0;
+                }
+
+                // Throttling in the background sleeps for the rest of a longer frame
+                let frame_time_nanos = match is_throttled {
+                    true => THROTTLE_FRAME_NANOS,
+                    false => refresh_time_nanos
+                };
                 let mut elapsed_time = start_time.elapsed().as_nanos() as u64;
-                if CONSOLE_MESSAGES && elapsed_time >= refresh_time_nanos {
-                    println!("Frame took an extra {} nanoseconds", elapsed_time - refresh_time_nanos);
+                if CONSOLE_MESSAGES && elapsed_time >= frame_time_nanos {
+                    println!("Frame took an extra {} nanoseconds", elapsed_time - frame_time_nanos);
                 }
 
                 // https://blog.bearcats.nl/perfect-sleep-function/
-                if elapsed_time < refresh_time_nanos {
-                    // Sleeps to minimize spinlock
-                    const SLEEP_PERIOD: u64 = 1020000;
-                    let mut sleep_time = (refresh_time_nanos - elapsed_time) as u64;
-                    if sleep_time >= SLEEP_PERIOD {
-                        // Subtracts 1.02 ms because of sleep inaccuracy
-                        sleep_time -= SLEEP_PERIOD;
-                        sleep(Duration::from_nanos(sleep_time));
+                if elapsed_time < frame_time_nanos {
+                    // Sleeps to minimize spinlock, stopping short by the timer's inaccuracy
+                    let sleep_time = Duration::from_nanos(frame_time_nanos - elapsed_time);
+                    if sleep_time >= precise_sleep.margin() {
+                        precise_sleep.sleep(sleep_time - precise_sleep.margin());
                     }
 
                     // Spin-locks the rest remaining period
                     loop {
                         elapsed_time = start_time.elapsed().as_nanos() as u64;
-                        if elapsed_time >= refresh_time_nanos { break }
+                        if elapsed_time >= frame_time_nanos { break }
                         yield_now();
                     }
 
                     // Debug message when an extra 200 microseconds is slept
-                    if CONSOLE_MESSAGES && elapsed_time >= refresh_time_nanos + 200000 {
-                        println!("Slept for an extra {} nanoseconds", elapsed_time - refresh_time_nanos);
+                    if CONSOLE_MESSAGES && elapsed_time >= frame_time_nanos + 200000 {
+                        println!("Slept for an extra {} nanoseconds", elapsed_time - frame_time_nanos);
                     }
                 }
 
@@ -325,41 +1555,251 @@ fn app_main() -> Option<&'static str> {
             }
         };
 
-        // Caps frame delta in case of very long (10 ms) delay
+        // Caps frame delta in case of very long (100 ms) delay
         if frame_delta > NANOS_IN_SECOND / 10 { frame_delta = NANOS_IN_SECOND / 10 }
+    };
+
+    save_reports(&chip8_context, &chip8_configuration);
+
+    // Remembers the window for the next run, unless it filled the display
+    if is_geometry_remembered && sdl_canvas.window().fullscreen_state() == FullscreenType::Off {
+        WindowGeometry::save(sdl_canvas.window());
+    }
+
+    // Renders the recorded beeps over the emulated time, with the tone and volume they played at
+    let volume = (MAX_VOLUME as i32 * chip8_configuration.volume as i32 / 100) as f32;
+    if let (Some(path), Some(beep_track)) = (&chip8_configuration.audio_dump_path, &beep_track) {
+        let end_nanos = chip8_context.frame_count() as f64 * NANOS_IN_SECOND as f64 / 60.0;
+        let mut oscillator = Oscillator::new(chip8_configuration.tone_hz, chip8_configuration.waveform);
+        if let Err(message) = beep_track.borrow().write_wav(path, &mut oscillator, volume, end_nanos) {
+            println!("{message}");
+        }
+    }
+    if let (Some(video_recording), Some(beep_track)) = (video_recording, &beep_track) {
+        let mut oscillator = Oscillator::new(chip8_configuration.tone_hz, chip8_configuration.waveform);
+        if let Err(message) = video_recording.save(&beep_track.borrow(), &mut oscillator, volume) {
+            println!("{message}");
+        }
+    }
+    result
+}
+
+// Applies the error policy when the program fails, returning the error when the emulator should exit
+// Other errors always exit with a crash report
+#[cfg(feature = "sdl")]
+fn handle_error(chip8: &mut Chip8, error: Chip8Error, policy: ErrorPolicy, debugger: &mut Option<Debugger>, osd: &mut Osd)
+    -> Option<Chip8Error> {
+    match policy {
+        _ if !error.is_crash() => Some(crash::report(chip8, error)),
+        ErrorPolicy::Terminate => Some(crash::report(chip8, error)),
+        ErrorPolicy::Skip if crash::skip_instruction(chip8, &error) => {
+            println!("{error} Skipped it.");
+            osd.show(&error.to_string());
+            None
+        }
+
+        // Pauses on the failing instruction, which is also where skipping falls back to when the program counter is invalid
+        ErrorPolicy::Debug | ErrorPolicy::Skip => {
+            println!("{error}");
+            osd.show(&error.to_string());
+            debugger.get_or_insert_with(Debugger::new);
+            chip8.pause();
+            None
+        }
+    }
+}
+
+// Writes the instruction profile, coverage map, and random log on exit, including after an emulation error
+fn save_reports(chip8: &Chip8, config: &Chip8Configuration) {
+    if let Some(path) = &config.profile_path
+    && let Err(message) = profiler::write_report(chip8, path) {
+        println!("{message}");
+    }
+    if let Some(path) = &config.coverage_path
+    && let Err(message) = coverage::write_map(chip8, path) {
+        println!("{message}");
+    }
+    if let Some(path) = &config.random_log_path
+    && let Err(message) = random_log::write_log(chip8, path) {
+        println!("{message}");
+    }
+}
+
+// Sends host input to the chip8 context, or through the movie when one is recording or playing
+// Keys are tracked per player, so a key stays pressed while the local or netplay player holds it
+// Lockstep applies the tracked keys at the start of each frame instead
+fn set_key(chip8: &mut Chip8, movie: &mut Option<Movie>, is_lockstep: bool, keys: &mut Keys, other_keys: &Keys, key: usize,
+    is_pressed: bool) {
+    keys[key] = is_pressed;
+    if is_lockstep { return }
+    let is_pressed = is_pressed || other_keys[key];
+    if chip8.keys()[key] == is_pressed { return }
+    match movie {
+        Some(movie) => movie.set_key(chip8, key, is_pressed),
+        None => chip8.set_key(key, is_pressed)
+    }
+}
+
+// Creates the streaming texture for a display resolution, and scales that resolution to the window by the scaling mode
+// Displays side by side are scaled together as columns of the same resolution
+#[cfg(feature = "sdl")]
+fn create_display_texture<'a>(canvas: &mut Canvas<Window>, texture_creator: &'a TextureCreator<WindowContext>,
+    pixel_format: PixelFormat, (width, height): (u32, u32), columns: u32, configuration: &Chip8Configuration)
+    -> Result<Texture<'a>, Chip8Error> {
+    if let Err(error) = canvas.set_logical_size(width * columns, height, configuration.scaling_mode.presentation()) {
+        return Err(Chip8Error::sdl("Failed to set logical size!", error))
+    }
+    create_texture(texture_creator, pixel_format, (width, height), configuration.is_smooth)
+}
+
+// Creates a streaming texture for a display resolution
+#[cfg(feature = "sdl")]
+pub fn create_texture(texture_creator: &TextureCreator<WindowContext>, pixel_format: PixelFormat, (width, height): (u32, u32),
+    is_smooth: bool) -> Result<Texture<'_>, Chip8Error> {
+    let mut texture = match texture_creator.create_texture_streaming(pixel_format, width, height) {
+        Ok(texture) => texture,
+        Err(error) => return Err(Chip8Error::sdl("Failed to initialize texture!", error))
+    };
+    // Keeps pixels sharp unless smooth scaling is requested for non integer scales
+    texture.set_scale_mode(match is_smooth {
+        true => ScaleMode::Linear,
+        false => ScaleMode::Nearest
+    });
+    Ok(texture)
+}
+
+// Formats a window title like "chip8-emulator — pong.ch8 (540 Hz, paused)"
+fn window_title(rom_path: &str, clock: &str, is_paused: bool) -> String {
+    let status = match is_paused { true => ", paused", false => "" };
+    format!("chip8-emulator — {} ({clock}{status})", rom_name(rom_path))
+}
+
+// Names the clock for the title and messages, where the uncapped clock and COSMAC timing ignore the clock speed
+fn clock_name(is_uncapped: bool, is_cosmac_timing: bool, clock_hz: u32) -> String {
+    match (is_uncapped, is_cosmac_timing) {
+        (true, _) => String::from("max clock"),
+        (false, true) => String::from("COSMAC timing"),
+        (false, false) => format!("{clock_hz} Hz")
+    }
+}
+
+// Names a file in the working directory after the current time, so repeated captures don't overwrite each other
+fn timestamped_path(prefix: &str, extension: &str) -> String {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    format!("{prefix}-{timestamp}.{extension}")
+}
+
+// Converts sound timer durations into samples for the audio callback, and records the beeps for -dump-audio and -record-video
+struct SampleCounter {
+    beep_samples: Arc<AtomicU64>, // Packed by pack_beep
+    beep_track: Option<Rc<RefCell<BeepTrack>>>,
+    frame_offset: Rc<Cell<f32>> // Emulated nanoseconds from the start of the frame to the run() call sending events
+}
+
+impl AudioSink for SampleCounter {
+    fn audio_event(&mut self, event: AudioEvent) {
+        // Delays the change by its offset into the frame, since the frame's instructions all ran when it started
+        let frame_offset = self.frame_offset.get();
+        let samples = |nanos: f32| (nanos * 48000.0 / NANOS_IN_SECOND as f32) as u32;
+        let _ = self.beep_samples.fetch_update(Ordering::AcqRel, Ordering::Acquire, |beep| {
+            let (start, end) = unpack_beep(beep);
+            Some(match event {
+                // A beep still playing at the offset carries on into the new one without a gap
+                AudioEvent::Start{offset_nanos, duration_nanos, ..} => {
+                    let offset = samples(frame_offset + offset_nanos);
+                    let start = match start <= offset && offset <= end && end > 0 { true => start, false => offset };
+                    pack_beep(start, offset.saturating_add(samples(duration_nanos)))
+                }
+                // Stopping cancels the rest of the beep after the offset
+                AudioEvent::Stop{offset_nanos, ..} => pack_beep(start, end.min(samples(frame_offset + offset_nanos)))
+            })
+        });
+        if let Some(beep_track) = &self.beep_track { beep_track.borrow_mut().record(event); }
+    }
+}
+
+// Packs the samples until the beep starts and until it ends, counted from the audio callback's next sample, so both
+// change together in one atomic
+fn pack_beep(start: u32, end: u32) -> u64 {
+    (start as u64) << 32 | end as u64
+}
+
+fn unpack_beep(beep: u64) -> (u32, u32) {
+    ((beep >> 32) as u32, beep as u32)
+}
+
+// Generates the buzzer's filtered waveform one sample at a time, for the audio callback and recordings
+pub struct Oscillator {
+    phase: f32, // Position in the tone's period from 0 to 1
+    previous: i16,
+    noise: u32, // Xorshift state of the noise waveform
+    noise_level: f32,
+    tone_hz: f32,
+    waveform: Waveform
+}
+
+impl Oscillator {
+    pub fn new(tone_hz: f32, waveform: Waveform) -> Oscillator {
+        Oscillator{phase: 0.0, previous: 0, noise: 0x12345678, noise_level: 0.0, tone_hz, waveform}
+    }
+
+    // Volume is the wave's amplitude
+    pub fn next_sample(&mut self, volume: f32) -> i16 {
+        // Calculates sample from the waveform's phase
+        let wave = match self.waveform {
+            Waveform::Square => match self.phase < 0.5 {
+                true => 1.0,
+                false => -1.0
+            },
+            Waveform::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            Waveform::Sine => (self.phase * std::f32::consts::TAU).sin(),
+            Waveform::Noise => self.noise_level
+        };
+
+        // Blends the previous sample with the wave
+        self.previous = (self.previous as f32 * 0.6) as i16 + (wave * volume) as i16;
+
+        // Picks a new noise level at the start of each period
+        self.phase += self.tone_hz / 48000.0;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+            self.noise ^= self.noise << 13;
+            self.noise ^= self.noise >> 17;
+            self.noise ^= self.noise << 5;
+            self.noise_level = self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0;
+        }
+        self.previous
     }
 }
 
 // Audio callback rendering a filtered square wave
+#[cfg(feature = "sdl")]
 struct AudioState {
     buffer: Vec<i16>,
-    phase: u16,
-    previous: i16,
-    remaining_samples: Arc<AtomicI32>
+    oscillator: Oscillator,
+    beep_samples: Arc<AtomicU64>, // Packed by pack_beep
+    volume: Arc<AtomicI32> // From 0 to 100, where 0 mutes
 }
 
+#[cfg(feature = "sdl")]
 impl AudioCallback<i16> for AudioState {
-    fn callback(&mut self, stream: &mut AudioStream, mut requested: i32) {
-        // Caps the played samples at the remaining samples
-        let remaining_samples = self.remaining_samples.fetch_sub(requested, Ordering::AcqRel);
-        if remaining_samples < requested { requested = remaining_samples; }
+    fn callback(&mut self, stream: &mut AudioStream, requested: i32) {
+        // Counts the beep's start and end down by the requested samples, which stay at zero once the beep ends or is canceled
+        let requested = requested.max(0) as u32;
+        let beep = self.beep_samples.fetch_update(Ordering::AcqRel, Ordering::Acquire, |beep| {
+            let (start, end) = unpack_beep(beep);
+            Some(pack_beep(start.saturating_sub(requested), end.saturating_sub(requested)))
+        }).unwrap_or(0);
+        let (start, end) = unpack_beep(beep);
+        let tone_end = end.min(requested);
 
-        // Sets buffer length to zero for next iteration
+        // Sets buffer length to zero for next iteration, then leads a beep starting later in the request with silence
         self.buffer.clear();
+        let volume = (MAX_VOLUME as i32 * self.volume.load(Ordering::Acquire) / 100) as f32;
+        if start < tone_end { self.buffer.resize(start as usize, 0); }
 
-        for _ in 0..requested {
-            // Calculates sample from square wave phase
-            const HALF_PERIOD_SAMPLES: u16 = (48000.0 / (261.63 * 2.0)) as u16;
-            const VOLUME: i16 = 1024;
-            let square = match self.phase < HALF_PERIOD_SAMPLES {
-                true => VOLUME,
-                false => -VOLUME,
-            };
-
-            // Blends the previous sample with a square wave
-            self.previous = (self.previous as f32 * 0.6) as i16 + square;
-            self.buffer.push(self.previous);
-            self.phase = (self.phase + 1) % (HALF_PERIOD_SAMPLES * 2);
+        for _ in start..tone_end {
+            self.buffer.push(self.oscillator.next_sample(volume));
         }
 
         // Copies audio samples from a buffer to the audio stream
@@ -369,14 +1809,64 @@ impl AudioCallback<i16> for AudioState {
     }
 }
 
+// Fills the display borderless or with its desktop mode as an exclusive mode, or returns to a window
+#[cfg(feature = "sdl")]
+fn set_fullscreen(window: &mut Window, mode: FullscreenMode, display: Display) -> Result<(), Chip8Error> {
+    let display_mode = match mode {
+        FullscreenMode::Exclusive => match display.get_mode() {
+            Ok(display_mode) => Some(display_mode),
+            Err(error) => return Err(Chip8Error::sdl("Failed to get display mode!", error))
+        }
+        _ => None
+    };
+    if let Err(error) = window.set_display_mode(display_mode) {
+        return Err(Chip8Error::sdl("Failed to set the fullscreen display mode!", error))
+    }
+    window.set_fullscreen(mode != FullscreenMode::Off).map_err(|error| Chip8Error::sdl("Failed to set fullscreen mode!", error))
+}
+
 // Returns frame time of a sdl display in nanoseconds
+#[cfg(feature = "sdl")]
 fn sdl3_get_refresh_time(display: Display) -> Option<u64> {
-    let display_mode = match display.get_mode() {
-        Ok(mode) => mode,
-        Err(_) => {
-            println!("Failed to get display mode!");
-            return None
+    let refresh_time = query_refresh_time(display);
+    if refresh_time.is_none() { println!("Failed to get display mode!"); }
+    refresh_time
+}
+
+// Returns frame time of a sdl display in nanoseconds without reporting failures, for the periodic display query
+#[cfg(feature = "sdl")]
+fn query_refresh_time(display: Display) -> Option<u64> {
+    let display_mode = display.get_mode().ok()?;
+
+    // Displays with an unknown or variable refresh rate report 0, so they're paced at 60 hz
+    match display_mode.refresh_rate > 0.0 {
+        true => Some((NANOS_IN_SECOND as f32 / display_mode.refresh_rate) as u64),
+        false => Some(NANOS_IN_SECOND / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chip8_emulator::{Chip8Options, Profile};
+
+    // A beep started in a frame's second tick begins a tick into the frame's audio, not at its start
+    #[test]
+    fn second_tick_beep_is_offset_by_first_tick() {
+        // Waits on a delay timer of 1, which runs out in the second tick, then sets the sound timer to 5
+        let rom = [0x60, 0x01, 0xF0, 0x15, 0xF1, 0x07, 0x31, 0x00, 0x12, 0x04, 0x60, 0x05, 0xF0, 0x18, 0x12, 0x0E];
+        let mut chip8 = Chip8::init(&rom, &Chip8Options::new(Profile::Chip8, 0)).unwrap();
+        let beep_samples = Arc::new(AtomicU64::new(0));
+        let frame_offset = Rc::new(Cell::new(0.0));
+        chip8.set_audio_sink(Box::new(SampleCounter{beep_samples: beep_samples.clone(), beep_track: None, frame_offset: frame_offset.clone()}));
+
+        let tick_nanos = (NANOS_IN_SECOND / 60) as f32;
+        for tick in 0..2 {
+            frame_offset.set(tick as f32 * tick_nanos);
+            assert!(chip8.run(tick_nanos).is_none());
         }
-    };
-    Some((NANOS_IN_SECOND as f32 / display_mode.refresh_rate) as u64)
+        let (start, end) = unpack_beep(beep_samples.load(Ordering::Acquire));
+        assert!((800..1600).contains(&start), "the beep starts {start} samples into the frame");
+        assert!(end > start);
+    }
 }
@@ -0,0 +1,146 @@
+use sdl3::{event::Event, keyboard::{Keycode, Mod}, messagebox::{show_message_box, ButtonData, ClickedButton, MessageBoxButtonFlag, MessageBoxFlag}, render::Canvas, video::Window};
+use chip8_emulator::Chip8;
+use crate::osd::draw_text;
+
+// Items run the hotkey with the same action, so the menu and the keyboard can't drift apart
+const MAIN_ITEMS: &[(&str, MenuAction)] = &[
+    ("Resume", MenuAction::Close),
+    ("Reset", MenuAction::Hotkey(Keycode::F2, Mod::NOMOD)),
+    ("Open rom", MenuAction::Hotkey(Keycode::O, Mod::LCTRLMOD)),
+    ("Save state", MenuAction::Hotkey(Keycode::F5, Mod::NOMOD)),
+    ("Load state", MenuAction::Hotkey(Keycode::F7, Mod::NOMOD)),
+    ("Options", MenuAction::Page(MenuPage::Options)),
+    ("Debug", MenuAction::Page(MenuPage::Debug)),
+    ("Quit", MenuAction::Quit)
+];
+const OPTION_ITEMS: &[(&str, MenuAction)] = &[
+    ("Fullscreen", MenuAction::Hotkey(Keycode::F11, Mod::NOMOD)),
+    ("Speed overlay", MenuAction::Hotkey(Keycode::F3, Mod::NOMOD)),
+    ("Keypad overlay", MenuAction::Hotkey(Keycode::F4, Mod::NOMOD)),
+    ("Mute", MenuAction::Hotkey(Keycode::M, Mod::NOMOD)),
+    ("Back", MenuAction::Back)
+];
+const DEBUG_ITEMS: &[(&str, MenuAction)] = &[
+    ("Registers window", MenuAction::Hotkey(Keycode::_1, Mod::LCTRLMOD)),
+    ("Memory window", MenuAction::Hotkey(Keycode::_2, Mod::LCTRLMOD)),
+    ("Sprites window", MenuAction::Hotkey(Keycode::_3, Mod::LCTRLMOD)),
+    ("Palette window", MenuAction::Hotkey(Keycode::_4, Mod::LCTRLMOD)),
+    ("Back", MenuAction::Back)
+];
+
+#[derive(Clone, Copy)]
+enum MenuAction {
+    Close,
+    Hotkey(Keycode, Mod),
+    Page(MenuPage),
+    Back,
+    Quit
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MenuPage {
+    Main,
+    Options,
+    Debug
+}
+
+// Pause menu opened with Escape, drawn with the on screen display's font over the paused machine
+pub struct PauseMenu {
+    pub is_open: bool,
+    page: MenuPage,
+    selected: usize,
+    was_paused: bool // Closing leaves a machine paused that was paused before opening
+}
+
+impl PauseMenu {
+    pub fn new() -> PauseMenu {
+        PauseMenu{is_open: false, page: MenuPage::Main, selected: 0, was_paused: false}
+    }
+
+    pub fn open(&mut self, chip8: &mut Chip8) {
+        self.is_open = true;
+        self.page = MenuPage::Main;
+        self.selected = 0;
+        self.was_paused = chip8.is_paused();
+        chip8.pause();
+    }
+
+    pub fn close(&mut self, chip8: &mut Chip8) {
+        self.is_open = false;
+        if !self.was_paused { chip8.resume(); }
+    }
+
+    // Handles a key while open, returning the event of a chosen item for the main loop to handle like the real one
+    pub fn press(&mut self, chip8: &mut Chip8, keycode: Keycode) -> Option<Event> {
+        let items = self.items();
+        match keycode {
+            Keycode::Up => self.selected = (self.selected + items.len() - 1) % items.len(),
+            Keycode::Down => self.selected = (self.selected + 1) % items.len(),
+            Keycode::Escape if self.page != MenuPage::Main => self.show_main(),
+            Keycode::Escape => self.close(chip8),
+            Keycode::Return => match items[self.selected].1 {
+                MenuAction::Close => self.close(chip8),
+                MenuAction::Page(page) => {
+                    self.page = page;
+                    self.selected = 0;
+                }
+                MenuAction::Back => self.show_main(),
+                MenuAction::Quit => return Some(Event::Quit{timestamp: 0}),
+
+                // Pages stay open so several options can be changed, while the main items close the menu first
+                MenuAction::Hotkey(keycode, keymod) => {
+                    if self.page == MenuPage::Main { self.close(chip8); }
+                    return Some(Event::KeyDown{timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None, keymod,
+                        repeat: false, which: 0, raw: 0})
+                }
+            }
+            _ => ()
+        }
+        None
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) -> Result<(), &'static str> {
+        if !self.is_open { return Ok(()) }
+
+        let mut text = String::from(match self.page {
+            MenuPage::Main => "Paused\n",
+            MenuPage::Options => "Options\n",
+            MenuPage::Debug => "Debug\n"
+        });
+        for (i, (name, _)) in self.items().iter().enumerate() {
+            let marker = match i == self.selected { true => "> ", false => "  " };
+            text += &format!("{marker}{name}\n");
+        }
+        draw_text(canvas, &text, 0.0, 0.0)
+    }
+
+    fn items(&self) -> &'static [(&'static str, MenuAction)] {
+        match self.page {
+            MenuPage::Main => MAIN_ITEMS,
+            MenuPage::Options => OPTION_ITEMS,
+            MenuPage::Debug => DEBUG_ITEMS
+        }
+    }
+
+    // Returns from a page to the main items, selecting the page's item again
+    fn show_main(&mut self) {
+        let page = self.page;
+        self.page = MenuPage::Main;
+        self.selected = MAIN_ITEMS.iter().position(|(_, action)| matches!(action, MenuAction::Page(item_page) if *item_page == page))
+            .unwrap_or(0);
+    }
+}
+
+// Asks whether to quit without a recent save state, quitting anyway when the message box fails
+pub fn confirm_quit(window: &Window) -> bool {
+    let buttons = [
+        ButtonData{flags: MessageBoxButtonFlag::RETURNKEY_DEFAULT, button_id: 0, text: "Quit"},
+        ButtonData{flags: MessageBoxButtonFlag::ESCAPEKEY_DEFAULT, button_id: 1, text: "Cancel"}
+    ];
+    let message = "The machine state hasn't been saved in the last 5 minutes. Quit anyway?";
+    match show_message_box(MessageBoxFlag::WARNING, &buttons, "Quit chip8 emulator", message, window, None) {
+        Ok(ClickedButton::CustomButton(button)) => button.button_id == 0,
+        Ok(ClickedButton::CloseButton) => false,
+        Err(_) => true
+    }
+}
@@ -0,0 +1,103 @@
+use std::{fs::File, io::{BufWriter, Write}};
+use chip8_emulator::{Chip8, Chip8Error};
+
+// Movies replay whole 60hz frames, so they don't depend on the host's frame rate
+pub const MOVIE_FRAME_NANOS: u64 = 1000000000 / 60;
+const MOVIE_MAGIC: &[u8; 4] = b"C8MV";
+const MOVIE_VERSION: u8 = 1;
+
+// Journals key events by frame number, after a header with the random number generator's seed
+// Each event is a little endian frame number, the chip8 key, and whether it was pressed
+pub enum Movie {
+    Recording{file: BufWriter<File>, frame: u32},
+    Playback{events: Vec<(u32, usize, bool)>, next_event: usize, frame: u32}
+}
+
+impl Movie {
+    // Creates a movie file with a random seed for the chip8 context
+    pub fn record(path: &str) -> Result<(Movie, u64), &'static str> {
+        let seed = rand::random::<u64>();
+        let mut file = match File::create(path) {
+            Ok(file) => BufWriter::new(file),
+            Err(_) => return Err("Failed to create the movie file!")
+        };
+
+        let mut header = MOVIE_MAGIC.to_vec();
+        header.push(MOVIE_VERSION);
+        header.extend_from_slice(&seed.to_le_bytes());
+        if file.write_all(&header).is_err() {
+            return Err("Failed to write the movie file!")
+        }
+
+        println!("Recording inputs to {path}");
+        Ok((Movie::Recording{file, frame: 0}, seed))
+    }
+
+    // Reads a movie's events and the seed it was recorded with
+    pub fn play(path: &str) -> Result<(Movie, u64), &'static str> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(_) => return Err("Path to the movie is invalid!")
+        };
+
+        if bytes.len() < 13 || &bytes[..4] != MOVIE_MAGIC {
+            return Err("Not a valid movie file!")
+        }
+        if bytes[4] != MOVIE_VERSION {
+            return Err("Movie file is from an unsupported version!")
+        }
+
+        let seed = u64::from_le_bytes(bytes[5..13].try_into().unwrap());
+        let mut events = Vec::new();
+        for event in bytes[13..].chunks(6) {
+            match event {
+                &[f0, f1, f2, f3, key, is_pressed] if key < 16 => {
+                    events.push((u32::from_le_bytes([f0, f1, f2, f3]), key as usize, is_pressed != 0));
+                }
+                _ => return Err("Movie file has an invalid event!")
+            }
+        }
+
+        println!("Playing back inputs from {path}");
+        Ok((Movie::Playback{events, next_event: 0, frame: 0}, seed))
+    }
+
+    // Presses a key before the next frame while recording, and ignores the host during playback
+    pub fn set_key(&mut self, chip8: &mut Chip8, key: usize, is_pressed: bool) {
+        if let Movie::Recording{file, frame} = self {
+            let mut event = frame.to_le_bytes().to_vec();
+            event.extend_from_slice(&[key as u8, is_pressed as u8]);
+            if file.write_all(&event).is_err() {
+                println!("Failed to write the movie file!");
+            }
+            chip8.set_key(key, is_pressed);
+        }
+    }
+
+    // Emulates one frame after replaying its key events, where frames don't pass while paused
+    pub fn run_frame(&mut self, chip8: &mut Chip8) -> Option<Chip8Error> {
+        if chip8.is_paused() { return None }
+
+        let frame = match self {
+            Movie::Recording{frame, ..} => frame,
+            Movie::Playback{events, next_event, frame} => {
+                while let Some(&(event_frame, key, is_pressed)) = events.get(*next_event) && event_frame <= *frame {
+                    chip8.set_key(key, is_pressed);
+                    *next_event += 1;
+                }
+                frame
+            }
+        };
+
+        *frame += 1;
+        chip8.run(MOVIE_FRAME_NANOS as f32)
+    }
+
+    // Playback ends after its last event, which returns input to the host
+    pub fn is_finished(&self) -> bool {
+        match self {
+            Movie::Recording{..} => false,
+            Movie::Playback{events, next_event, ..} => *next_event >= events.len()
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::{io::{BufReader, Read, Write}, net::{TcpListener, TcpStream}, ops::Range, sync::mpsc::{self, Receiver, Sender}, thread};
+use chip8_emulator::{Chip8, Chip8Error, FrameBuffer, Frontend, Keys};
+
+// Messages are a type byte and a little endian u32 payload length, followed by the payload
+const MESSAGE_KEY: u8 = 0;  // Client to host: a chip8 key, then 1 for pressed or 0 for released
+const MESSAGE_ROWS: u8 = 1; // Host to client: width, height, and first row as little endian u16s, then palette indices
+const MESSAGE_BEEP: u8 = 2; // Host to client: 1 while the sound timer runs, or 0
+
+// Largest payload, which is every row of the largest display after the row header
+const MAX_PAYLOAD_SIZE: usize = 6 + 256 * 192;
+
+// Messages from a connection thread to the main thread
+enum NetplayMessage {
+    Connected(TcpStream),
+    Key(usize, bool),
+    Rows{width: u32, height: u32, first_row: u32, pixels: Vec<u8>},
+    Beep(bool),
+    Disconnected
+}
+
+// Runs the machine and streams its display to one remote player, whose keys merge with the local ones
+pub struct NetplayHost {
+    messages: Receiver<NetplayMessage>,
+    connection: Option<TcpStream>,
+    sent_frame: Vec<u8>, // Frame buffer the client last received, so only changed rows are sent
+    sent_size: (u32, u32),
+    is_beeping: bool
+}
+
+impl NetplayHost {
+    // Accepts one player at a time on a separate thread
+    pub fn listen(port: u16) -> Option<NetplayHost> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).ok()?;
+        let (sender, receiver) = mpsc::channel::<NetplayMessage>();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let _ = stream.set_nodelay(true);
+                let Ok(writer) = stream.try_clone() else { continue };
+                if sender.send(NetplayMessage::Connected(writer)).is_err() { return }
+
+                read_messages(stream, &sender);
+                if sender.send(NetplayMessage::Disconnected).is_err() { return }
+            }
+        });
+
+        println!("Hosting netplay on port {port}");
+        Some(NetplayHost{messages: receiver, connection: None, sent_frame: Vec::new(), sent_size: (0, 0), is_beeping: false})
+    }
+
+    // Applies the remote player's keys, where a disconnect releases all of them
+    pub fn update(&mut self, set_key: &mut dyn FnMut(usize, bool)) {
+        while let Ok(message) = self.messages.try_recv() {
+            match message {
+                NetplayMessage::Connected(connection) => {
+                    println!("Netplay player joined");
+                    self.connection = Some(connection);
+                    self.sent_frame.clear();
+                    self.is_beeping = false;
+                }
+                NetplayMessage::Key(key, is_pressed) => set_key(key, is_pressed),
+                NetplayMessage::Disconnected => {
+                    println!("Netplay player left");
+                    self.connection = None;
+                    for key in 0..16 { set_key(key, false); }
+                }
+                _ => ()
+            }
+        }
+    }
+
+    // Sends the rows that changed since the last send, all rows after connecting or resizing, and the buzzer state
+    pub fn send(&mut self, chip8: &Chip8) {
+        let Some(connection) = &mut self.connection else { return };
+        let frame_buffer = chip8.front_buffer();
+        let size = chip8.display_mode().size();
+        let width = size.0 as usize;
+
+        let is_resized = self.sent_size != size || self.sent_frame.len() != frame_buffer.len();
+        let is_changed = |row: &usize| is_resized || frame_buffer[row * width..(row + 1) * width] != self.sent_frame[row * width..(row + 1) * width];
+        let first_row = (0..size.1 as usize).find(is_changed);
+        let last_row = (0..size.1 as usize).rev().find(is_changed);
+
+        let mut is_sent = true;
+        if let (Some(first_row), Some(last_row)) = (first_row, last_row) {
+            let mut payload = Vec::new();
+            for value in [size.0, size.1, first_row as u32] { payload.extend_from_slice(&(value as u16).to_le_bytes()); }
+            payload.extend_from_slice(&frame_buffer[first_row * width..(last_row + 1) * width]);
+            is_sent &= write_message(connection, MESSAGE_ROWS, &payload);
+        }
+
+        let is_beeping = chip8.sound_timer() > 1;
+        if is_beeping != self.is_beeping {
+            is_sent &= write_message(connection, MESSAGE_BEEP, &[is_beeping as u8]);
+        }
+
+        // Waits for the reading thread to report the disconnect when a write fails
+        match is_sent {
+            true => {
+                self.sent_frame.clear();
+                self.sent_frame.extend_from_slice(frame_buffer);
+                self.sent_size = size;
+                self.is_beeping = is_beeping;
+            }
+            false => self.connection = None
+        }
+    }
+}
+
+// Shows a hosted machine's display and sends the local keys to it, without emulating anything itself
+pub struct NetplayClient {
+    messages: Receiver<NetplayMessage>,
+    connection: TcpStream,
+    frame_buffer: Vec<u8>,
+    size: (u32, u32),
+    dirty_rows: Range<u32>,
+    is_resized: bool,
+    sent_keys: Keys,
+    beep: Option<bool> // Buzzer change since the last take_beep
+}
+
+impl NetplayClient {
+    pub fn join(address: &str) -> Result<NetplayClient, &'static str> {
+        let connection = match TcpStream::connect(address) {
+            Ok(connection) => connection,
+            Err(_) => return Err("Failed to join the netplay host!")
+        };
+        let _ = connection.set_nodelay(true);
+        let reader = match connection.try_clone() {
+            Ok(reader) => reader,
+            Err(_) => return Err("Failed to join the netplay host!")
+        };
+
+        let (sender, receiver) = mpsc::channel::<NetplayMessage>();
+        thread::spawn(move || {
+            read_messages(reader, &sender);
+            let _ = sender.send(NetplayMessage::Disconnected);
+        });
+
+        println!("Joined netplay at {address}");
+        Ok(NetplayClient{messages: receiver, connection, frame_buffer: Vec::new(), size: (0, 0), dirty_rows: 0..0,
+            is_resized: false, sent_keys: [false; 16], beep: None})
+    }
+
+    // Copies received rows into the display, and sends the keys that changed since the last update
+    pub fn update(&mut self, keys: &Keys) -> Result<(), &'static str> {
+        while let Ok(message) = self.messages.try_recv() {
+            match message {
+                NetplayMessage::Rows{width, height, first_row, pixels} => {
+                    if (width, height) != self.size {
+                        if width == 0 || height == 0 || width * height > 256 * 192 {
+                            return Err("The netplay host sent an invalid display size!")
+                        }
+                        self.size = (width, height);
+                        self.frame_buffer = vec![0; width as usize * height as usize];
+                        self.dirty_rows = 0..height;
+                        self.is_resized = true;
+                    }
+                    let start = (first_row * width) as usize;
+                    let Some(rows) = self.frame_buffer.get_mut(start..start + pixels.len()) else {
+                        return Err("The netplay host sent rows outside the display!")
+                    };
+                    rows.copy_from_slice(&pixels);
+
+                    let end_row = first_row + (pixels.len() as u32).div_ceil(width.max(1));
+                    self.dirty_rows = match self.dirty_rows.is_empty() {
+                        true => first_row..end_row,
+                        false => self.dirty_rows.start.min(first_row)..self.dirty_rows.end.max(end_row)
+                    };
+                }
+                NetplayMessage::Beep(is_on) => self.beep = Some(is_on),
+                NetplayMessage::Disconnected => return Err("The netplay host disconnected!"),
+                _ => ()
+            }
+        }
+
+        for (key, (&is_pressed, sent_key)) in keys.iter().zip(self.sent_keys.iter_mut()).enumerate() {
+            if is_pressed != *sent_key {
+                if !write_message(&mut self.connection, MESSAGE_KEY, &[key as u8, is_pressed as u8]) {
+                    return Err("The netplay host disconnected!")
+                }
+                *sent_key = is_pressed;
+            }
+        }
+        Ok(())
+    }
+
+    // Takes the host's new display size, where the next present draws every row
+    pub fn take_resize(&mut self) -> Option<(u32, u32)> {
+        match std::mem::take(&mut self.is_resized) {
+            true => Some(self.size),
+            false => None
+        }
+    }
+
+    pub fn take_beep(&mut self) -> Option<bool> {
+        self.beep.take()
+    }
+
+    // Presents the rows received since the last present
+    pub fn present(&mut self, frontend: &mut impl Frontend) -> Result<(), Chip8Error> {
+        let dirty_rows = std::mem::replace(&mut self.dirty_rows, 0..0);
+        let (width, height) = self.size;
+        frontend.present(&FrameBuffer{pixels: &self.frame_buffer, width, height, dirty_rows})
+    }
+}
+
+// Forwards messages until the connection closes or sends something malformed
+fn read_messages(stream: TcpStream, sender: &Sender<NetplayMessage>) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut header = [0; 5];
+        if reader.read_exact(&mut header).is_err() { return }
+        let length = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if length > MAX_PAYLOAD_SIZE { return }
+        let mut payload = vec![0; length];
+        if reader.read_exact(&mut payload).is_err() { return }
+
+        let message = match (header[0], payload.as_slice()) {
+            (MESSAGE_KEY, &[key, is_pressed]) if key < 16 => NetplayMessage::Key(key as usize, is_pressed != 0),
+            (MESSAGE_ROWS, [width_low, width_high, height_low, height_high, row_low, row_high, pixels @ ..]) => NetplayMessage::Rows{
+                width: u16::from_le_bytes([*width_low, *width_high]) as u32,
+                height: u16::from_le_bytes([*height_low, *height_high]) as u32,
+                first_row: u16::from_le_bytes([*row_low, *row_high]) as u32,
+                pixels: pixels.to_vec()
+            },
+            (MESSAGE_BEEP, &[is_on]) => NetplayMessage::Beep(is_on != 0),
+            _ => return
+        };
+        if sender.send(message).is_err() { return }
+    }
+}
+
+// Writes one message, returning false once the connection is closed
+fn write_message(stream: &mut TcpStream, kind: u8, payload: &[u8]) -> bool {
+    let mut message = vec![kind];
+    message.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    message.extend_from_slice(payload);
+    stream.write_all(&message).is_ok()
+}
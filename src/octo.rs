@@ -0,0 +1,569 @@
+use std::collections::HashMap;
+use crate::{chip8::Profile, disasm::OPCODE_FORMS, error::Chip8Error, symbols::Symbols};
+
+// Assembles Octo source, the language of the Octo IDE, into a rom loaded at the start address
+// Covers Octo's statements, if and loop blocks, labels, :const, :alias, :unpack, :next, :org, and :byte,
+// but not macros, :calc, or strings
+// Returns the rom with its labels as symbols
+pub fn assemble_octo(source: &str, profile: Profile, start: u16) -> Result<(Vec<u8>, Symbols), Chip8Error> {
+    // Splits the source into words by their line numbers, where comments start with #
+    let tokens = source.lines().enumerate().flat_map(|(line_index, line)| {
+        line.split('#').next().unwrap_or("").split_whitespace().map(move |word| (word, line_index + 1))
+    }).collect();
+
+    let mut octo = Octo{tokens, position: 0, line: 1, profile, start, rom: vec![0x10, 0x00], is_main_jump: true,
+        labels: HashMap::new(), symbols: Symbols::default(), constants: HashMap::new(), aliases: HashMap::new(), fixups: Vec::new(),
+        blocks: Vec::new()};
+    octo.assemble().map_err(|message| Chip8Error::Assembly{line: octo.line, message})?;
+    Ok((octo.rom, octo.symbols))
+}
+
+// Addresses written before their labels were defined, which are filled in at the end
+enum FixupKind {
+    Address,    // The low 12 bits of an instruction
+    Long,       // The 16 bit word after XO-CHIP's F000
+    Unpack(u8)  // The bytes of the two loads from :unpack, after the nibble
+}
+
+struct Fixup<'a> {
+    offset: usize,
+    label: &'a str,
+    kind: FixupKind,
+    line: usize
+}
+
+// Open blocks, by the offsets of their jumps that wait for the block's end
+enum Block {
+    If{jump: usize},
+    Else{jump: usize},
+    Loop{address: u16, breaks: Vec<usize>} // Jumps out of the loop from while
+}
+
+// A comparison's right side
+#[derive(Clone, Copy)]
+enum Operand {
+    Register(u8),
+    Byte(u8)
+}
+
+struct Octo<'a> {
+    tokens: Vec<(&'a str, usize)>,
+    position: usize,
+    line: usize, // Line of the last word read, which errors report
+    profile: Profile,
+    start: u16,
+    rom: Vec<u8>,
+    is_main_jump: bool, // The rom starts with a jump to main, unless main is first
+    labels: HashMap<&'a str, u16>,
+    symbols: Symbols, // The labels in the order they were defined
+    constants: HashMap<&'a str, i32>,
+    aliases: HashMap<&'a str, u8>,
+    fixups: Vec<Fixup<'a>>,
+    blocks: Vec<Block>
+}
+
+impl<'a> Octo<'a> {
+    fn assemble(&mut self) -> Result<(), &'static str> {
+        while self.position < self.tokens.len() {
+            self.statement()?;
+        }
+        if !self.blocks.is_empty() { return Err("An if or loop isn't closed!") }
+
+        // Fills in the addresses of labels defined later
+        for fixup in std::mem::take(&mut self.fixups) {
+            self.line = fixup.line;
+            let Some(&address) = self.labels.get(fixup.label) else { return Err("Unknown label!") };
+            let [high, low] = address.to_be_bytes();
+            match fixup.kind {
+                FixupKind::Address if address > 0xFFF => return Err("The label's address is out of range!"),
+                FixupKind::Address => {
+                    self.rom[fixup.offset] |= high;
+                    self.rom[fixup.offset + 1] = low;
+                }
+                FixupKind::Long => self.rom[fixup.offset..fixup.offset + 2].copy_from_slice(&[high, low]),
+                FixupKind::Unpack(_) if address > 0xFFF => return Err("The label's address is out of range!"),
+                FixupKind::Unpack(nibble) => {
+                    self.rom[fixup.offset + 1] = nibble << 4 | high;
+                    self.rom[fixup.offset + 3] = low;
+                }
+            }
+        }
+
+        // Execution starts at main
+        let Some(&main) = self.labels.get("main") else { return Err("The program has no main label!") };
+        if self.is_main_jump { self.rom[..2].copy_from_slice(&(0x1000 | main).to_be_bytes()); }
+        Ok(())
+    }
+
+    fn statement(&mut self) -> Result<(), &'static str> {
+        let word = self.next()?;
+        match word {
+            ":" => {
+                let name = self.name()?;
+                // A main at the very start runs without the jump to it
+                if name == "main" && self.is_main_jump && self.rom.len() == 2 && self.labels.is_empty() {
+                    self.rom.clear();
+                    self.is_main_jump = false;
+                }
+                self.define(name, self.here())?;
+            }
+            ":const" => {
+                let name = self.name()?;
+                let value = self.value()?;
+                self.constants.insert(name, value);
+            }
+            ":alias" => {
+                let name = self.name()?;
+                let register = self.register()?;
+                self.aliases.insert(name, register);
+            }
+            ":unpack" => {
+                let nibble = self.nibble()?;
+                let address = self.address(FixupKind::Unpack(nibble), self.rom.len())?;
+                self.emit(0x6000 | (nibble as u16) << 4 | address >> 8)?;
+                self.emit(0x6100 | address & 0xFF)?;
+            }
+            ":next" => {
+                let name = self.name()?;
+                self.define(name, self.here() + 1)?;
+            }
+            ":org" => {
+                let address = self.value()?;
+                if address < self.here() as i32 || address > 0xFFFF { return Err(":org can't move back or past 0xFFFF!") }
+                self.rom.resize(self.rom.len() + (address - self.here() as i32) as usize, 0);
+            }
+            ":byte" => {
+                let byte = self.byte()?;
+                self.rom.push(byte);
+            }
+            ":breakpoint" => { self.next()?; }
+            ":monitor" => {
+                self.next()?;
+                self.next()?;
+            }
+            ":macro" | ":calc" | ":stringmode" | ":call" | ":assert" | ":pointer" | "{" =>
+                return Err("Octo macros and calculations aren't supported!"),
+
+            ";" | "return" => self.emit(0x00EE)?,
+            "clear" => self.emit(0x00E0)?,
+            "hires" => self.emit(0x00FF)?,
+            "lores" => self.emit(0x00FE)?,
+            "scroll-down" => {
+                let rows = self.nibble()?;
+                self.emit(0x00C0 | rows as u16)?;
+            }
+            "scroll-up" => {
+                let rows = self.nibble()?;
+                self.emit(0x00D0 | rows as u16)?;
+            }
+            "scroll-right" => self.emit(0x00FB)?,
+            "scroll-left" => self.emit(0x00FC)?,
+            "exit" => self.emit(0x00FD)?,
+            "audio" => self.emit(0xF002)?,
+            "plane" => {
+                let planes = self.nibble()?;
+                self.emit(0xF001 | (planes as u16) << 8)?;
+            }
+            "jump" => {
+                let address = self.address(FixupKind::Address, self.rom.len())?;
+                self.emit(0x1000 | address)?;
+            }
+            "jump0" => {
+                let address = self.address(FixupKind::Address, self.rom.len())?;
+                self.emit(0xB000 | address)?;
+            }
+            "native" => {
+                let address = self.address(FixupKind::Address, self.rom.len())?;
+                self.emit(address)?;
+            }
+            "sprite" => {
+                let x = self.register()?;
+                let y = self.register()?;
+                let rows = self.nibble()?;
+                self.emit(0xD000 | (x as u16) << 8 | (y as u16) << 4 | rows as u16)?;
+            }
+            "bcd" | "saveflags" | "loadflags" => {
+                let x = self.register()?;
+                self.emit(match word {
+                    "bcd" => 0xF033,
+                    "saveflags" => 0xF075,
+                    _ => 0xF085
+                } | (x as u16) << 8)?;
+            }
+            "save" | "load" => {
+                let x = self.register()?;
+                let is_save = word == "save";
+                match self.peek() == Some("-") {
+                    true => {
+                        self.next()?;
+                        let y = self.register()?;
+                        self.emit(match is_save { true => 0x5002, false => 0x5003 } | (x as u16) << 8 | (y as u16) << 4)?;
+                    }
+                    false => self.emit(match is_save { true => 0xF055, false => 0xF065 } | (x as u16) << 8)?
+                }
+            }
+            "delay" | "buzzer" | "pitch" => {
+                self.expect(":=")?;
+                let x = self.register()?;
+                self.emit(match word {
+                    "delay" => 0xF015,
+                    "buzzer" => 0xF018,
+                    _ => 0xF03A
+                } | (x as u16) << 8)?;
+            }
+            "i" => self.index_statement()?,
+
+            // Blocks jump over their bodies, where "then" guards only the next instruction with a skip
+            "if" => {
+                let skip = self.condition()?;
+                match self.next()? {
+                    "then" => {
+                        self.emit(skip)?;
+                        let length = self.rom.len();
+                        self.statement()?;
+                        if self.rom.len() != length + 2 { return Err("\"then\" must be followed by a single instruction!") }
+                    }
+                    "begin" => {
+                        self.emit(inverse_skip(skip))?;
+                        self.blocks.push(Block::If{jump: self.rom.len()});
+                        self.emit(0x1000)?;
+                    }
+                    _ => return Err("Expected \"then\" or \"begin\" after the condition!")
+                }
+            }
+            "else" => {
+                let Some(Block::If{jump}) = self.blocks.pop() else { return Err("\"else\" isn't inside an if block!") };
+                self.blocks.push(Block::Else{jump: self.rom.len()});
+                self.emit(0x1000)?;
+                self.patch(jump, self.here())?;
+            }
+            "end" => match self.blocks.pop() {
+                Some(Block::If{jump} | Block::Else{jump}) => self.patch(jump, self.here())?,
+                _ => return Err("\"end\" isn't closing an if block!")
+            }
+            "loop" => self.blocks.push(Block::Loop{address: self.here(), breaks: Vec::new()}),
+            "while" => {
+                let skip = self.condition()?;
+                self.emit(inverse_skip(skip))?;
+                let jump = self.rom.len();
+                self.emit(0x1000)?;
+                match self.blocks.iter_mut().rev().find(|block| matches!(block, Block::Loop{..})) {
+                    Some(Block::Loop{breaks, ..}) => breaks.push(jump),
+                    _ => return Err("\"while\" isn't inside a loop!")
+                }
+            }
+            "again" => {
+                let Some(Block::Loop{address, breaks}) = self.blocks.pop() else { return Err("\"again\" isn't closing a loop!") };
+                self.emit(0x1000 | address)?;
+                for jump in breaks {
+                    self.patch(jump, self.here())?;
+                }
+            }
+
+            _ if self.to_register(word).is_some() => self.register_statement(word)?,
+
+            // Numbers and constants are data, and any other name calls the label
+            _ => match self.to_value(word) {
+                Some(_) => {
+                    self.position -= 1;
+                    let byte = self.byte()?;
+                    self.rom.push(byte);
+                }
+                None => {
+                    self.position -= 1;
+                    let address = self.address(FixupKind::Address, self.rom.len())?;
+                    self.emit(0x2000 | address)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Statements starting with i, which load or add to the index register
+    fn index_statement(&mut self) -> Result<(), &'static str> {
+        match self.next()? {
+            "+=" => {
+                let x = self.register()?;
+                self.emit(0xF01E | (x as u16) << 8)
+            }
+            ":=" => match self.peek() {
+                Some("hex") | Some("bighex") => {
+                    let is_big = self.next()? == "bighex";
+                    let x = self.register()?;
+                    self.emit(match is_big { true => 0xF030, false => 0xF029 } | (x as u16) << 8)
+                }
+                Some("long") => {
+                    self.next()?;
+                    self.emit(0xF000)?;
+                    let address = self.address(FixupKind::Long, self.rom.len())?;
+                    self.rom.extend(address.to_be_bytes());
+                    Ok(())
+                }
+                _ => {
+                    let address = self.address(FixupKind::Address, self.rom.len())?;
+                    self.emit(0xA000 | address)
+                }
+            }
+            _ => Err("Expected := or += after i!")
+        }
+    }
+
+    // Statements starting with a register, which load it or do arithmetic on it
+    fn register_statement(&mut self, word: &str) -> Result<(), &'static str> {
+        let x = (self.to_register(word).unwrap_or(0) as u16) << 8;
+        let operator = self.next()?;
+        let operand = match (operator, self.peek()) {
+            (":=", Some("random")) => {
+                self.next()?;
+                let mask = self.byte()?;
+                return self.emit(0xC000 | x | mask as u16)
+            }
+            (":=", Some("key")) => {
+                self.next()?;
+                return self.emit(0xF00A | x)
+            }
+            (":=", Some("delay")) => {
+                self.next()?;
+                return self.emit(0xF007 | x)
+            }
+            _ => self.operand()?
+        };
+
+        let opcode = match (operator, operand) {
+            (":=", Operand::Register(y)) => 0x8000 | (y as u16) << 4,
+            (":=", Operand::Byte(kk)) => 0x6000 | kk as u16,
+            ("+=", Operand::Register(y)) => 0x8004 | (y as u16) << 4,
+            ("+=", Operand::Byte(kk)) => 0x7000 | kk as u16,
+            ("-=", Operand::Register(y)) => 0x8005 | (y as u16) << 4,
+            ("-=", Operand::Byte(kk)) => 0x7000 | kk.wrapping_neg() as u16,
+            ("=-", Operand::Register(y)) => 0x8007 | (y as u16) << 4,
+            ("|=", Operand::Register(y)) => 0x8001 | (y as u16) << 4,
+            ("&=", Operand::Register(y)) => 0x8002 | (y as u16) << 4,
+            ("^=", Operand::Register(y)) => 0x8003 | (y as u16) << 4,
+            (">>=", Operand::Register(y)) => 0x8006 | (y as u16) << 4,
+            ("<<=", Operand::Register(y)) => 0x800E | (y as u16) << 4,
+            _ => return Err("Unknown operator for the operand!")
+        };
+        self.emit(opcode | x)
+    }
+
+    // Reads a condition, emitting any comparison it needs, and returns the skip that passes over the next instruction when it's false
+    fn condition(&mut self) -> Result<u16, &'static str> {
+        let x = self.register()?;
+        let operator = self.next()?;
+        if operator == "key" { return Ok(0xE0A1 | (x as u16) << 8) }
+        if operator == "-key" { return Ok(0xE09E | (x as u16) << 8) }
+
+        let operand = self.operand()?;
+        let (left, right, is_true_when_set) = match (operator, operand) {
+            ("==", Operand::Register(y)) => return Ok(0x9000 | (x as u16) << 8 | (y as u16) << 4),
+            ("==", Operand::Byte(kk)) => return Ok(0x4000 | (x as u16) << 8 | kk as u16),
+            ("!=", Operand::Register(y)) => return Ok(0x5000 | (x as u16) << 8 | (y as u16) << 4),
+            ("!=", Operand::Byte(kk)) => return Ok(0x3000 | (x as u16) << 8 | kk as u16),
+
+            // Compares with "vf := left  vf -= right", which leaves vf at 1 when left >= right
+            // A byte can only be on the left, so x < kk is x <= kk - 1 and x >= kk is x > kk - 1
+            ("<" | ">=", Operand::Byte(0)) => return Err("Comparing with 0 is always true or false!"),
+            ("<", Operand::Byte(kk)) => (Operand::Byte(kk - 1), x, true),
+            (">=", Operand::Byte(kk)) => (Operand::Byte(kk - 1), x, false),
+            ("<", Operand::Register(y)) => (Operand::Register(x), y, false),
+            (">=", Operand::Register(y)) => (Operand::Register(x), y, true),
+            (">", _) => (operand, x, false),
+            ("<=", _) => (operand, x, true),
+            _ => return Err("Unknown comparison!")
+        };
+        self.emit(match left {
+            Operand::Register(y) => 0x8F00 | (y as u16) << 4,
+            Operand::Byte(kk) => 0x6F00 | kk as u16
+        })?;
+        self.emit(0x8F05 | (right as u16) << 4)?;
+        Ok(match is_true_when_set {
+            true => 0x3F00,
+            false => 0x4F00
+        })
+    }
+
+    // Appends an instruction the profile supports
+    fn emit(&mut self, opcode: u16) -> Result<(), &'static str> {
+        if !is_supported(opcode, self.profile) { return Err("The instruction isn't supported by the profile!") }
+        self.rom.extend(opcode.to_be_bytes());
+        Ok(())
+    }
+
+    // Points the jump at the offset to the address
+    fn patch(&mut self, offset: usize, address: u16) -> Result<(), &'static str> {
+        if address > 0xFFF { return Err("The block ends past 0xFFF!") }
+        self.rom[offset..offset + 2].copy_from_slice(&(0x1000 | address).to_be_bytes());
+        Ok(())
+    }
+
+    fn here(&self) -> u16 {
+        self.start.wrapping_add(self.rom.len() as u16)
+    }
+
+    fn define(&mut self, name: &'a str, address: u16) -> Result<(), &'static str> {
+        match self.labels.insert(name, address) {
+            Some(_) => Err("Label is defined more than once!"),
+            None => {
+                self.symbols.insert(address, name);
+                Ok(())
+            }
+        }
+    }
+
+    fn next(&mut self) -> Result<&'a str, &'static str> {
+        let Some(&(word, line)) = self.tokens.get(self.position) else { return Err("The source ends in the middle of a statement!") };
+        self.position += 1;
+        self.line = line;
+        Ok(word)
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|&(word, _)| word)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), &'static str> {
+        match self.next()? == expected {
+            true => Ok(()),
+            false => Err("Unexpected word in the statement!")
+        }
+    }
+
+    // A name for a label, constant, or alias, which can't be a number or register
+    fn name(&mut self) -> Result<&'a str, &'static str> {
+        let word = self.next()?;
+        match parse_number(word).is_none() && self.to_register(word).is_none() {
+            true => Ok(word),
+            false => Err("Invalid name!")
+        }
+    }
+
+    fn register(&mut self) -> Result<u8, &'static str> {
+        let word = self.next()?;
+        self.to_register(word).ok_or("Expected a register!")
+    }
+
+    fn to_register(&self, word: &str) -> Option<u8> {
+        if let Some(&register) = self.aliases.get(word) { return Some(register) }
+        let digit = word.strip_prefix(['v', 'V'])?;
+        match digit.len() {
+            1 => u8::from_str_radix(digit, 16).ok(),
+            _ => None
+        }
+    }
+
+    fn value(&mut self) -> Result<i32, &'static str> {
+        let word = self.next()?;
+        self.to_value(word).ok_or("Expected a number!")
+    }
+
+    fn to_value(&self, word: &str) -> Option<i32> {
+        parse_number(word).or_else(|| self.constants.get(word).copied())
+    }
+
+    // Bytes may be negative, like -1 for 0xFF
+    fn byte(&mut self) -> Result<u8, &'static str> {
+        match self.value()? {
+            value @ -128..=255 => Ok(value as u8),
+            _ => Err("Byte is out of range!")
+        }
+    }
+
+    fn nibble(&mut self) -> Result<u8, &'static str> {
+        match self.value()? {
+            value @ 0..=15 => Ok(value as u8),
+            _ => Err("Nibble is out of range!")
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, &'static str> {
+        match self.peek().and_then(|word| self.to_register(word)) {
+            Some(register) => {
+                self.next()?;
+                Ok(Operand::Register(register))
+            }
+            None => Ok(Operand::Byte(self.byte()?))
+        }
+    }
+
+    // Reads an address for the instruction at the offset, returning 0 for labels filled in at the end
+    fn address(&mut self, kind: FixupKind, offset: usize) -> Result<u16, &'static str> {
+        let word = self.next()?;
+        let maximum = match kind {
+            FixupKind::Long => 0xFFFF,
+            _ => 0xFFF
+        };
+        if let Some(value) = self.to_value(word) {
+            return match value {
+                0.. if value <= maximum => Ok(value as u16),
+                _ => Err("Address is out of range!")
+            }
+        }
+        if self.to_register(word).is_some() { return Err("Expected an address!") }
+        self.fixups.push(Fixup{offset, label: word, kind, line: self.line});
+        Ok(0)
+    }
+}
+
+// The skip for the opposite condition, which jumps over a block when the condition is false
+fn inverse_skip(skip: u16) -> u16 {
+    match skip & 0xF0FF {
+        0xE0A1 => skip & 0xFF00 | 0x9E,
+        0xE09E => skip & 0xFF00 | 0xA1,
+        _ => match skip & 0xF000 {
+            0x3000 => skip & 0x0FFF | 0x4000,
+            0x4000 => skip & 0x0FFF | 0x3000,
+            0x5000 => skip & 0x0FFF | 0x9000,
+            _ => skip & 0x0FFF | 0x5000
+        }
+    }
+}
+
+// The first form an opcode matches is its instruction, which other forms with the same bits can support instead
+fn is_supported(opcode: u16, profile: Profile) -> bool {
+    let Some(first) = OPCODE_FORMS.iter().find(|form| opcode & form.mask == form.opcode) else { return false };
+    OPCODE_FORMS.iter().any(|form| form.opcode == first.opcode && form.mask == first.mask && form.set.is_supported(profile))
+}
+
+// Reads decimal, with an optional minus, or hexadecimal and binary with 0x and 0b
+fn parse_number(word: &str) -> Option<i32> {
+    let (is_negative, digits) = match word.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, word)
+    };
+    let lowercase = digits.to_ascii_lowercase();
+    let value = match (lowercase.strip_prefix("0x"), lowercase.strip_prefix("0b")) {
+        (Some(digits), _) => i32::from_str_radix(digits, 16).ok(),
+        (_, Some(digits)) => i32::from_str_radix(digits, 2).ok(),
+        _ => lowercase.parse().ok().filter(|_| lowercase.starts_with(|character: char| character.is_ascii_digit()))
+    }?;
+    match is_negative {
+        true => Some(-value),
+        false => Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assembles_statements_and_blocks() {
+        let source = "# Counts to 5\n: main\n  v0 := 0\n  loop\n    v0 += 1\n    if v0 == 5 then i := data\n  again\n: data 0xF0";
+        let (rom, symbols) = assemble_octo(source, Profile::Chip8, 0x200).unwrap();
+        assert_eq!(rom, [0x60, 0x00, 0x70, 0x01, 0x40, 0x05, 0xA2, 0x0A, 0x12, 0x02, 0xF0]);
+        assert_eq!(symbols.address("data"), Some(0x20A));
+    }
+
+    #[test]
+    fn jumps_to_main_when_it_comes_later() {
+        let (rom, _) = assemble_octo(": sub return\n: main sub", Profile::Chip8, 0x200).unwrap();
+        assert_eq!(rom, [0x12, 0x04, 0x00, 0xEE, 0x22, 0x02]);
+    }
+
+    #[test]
+    fn reports_errors_by_line() {
+        let result = assemble_octo(": main\n  v0 := missing", Profile::Chip8, 0x200);
+        assert!(matches!(result, Err(Chip8Error::Assembly{line: 2, ..})));
+    }
+}
@@ -0,0 +1,219 @@
+use std::time::{Duration, Instant};
+use sdl3::{keyboard::Keycode, pixels::Color, render::{BlendMode, Canvas, FRect}, video::Window};
+
+// Font pixels are a quarter of a chip8 pixel, so a 64x32 screen fits 16 characters per chip8 row of width
+pub const FONT_PIXEL: f32 = 0.25;
+pub const GLYPH_WIDTH: f32 = 4.0 * FONT_PIXEL; // Includes one pixel of spacing
+pub const LINE_HEIGHT: f32 = 6.0 * FONT_PIXEL;
+const MESSAGE_DURATION: Duration = Duration::from_secs(2);
+
+// Shows transient status messages in the bottom left corner of the screen
+pub struct Osd {
+    message: Option<(String, Instant)>
+}
+
+impl Osd {
+    pub fn new() -> Osd {
+        Osd{message: None}
+    }
+
+    // Replaces the current message, which disappears after two seconds
+    pub fn show(&mut self, message: &str) {
+        self.message = Some((String::from(message), Instant::now()));
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>, screen_height: f32) -> Result<(), &'static str> {
+        let Some((message, shown_time)) = &self.message else { return Ok(()) };
+        if shown_time.elapsed() >= MESSAGE_DURATION {
+            self.message = None;
+            return Ok(())
+        }
+        draw_text(canvas, message, 0.0, screen_height - LINE_HEIGHT - FONT_PIXEL)
+    }
+}
+
+// Toggleable counters of frames and instructions per second, and whether frames are paced by vsync or sleeping
+pub struct SpeedOverlay {
+    pub is_visible: bool,
+    frame_count: u32,
+    instruction_count: u64,
+    last_update: Instant,
+    text: String
+}
+
+impl SpeedOverlay {
+    pub fn new() -> SpeedOverlay {
+        SpeedOverlay{is_visible: false, frame_count: 0, instruction_count: 0, last_update: Instant::now(), text: String::new()}
+    }
+
+    // Counts a presented frame and refreshes the text once a second
+    pub fn update(&mut self, instruction_count: u64, is_vsync: bool) {
+        self.frame_count += 1;
+        let elapsed = self.last_update.elapsed();
+        if elapsed < Duration::from_secs(1) { return }
+
+        let seconds = elapsed.as_secs_f64();
+        let frames_per_second = self.frame_count as f64 / seconds;
+        let instructions_per_second = instruction_count.saturating_sub(self.instruction_count) as f64 / seconds;
+        let pacing = match is_vsync { true => "vsync", false => "sleep" };
+        self.text = format!("{frames_per_second:.0} fps\n{instructions_per_second:.0} ips\n{pacing}");
+
+        self.frame_count = 0;
+        self.instruction_count = instruction_count;
+        self.last_update = Instant::now();
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) -> Result<(), &'static str> {
+        match self.is_visible && !self.text.is_empty() {
+            true => draw_text(canvas, &self.text, 0.0, 0.0),
+            false => Ok(())
+        }
+    }
+}
+
+// Chip8 keys in the layout of the COSMAC VIP's hex keypad
+const KEYPAD_ROWS: [[usize; 4]; 4] = [[0x1, 0x2, 0x3, 0xC], [0x4, 0x5, 0x6, 0xD], [0x7, 0x8, 0x9, 0xE], [0xA, 0x0, 0xB, 0xF]];
+
+// Toggleable 4x4 keypad in the top right corner, labelling each chip8 key with its host key and highlighting held keys
+pub struct KeypadOverlay {
+    pub is_visible: bool
+}
+
+impl KeypadOverlay {
+    pub fn new() -> KeypadOverlay {
+        KeypadOverlay{is_visible: false}
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, keys: &[bool; 16], key_map: &[(Keycode, usize)], screen_width: f32)
+    -> Result<(), &'static str> {
+        if !self.is_visible { return Ok(()) }
+
+        // Lists every host key bound to a chip8 key, padding the cells to the longest list so the columns line up
+        let labels: [String; 16] = std::array::from_fn(|chip8_key| {
+            let names: Vec<String> = key_map.iter().filter(|&&(_, key)| key == chip8_key).map(|(keycode, _)| keycode.name()).collect();
+            names.join("/")
+        });
+        let cell_columns = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0) + 3;
+        let cell_width = cell_columns as f32 * GLYPH_WIDTH;
+        let x = screen_width - cell_width * 4.0 - FONT_PIXEL;
+
+        let text: Vec<String> = KEYPAD_ROWS.iter()
+            .map(|row| row.iter().map(|&key| format!("{key:X} {:<width$} ", labels[key], width = cell_columns - 3)).collect())
+            .collect();
+        draw_text(canvas, &text.join("\n"), x, 0.0)?;
+
+        // Tints the cells of held keys
+        let held: Vec<FRect> = KEYPAD_ROWS.iter().enumerate()
+            .flat_map(|(row, keys_in_row)| keys_in_row.iter().enumerate().map(move |(column, &key)| (row, column, key)))
+            .filter(|&(_, _, key)| keys[key])
+            .map(|(row, column, _)| FRect::new(x + column as f32 * cell_width, row as f32 * LINE_HEIGHT, cell_width, LINE_HEIGHT + FONT_PIXEL))
+            .collect();
+        if held.is_empty() { return Ok(()) }
+
+        let draw_color = canvas.draw_color();
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(255, 160, 0, 120));
+        let result = canvas.fill_rects(&held);
+        canvas.set_draw_color(draw_color);
+        canvas.set_blend_mode(BlendMode::None);
+        match result {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Failed to draw the keypad overlay!")
+        }
+    }
+}
+
+// Draws lines of text over a translucent box, in the canvas's logical coordinates
+pub fn draw_text(canvas: &mut Canvas<Window>, text: &str, x: f32, y: f32) -> Result<(), &'static str> {
+    let lines: Vec<&str> = text.lines().collect();
+    let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let backing = FRect::new(x, y, columns as f32 * GLYPH_WIDTH + FONT_PIXEL, lines.len() as f32 * LINE_HEIGHT + FONT_PIXEL);
+
+    // Converts each lit font pixel into a rectangle
+    let mut pixels = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        for (column, character) in line.chars().enumerate() {
+            for (glyph_y, bits) in glyph(character.to_ascii_uppercase()).iter().enumerate() {
+                for glyph_x in 0..3 {
+                    if bits & (0b100 >> glyph_x) == 0 { continue }
+                    let pixel_x = x + FONT_PIXEL + column as f32 * GLYPH_WIDTH + glyph_x as f32 * FONT_PIXEL;
+                    let pixel_y = y + FONT_PIXEL + row as f32 * LINE_HEIGHT + glyph_y as f32 * FONT_PIXEL;
+                    pixels.push(FRect::new(pixel_x, pixel_y, FONT_PIXEL, FONT_PIXEL));
+                }
+            }
+        }
+    }
+
+    let draw_color = canvas.draw_color();
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+    let backing_result = canvas.fill_rect(backing);
+    canvas.set_draw_color(Color::RGBA(255, 255, 255, 255));
+    let text_result = canvas.fill_rects(&pixels);
+
+    canvas.set_draw_color(draw_color);
+    canvas.set_blend_mode(BlendMode::None);
+    match backing_result.and(text_result) {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Failed to draw on screen text!")
+    }
+}
+
+// Rows of a 3x5 font from top to bottom, where unknown characters are question marks
+fn glyph(character: char) -> [u8; 5] {
+    match character {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '[' => [0b110, 0b100, 0b100, 0b100, 0b110],
+        ']' => [0b011, 0b001, 0b001, 0b001, 0b011],
+        '*' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        _ => [0b111, 0b001, 0b010, 0b000, 0b010]
+    }
+}
@@ -0,0 +1,53 @@
+// Nanoseconds for an erased pixel to fade to half its brightness
+const HALF_LIFE_NANOS: f32 = 20000000.0;
+
+// Fades erased pixels toward the background like a slow CRT phosphor, which hides XOR drawing flicker
+pub struct PhosphorFilter {
+    background_color: u32,
+    intensities: Vec<f32>,
+    colors: Vec<u32>, // Last lit color of each pixel
+    output: Vec<u32>
+}
+
+impl PhosphorFilter {
+    pub fn new(background_color: u32) -> PhosphorFilter {
+        PhosphorFilter{background_color, intensities: Vec::new(), colors: Vec::new(), output: Vec::new()}
+    }
+
+    // Lights pixels at full intensity and decays the rest by the time since the last frame
+    pub fn apply(&mut self, frame_buffer: &[u32], frame_nanos: u64) -> &[u32] {
+        // Starts over when the display changes resolution
+        if self.output.len() != frame_buffer.len() {
+            self.intensities = vec![0.0; frame_buffer.len()];
+            self.colors = vec![self.background_color; frame_buffer.len()];
+            self.output = vec![self.background_color; frame_buffer.len()];
+        }
+
+        let decay = 0.5f32.powf(frame_nanos as f32 / HALF_LIFE_NANOS);
+        for (i, &pixel) in frame_buffer.iter().enumerate() {
+            if pixel != self.background_color {
+                self.intensities[i] = 1.0;
+                self.colors[i] = pixel;
+                self.output[i] = pixel;
+                continue
+            }
+
+            // Snaps to the background once the fade is no longer visible
+            self.intensities[i] *= decay;
+            if self.intensities[i] < 1.0 / 255.0 { self.intensities[i] = 0.0; }
+            self.output[i] = blend(self.background_color, self.colors[i], self.intensities[i]);
+        }
+        &self.output
+    }
+}
+
+// Mixes each color channel from the background toward the lit color
+fn blend(background: u32, color: u32, intensity: f32) -> u32 {
+    let (background, color) = (background.to_be_bytes(), color.to_be_bytes());
+    let mut blended = [0xFF; 4];
+    for channel in 1..4 {
+        let difference = color[channel] as f32 - background[channel] as f32;
+        blended[channel] = (background[channel] as f32 + difference * intensity).round() as u8;
+    }
+    u32::from_be_bytes(blended)
+}
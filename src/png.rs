@@ -0,0 +1,123 @@
+// Encodes ARGB8888 pixels as an uncompressed RGB png
+pub fn encode_png(pixels: &[u32], width: u32, height: u32) -> Vec<u8> {
+    // Prefixes each row of RGB bytes with the "none" filter type
+    let mut scanlines = Vec::with_capacity(pixels.len() * 3 + height as usize);
+    for row in pixels.chunks(width as usize) {
+        scanlines.push(0);
+        for pixel in row {
+            scanlines.extend_from_slice(&pixel.to_be_bytes()[1..]);
+        }
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8 bit RGB, deflate, no interlacing
+
+    let mut png = b"\x89PNG\r\n\x1A\n".to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// Encodes ARGB8888 frames and their delays in milliseconds as an animated png that loops forever
+// Frames share a palette of at most 256 colors, which chip8 screens never exceed
+pub fn encode_apng(frames: &[(&[u32], u16)], width: u32, height: u32) -> Vec<u8> {
+    let mut palette: Vec<u32> = Vec::new();
+    for &pixel in frames.iter().flat_map(|(pixels, _)| pixels.iter()) {
+        if !palette.contains(&pixel) && palette.len() < 256 { palette.push(pixel); }
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 3, 0, 0, 0]); // 8 bit palette indices, deflate, no interlacing
+
+    let mut animation_control = (frames.len() as u32).to_be_bytes().to_vec();
+    animation_control.extend_from_slice(&0u32.to_be_bytes());
+
+    let mut png = b"\x89PNG\r\n\x1A\n".to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"acTL", &animation_control);
+    write_chunk(&mut png, b"PLTE", &palette.iter().flat_map(|color| color.to_be_bytes()[1..].to_vec()).collect::<Vec<u8>>());
+
+    // Frame controls and frame data share one sequence number
+    let mut sequence = 0u32;
+    for (i, &(pixels, delay)) in frames.iter().enumerate() {
+        let mut frame_control = sequence.to_be_bytes().to_vec();
+        frame_control.extend_from_slice(&width.to_be_bytes());
+        frame_control.extend_from_slice(&height.to_be_bytes());
+        frame_control.extend_from_slice(&[0; 8]); // X and y offsets
+        frame_control.extend_from_slice(&delay.to_be_bytes());
+        frame_control.extend_from_slice(&1000u16.to_be_bytes());
+        frame_control.extend_from_slice(&[0, 0]); // No disposal, and replaces the previous frame
+        write_chunk(&mut png, b"fcTL", &frame_control);
+        sequence += 1;
+
+        // Maps each pixel to its palette index, where any colors past the palette's limit use the first color
+        let mut scanlines = Vec::with_capacity(pixels.len() + height as usize);
+        for row in pixels.chunks(width as usize) {
+            scanlines.push(0);
+            scanlines.extend(row.iter().map(|pixel| palette.iter().position(|color| color == pixel).unwrap_or(0) as u8));
+        }
+
+        // The first frame is the default image and the rest are frame data chunks
+        match i {
+            0 => write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines)),
+            _ => {
+                let mut frame_data = sequence.to_be_bytes().to_vec();
+                frame_data.extend_from_slice(&zlib_stored(&scanlines));
+                write_chunk(&mut png, b"fdAT", &frame_data);
+                sequence += 1;
+            }
+        }
+    }
+
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// Writes the length, type, data, and crc of a chunk
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32(&[chunk_type, data]).to_be_bytes());
+}
+
+// Wraps data in a zlib stream of uncompressed deflate blocks, which are small enough for chip8 screens
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() { stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]); }
+    while let Some(block) = blocks.next() {
+        let length = block.len() as u16;
+        stream.push(blocks.peek().is_none() as u8);
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    // Adler-32 checksum of the uncompressed data
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    stream.extend_from_slice(&(b << 16 | a).to_be_bytes());
+    stream
+}
+
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for &byte in parts.iter().flat_map(|part| part.iter()) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => crc >> 1 ^ 0xEDB88320,
+                _ => crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,91 @@
+use chip8_emulator::{disassemble, Chip8};
+
+// Most executed addresses kept in a report, which covers the hot loops of most programs
+const HOTSPOT_COUNT: usize = 64;
+
+// Writes the instruction profile as json, with counts by opcode family and the most executed addresses
+pub fn write_report(chip8: &Chip8, path: &str) -> Result<(), &'static str> {
+    let families = family_counts(chip8);
+    let hotspots = hotspots(chip8, HOTSPOT_COUNT);
+
+    let mut json = format!("{{\n  \"instructions\": {},\n  \"families\": [", chip8.address_counts().iter().sum::<u64>());
+    for (index, (family, mnemonic, count)) in families.iter().enumerate() {
+        let separator = match index { 0 => "", _ => "," };
+        json += &format!("{separator}\n    {{\"family\": \"{family}\", \"mnemonic\": \"{mnemonic}\", \"count\": {count}}}");
+    }
+    json += "\n  ],\n  \"hotspots\": [";
+    for (index, (address, count, instruction)) in hotspots.iter().enumerate() {
+        let separator = match index { 0 => "", _ => "," };
+        json += &format!("{separator}\n    {{\"address\": \"0x{address:03X}\", \"count\": {count}, \"instruction\": \"{instruction}\"}}");
+    }
+    json += "\n  ]\n}\n";
+
+    match std::fs::write(path, json) {
+        Ok(_) => {
+            println!("Saved the instruction profile to {path}");
+            Ok(())
+        }
+        Err(_) => Err("Failed to write the instruction profile!")
+    }
+}
+
+// Prints the busiest opcode families and addresses for the debugger
+pub fn print_report(chip8: &Chip8, count: usize) {
+    let instructions = chip8.address_counts().iter().sum::<u64>();
+    let total = instructions.max(1);
+    println!("{instructions} instructions");
+    for (family, mnemonic, executions) in family_counts(chip8).iter().take(count) {
+        println!("{family} {mnemonic:<5} {executions:>12} {:>5.1}%", *executions as f64 * 100.0 / total as f64);
+    }
+    for (address, executions, instruction) in hotspots(chip8, count) {
+        println!("0x{address:03X} {executions:>12} {:>5.1}%  {instruction}", executions as f64 * 100.0 / total as f64);
+    }
+}
+
+// Sums opcodes into families like 8XY4, most executed first, with the mnemonic they disassemble to
+fn family_counts(chip8: &Chip8) -> Vec<(String, String, u64)> {
+    let mut families: Vec<(String, String, u64)> = Vec::new();
+    for (opcode, &count) in chip8.opcode_counts().iter().enumerate().filter(|&(_, &count)| count > 0) {
+        let family = opcode_family(opcode as u16);
+        match families.iter_mut().find(|(name, _, _)| *name == family) {
+            Some((_, _, total)) => *total += count,
+            None => {
+                let (mnemonic, _) = disassemble(&[(opcode >> 8) as u8, opcode as u8, 0, 0], chip8.profile());
+                let mnemonic = mnemonic.split_whitespace().next().unwrap_or("").to_string();
+                families.push((family, mnemonic, count));
+            }
+        }
+    }
+    families.sort_by_key(|&(_, _, count)| std::cmp::Reverse(count));
+    families
+}
+
+// The most executed addresses with the instruction now in ram there
+fn hotspots(chip8: &Chip8, count: usize) -> Vec<(usize, u64, String)> {
+    let mut addresses: Vec<(usize, u64)> = chip8.address_counts().iter().copied().enumerate().filter(|&(_, count)| count > 0).collect();
+    addresses.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    addresses.truncate(count);
+
+    addresses.into_iter().map(|(address, executions)| {
+        let code = chip8.memory().get(address..).unwrap_or(&[]);
+        (address, executions, disassemble(code, chip8.profile()).0)
+    }).collect()
+}
+
+// Names an opcode by its pattern, where X and Y are registers, N is a nibble, KK is a byte, and NNN is an address
+fn opcode_family(opcode: u16) -> String {
+    let (op0, n, kk) = (opcode >> 12, opcode & 0xF, opcode & 0xFF);
+    match op0 {
+        0x0 => match opcode {
+            0x00C0..=0x00CF => String::from("00CN"),
+            0x00D0..=0x00DF => String::from("00DN"),
+            0x00E0 | 0x00EE | 0x00FB..=0x00FF => format!("{opcode:04X}"),
+            _ => String::from("0NNN")
+        }
+        0x1 | 0x2 | 0xA | 0xB => format!("{op0:X}NNN"),
+        0x3 | 0x4 | 0x6 | 0x7 | 0xC => format!("{op0:X}XKK"),
+        0x5 | 0x8 | 0x9 => format!("{op0:X}XY{n:X}"),
+        0xD => String::from("DXYN"),
+        _ => format!("{op0:X}X{kk:02X}")
+    }
+}
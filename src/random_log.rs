@@ -0,0 +1,44 @@
+use chip8_emulator::{Chip8, RandomDivergence, RandomRoll};
+
+// Writes every CXKK roll as "<Frame> <PC> <Byte> <Result>", where the byte is the roll before the instruction's mask
+pub fn write_log(chip8: &Chip8, path: &str) -> Result<(), &'static str> {
+    let mut text = String::from("# frame pc byte result\n");
+    for roll in chip8.random_log() {
+        text += &format!("{} 0x{:03X} 0x{:02X} 0x{:02X}\n", roll.frame, roll.pc, roll.byte, roll.result);
+    }
+
+    match std::fs::write(path, text) {
+        Ok(_) => {
+            println!("Saved {} random rolls to {path}", chip8.random_log().len());
+            Ok(())
+        }
+        Err(_) => Err("Failed to write the random log!")
+    }
+}
+
+// Reads a log written by write_log, ignoring comments after #
+pub fn read_log(path: &str) -> Result<Vec<RandomRoll>, &'static str> {
+    let text = std::fs::read_to_string(path).map_err(|_| "Failed to read the random log!")?;
+    let hex = |word: &str| u16::from_str_radix(word.strip_prefix("0x").unwrap_or(word), 16).ok();
+    let mut rolls = Vec::new();
+    for line in text.lines() {
+        let words: Vec<&str> = line.split('#').next().unwrap_or("").split_whitespace().collect();
+        match words.as_slice() {
+            [] => (),
+            [frame, pc, byte, result] => {
+                let (Ok(frame), Some(pc), Some(byte), Some(result)) = (frame.parse(), hex(pc), hex(byte), hex(result)) else {
+                    return Err("Invalid roll in the random log!")
+                };
+                rolls.push(RandomRoll{frame, pc, byte: byte as u8, result: result as u8});
+            }
+            _ => return Err("Random log lines need a frame, address, byte, and result!")
+        }
+    }
+    Ok(rolls)
+}
+
+pub fn describe_divergence(divergence: &RandomDivergence) -> String {
+    let expected = divergence.expected;
+    format!("Random rolls diverged on frame {}: CXKK at 0x{:03X} took the roll logged from 0x{:03X} on frame {}",
+        divergence.frame, divergence.pc, expected.pc, expected.frame)
+}
@@ -0,0 +1,79 @@
+#[cfg(feature = "sdl")]
+use sdl3::{render::Canvas, video::Window};
+#[cfg(feature = "sdl")]
+use crate::{osd::draw_text, rom::rom_name};
+use crate::toml::config_path;
+
+// Number of rom paths remembered between runs
+const RECENT_ROM_COUNT: usize = 8;
+
+// Recently opened roms, saved one path per line in the config directory, with an on screen menu to switch between them
+pub struct RecentRoms {
+    paths: Vec<String>,
+    pub is_open: bool,
+    selected: usize
+}
+
+impl RecentRoms {
+    pub fn load() -> RecentRoms {
+        let text = config_path("recent.txt").and_then(|path| std::fs::read_to_string(path).ok()).unwrap_or_default();
+        let paths = text.lines().filter(|line| !line.is_empty()).take(RECENT_ROM_COUNT).map(String::from).collect();
+        RecentRoms{paths, is_open: false, selected: 0}
+    }
+
+    // Moves a rom to the top of the list and saves it, where standard input can't be opened again and the splash rom has no path
+    pub fn add(&mut self, rom_path: &str) {
+        if rom_path == "-" || rom_path.is_empty() { return }
+        let rom_path = match std::fs::canonicalize(rom_path) {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(_) => String::from(rom_path) // Urls
+        };
+
+        self.paths.retain(|path| *path != rom_path);
+        self.paths.insert(0, rom_path);
+        self.paths.truncate(RECENT_ROM_COUNT);
+
+        if let Some(path) = config_path("recent.txt") {
+            if let Some(directory) = path.parent() { let _ = std::fs::create_dir_all(directory); }
+            if std::fs::write(path, self.paths.join("\n") + "\n").is_err() {
+                println!("Failed to save recent roms!");
+            }
+        }
+    }
+
+    // Opens the menu on the rom before the current one, so switching back and forth takes one key press
+    pub fn toggle(&mut self) {
+        self.is_open = !self.is_open;
+        self.selected = 1.min(self.paths.len().saturating_sub(1));
+    }
+
+    // Moves the selection up or down, wrapping around the ends of the list
+    pub fn move_selection(&mut self, is_down: bool) {
+        if self.paths.is_empty() { return }
+        self.selected = match is_down {
+            true => (self.selected + 1) % self.paths.len(),
+            false => (self.selected + self.paths.len() - 1) % self.paths.len()
+        };
+    }
+
+    // Closes the menu and returns the chosen rom path
+    pub fn choose(&mut self) -> Option<String> {
+        self.is_open = false;
+        self.paths.get(self.selected).cloned()
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn draw(&self, canvas: &mut Canvas<Window>) -> Result<(), &'static str> {
+        if !self.is_open { return Ok(()) }
+
+        // Lists file names, since full paths don't fit on the screen
+        let mut text = String::from("Recent roms\n");
+        if self.paths.is_empty() { text += "  None\n"; }
+        for (i, path) in self.paths.iter().enumerate() {
+            let name = rom_name(path);
+            let marker = match i == self.selected { true => "> ", false => "  " };
+            text += &format!("{marker}{name}\n");
+        }
+        draw_text(canvas, &text, 0.0, 0.0)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::png::encode_apng;
+
+// Longest delay an animated png frame holds in milliseconds
+const MAX_FRAME_DELAY: u64 = u16::MAX as u64;
+
+// Captures displayed frames and how long each stayed on screen
+pub struct Recording {
+    frames: Vec<(Vec<u32>, u64)>, // Pixels and nanoseconds on screen
+    width: u32,
+    height: u32
+}
+
+impl Recording {
+    pub fn new(width: u32, height: u32) -> Recording {
+        println!("Started recording");
+        Recording{frames: Vec::new(), width, height}
+    }
+
+    // Credits the time since the last capture to the previous frame, and merges unchanged frames
+    pub fn capture(&mut self, pixels: &[u32], elapsed_nanos: u64) {
+        if let Some((previous, duration)) = self.frames.last_mut() {
+            *duration += elapsed_nanos;
+            if previous.as_slice() == pixels { return }
+        }
+        self.frames.push((pixels.to_vec(), 0));
+    }
+
+    // Encodes the animation to a file, splitting frames longer than a png delay allows
+    pub fn save(self, path: &str) {
+        let mut frames = Vec::new();
+        for (pixels, duration) in &self.frames {
+            let mut delay = (duration / 1000000).max(1000 / 60);
+            while delay > 0 {
+                frames.push((pixels.as_slice(), delay.min(MAX_FRAME_DELAY) as u16));
+                delay = delay.saturating_sub(MAX_FRAME_DELAY);
+            }
+        }
+
+        match std::fs::write(path, encode_apng(&frames, self.width, self.height)) {
+            Ok(_) => println!("Saved recording to {path}"),
+            Err(_) => println!("Failed to write recording!")
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::{io::Read, path::{Path, PathBuf}, process::Command};
+#[cfg(feature = "sdl")]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(feature = "sdl")]
+use sdl3::{dialog::{show_open_file_dialog, DialogCallback}, video::Window};
+use chip8_emulator::{assemble_octo, Chip8, Chip8Error, Profile, Symbols};
+use crate::zip::extract_rom;
+
+// Bounces a "C8" logo drawn with the built in font around the screen until a rom is opened
+const SPLASH_ROM: [u8; 66] = [
+    0x00, 0xE0, 0x60, 0x00, 0x61, 0x00, 0x62, 0x01, 0x63, 0x01, // CLS, V0 = 0, V1 = 0, V2 = 1, V3 = 1
+    0x22, 0x30, 0x65, 0x03, 0xF5, 0x15, 0xF5, 0x07, 0x35, 0x00, // Draws the logo and waits 3 ticks
+    0x12, 0x10, 0x22, 0x30, 0x80, 0x24, 0x81, 0x34,             // Erases the logo and moves it by V2 and V3
+    0x40, 0x00, 0x62, 0x01, 0x40, 0x36, 0x62, 0xFF,             // Bounces off the left and right edges
+    0x41, 0x00, 0x63, 0x01, 0x41, 0x1B, 0x63, 0xFF,             // Bounces off the top and bottom edges
+    0x12, 0x0A, 0x00, 0x00,
+    0x64, 0x0C, 0xF4, 0x29, 0xD0, 0x15, 0x64, 0x08, 0xF4, 0x29, // Draws C at V0, V1 and 8 five pixels right
+    0x86, 0x00, 0x76, 0x05, 0xD6, 0x15, 0x00, 0xEE
+];
+
+// Reads a rom from a file or zip archive, from standard input with "-", or from an http or https url through curl
+pub fn read_rom(rom_path: &str) -> Result<Vec<u8>, &'static str> {
+    if rom_path.is_empty() { return Ok(SPLASH_ROM.to_vec()) }
+
+    if rom_path == "-" {
+        let mut rom = Vec::new();
+        return match std::io::stdin().read_to_end(&mut rom) {
+            Ok(_) => Ok(rom),
+            Err(_) => Err("Failed to read the rom from standard input!")
+        }
+    }
+
+    if is_url(rom_path) {
+        return match Command::new("curl").args(["--fail", "--silent", "--show-error", "--location", rom_path]).output() {
+            Ok(output) if output.status.success() => Ok(output.stdout),
+            Ok(_) => Err("Failed to download the rom!"),
+            Err(_) => Err("Downloading roms requires curl!")
+        }
+    }
+
+    let rom = match std::fs::read(rom_path) {
+        Ok(rom) => rom,
+        Err(_) => return Err("Path to the rom is invalid!")
+    };
+
+    // Extracts roms from zip archives
+    match rom_path.to_ascii_lowercase().ends_with(".zip") {
+        true => extract_rom(&rom),
+        false => Ok(rom)
+    }
+}
+
+// Reads a rom like read_rom, assembling Octo sources ending in .8o for the profile so they run directly
+// Returns the source's labels as symbols, or those of the .sym file beside the rom
+pub fn read_program(rom_path: &str, profile: Profile, start: u16) -> Result<(Vec<u8>, Symbols), Chip8Error> {
+    let rom = read_rom(rom_path).map_err(Chip8Error::Rom)?;
+    if !is_octo_source(rom_path) { return Ok((rom, beside_symbols(rom_path)?)) }
+    match String::from_utf8(rom) {
+        Ok(source) => assemble_octo(&source, profile, start),
+        Err(_) => Err(Chip8Error::Rom("The source isn't UTF-8 text!"))
+    }
+}
+
+// Roms without a symbol file beside them, including downloaded and piped roms, have no symbols
+fn beside_symbols(rom_path: &str) -> Result<Symbols, Chip8Error> {
+    if rom_path.is_empty() || rom_path == "-" || is_url(rom_path) { return Ok(Symbols::default()) }
+    match std::fs::read_to_string(Path::new(rom_path).with_extension("sym")) {
+        Ok(text) => Symbols::parse(&text),
+        Err(_) => Ok(Symbols::default())
+    }
+}
+
+pub fn read_symbols(path: &str) -> Result<Symbols, Chip8Error> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Symbols::parse(&text),
+        Err(_) => Err("Path to the symbol file is invalid!".into())
+    }
+}
+
+pub fn is_octo_source(rom_path: &str) -> bool {
+    rom_path.to_ascii_lowercase().ends_with(".8o")
+}
+
+// Reads the rom again and restarts, where standard input can only be read once so its first read is reused
+// Returns the symbols read with the rom, which stay the same for standard input
+pub fn reload_rom(chip8: &mut Chip8, rom_path: &str, first_rom: &[u8], symbols: &Symbols) -> Result<Symbols, Chip8Error> {
+    let (rom, symbols) = match rom_path {
+        "-" => (first_rom.to_vec(), symbols.clone()),
+        _ => read_program(rom_path, chip8.profile(), chip8.start_address())?
+    };
+    chip8.load_rom(&rom)?;
+    Ok(symbols)
+}
+
+// Opens the asynchronous file dialog, where the chosen path is received later and an empty path means it was canceled
+#[cfg(feature = "sdl")]
+pub fn open_rom_dialog(window: &Window) -> Result<Receiver<PathBuf>, &'static str> {
+    let (sender, receiver) = mpsc::channel::<PathBuf>();
+    let dialog_callback: DialogCallback = Box::new(move |paths, _| {
+        // Reads the rom path from dialog if provided
+        let rom = match paths {
+            Ok(paths) if !paths.is_empty() => paths[0].clone(),
+            _ => PathBuf::from("")
+        };
+
+        // Sends the rom path to the main thread
+        if sender.send(rom).is_err() {
+            println!("Failed to send rom path from dialog!")
+        }
+    });
+
+    match show_open_file_dialog(&[], None::<&str>, false, Some(window), dialog_callback) {
+        Ok(_) => Ok(receiver),
+        Err(_) => Err("Failed to open file dialog!")
+    }
+}
+
+// Save states sit next to rom files, while downloaded and piped roms save in the working directory
+pub fn state_path(rom_path: &str) -> PathBuf {
+    match rom_path {
+        "-" => PathBuf::from("stdin.state"),
+        _ if is_url(rom_path) => {
+            let name = rom_path.trim_end_matches('/').rsplit('/').next().unwrap_or("download");
+            Path::new(name).with_extension("state")
+        }
+        _ => Path::new(rom_path).with_extension("state")
+    }
+}
+
+// Names a rom by its file name for display, where standard input has none
+pub fn rom_name(rom_path: &str) -> String {
+    match rom_path {
+        "" => String::from("no rom"),
+        "-" => String::from("stdin"),
+        _ => match Path::new(rom_path.trim_end_matches('/')).file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => String::from(rom_path)
+        }
+    }
+}
+
+fn is_url(rom_path: &str) -> bool {
+    rom_path.starts_with("http://") || rom_path.starts_with("https://")
+}
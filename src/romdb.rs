@@ -0,0 +1,77 @@
+use chip8_emulator::Profile;
+use crate::toml::config_path;
+
+// Database embedded in the binary, which entries in romdb.txt next to the config file extend
+const EMBEDDED_DATABASE: &str = include_str!("romdb.txt");
+
+// Title, author, and recommended settings of a known rom
+pub struct RomInfo {
+    pub title: String,
+    pub author: String,
+    pub profile: Profile,
+    pub clock_hz: Option<u32>
+}
+
+// Looks up a rom by its SHA-1 hash, preferring the user's database over the embedded one
+pub fn lookup(rom: &[u8]) -> Option<RomInfo> {
+    let hash: String = sha1(rom).iter().map(|byte| format!("{byte:02x}")).collect();
+    let user_database = config_path("romdb.txt").and_then(|path| std::fs::read_to_string(path).ok()).unwrap_or_default();
+
+    user_database.lines().chain(EMBEDDED_DATABASE.lines()).find_map(|line| {
+        let line = line.trim();
+        if line.starts_with('#') { return None }
+
+        let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        let [entry_hash, profile, clock, title, author] = fields.as_slice() else { return None };
+        if !entry_hash.eq_ignore_ascii_case(&hash) { return None }
+
+        let clock_hz = match *clock {
+            "-" => None,
+            clock => Some(clock.parse().ok()?)
+        };
+        Some(RomInfo{title: String::from(*title), author: String::from(*author), profile: Profile::parse(profile)?, clock_hz})
+    })
+}
+
+// https://www.rfc-editor.org/rfc/rfc3174
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Pads the message with a one bit, zeros, and its length in bits to a multiple of 64 bytes
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 { message.push(0); }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..20 => ((b & c) | (!b & d), 0x5A827999),
+                20..40 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..60 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6)
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+
+        for (value, new) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(new);
+        }
+    }
+
+    let mut hash = [0; 20];
+    for (bytes, value) in hash.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    hash
+}
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+use chip8_emulator::Chip8;
+
+// Keeps SCHIP's RPL user flags in a file next to the rom, so high scores saved with FX75 survive restarts
+pub struct RplFlags {
+    path: PathBuf,
+    saved: [u8; 16]
+}
+
+impl RplFlags {
+    // Restores the rom's flags into the chip8 context, where a missing file leaves them cleared
+    pub fn load(chip8: &mut Chip8, path: PathBuf) -> RplFlags {
+        let mut saved = [0; 16];
+        if let Ok(bytes) = std::fs::read(&path) {
+            for (flag, byte) in saved.iter_mut().zip(bytes) { *flag = byte; }
+        }
+        chip8.set_rpl_flags(saved);
+        RplFlags{path, saved}
+    }
+
+    // Writes the flags whenever the program changes them
+    pub fn update(&mut self, chip8: &Chip8) {
+        if *chip8.rpl_flags() == self.saved { return }
+        self.saved = *chip8.rpl_flags();
+        if std::fs::write(&self.path, self.saved).is_err() {
+            println!("Failed to save RPL flags!");
+        }
+    }
+}
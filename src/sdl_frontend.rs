@@ -0,0 +1,73 @@
+use sdl3::{rect::Rect, render::Texture};
+use chip8_emulator::{Chip8Error, FrameBuffer, Frontend, Keys, Palette};
+use crate::phosphor::PhosphorFilter;
+
+// Colors the frame into the window's texture, while the main loop draws the canvas around it
+// Keys are applied as their events arrive so movies can journal them, and beeps are timed by the audio sink
+pub struct SdlFrontend<'a> {
+    pub texture: Texture<'a>,
+    pub pixels: Vec<u32>, // ARGB8888 colors of the displayed frame
+    pub keys: Keys,
+    pub frame_nanos: u64, // Time since the last present, which the phosphor filter fades by
+    upload_bytes: Vec<u8>, // Uploaded rows as native endian bytes, reused between frames
+    palette: Palette,
+    is_recolored: bool, // Colors every row on the next present, after the palette changed
+    phosphor_filter: Option<PhosphorFilter>
+}
+
+impl SdlFrontend<'_> {
+    pub fn new(texture: Texture<'_>, palette: Palette, is_phosphor: bool) -> SdlFrontend<'_> {
+        // Fades erased pixels over a few frames when requested
+        let phosphor_filter = match is_phosphor {
+            true => Some(PhosphorFilter::new(palette[0])),
+            false => None
+        };
+        SdlFrontend{texture, pixels: Vec::new(), keys: [false; 16], frame_nanos: 0, upload_bytes: Vec::new(), palette, is_recolored: false,
+            phosphor_filter}
+    }
+
+    // Changes the colors at runtime, recoloring the pixels already drawn on the next present
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        self.is_recolored = true;
+        if let Some(filter) = &mut self.phosphor_filter { *filter = PhosphorFilter::new(palette[0]); }
+    }
+}
+
+impl Frontend for SdlFrontend<'_> {
+    fn present(&mut self, frame_buffer: &FrameBuffer) -> Result<(), Chip8Error> {
+        // Colors the palette indices of the rows drawn since the last frame, or every row for a new palette
+        let width = frame_buffer.width as usize;
+        let dirty_rows = &match std::mem::take(&mut self.is_recolored) {
+            true => 0..frame_buffer.height,
+            false => frame_buffer.dirty_rows.clone()
+        };
+        self.pixels.resize(frame_buffer.pixels.len(), 0);
+        let dirty_pixels = dirty_rows.start as usize * width..dirty_rows.end as usize * width;
+        for (pixel, &index) in self.pixels[dirty_pixels.clone()].iter_mut().zip(&frame_buffer.pixels[dirty_pixels]) {
+            *pixel = self.palette[index as usize & 0xF];
+        }
+
+        // Uploads the drawn rows, or the whole faded copy from the phosphor filter which changes every frame
+        let (pixels, rows) = match &mut self.phosphor_filter {
+            Some(filter) => (filter.apply(&self.pixels, self.frame_nanos), 0..frame_buffer.height),
+            None => (self.pixels.as_slice(), dirty_rows.clone())
+        };
+        if rows.is_empty() { return Ok(()) }
+
+        let row_pixels = &pixels[rows.start as usize * width..rows.end as usize * width];
+        self.upload_bytes.clear();
+        self.upload_bytes.extend(row_pixels.iter().flat_map(|pixel| pixel.to_ne_bytes()));
+        let rect = Rect::new(0, rows.start as i32, frame_buffer.width, rows.end - rows.start);
+        match self.texture.update(rect, &self.upload_bytes, width * 4) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(Chip8Error::sdl("Failed to update texture!", error))
+        }
+    }
+
+    fn beep(&mut self, _: bool) {}
+
+    fn poll_keys(&mut self) -> Keys {
+        self.keys
+    }
+}
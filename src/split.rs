@@ -0,0 +1,135 @@
+use sdl3::{keyboard::Keycode, pixels::{Color, PixelFormat}, rect::Rect, render::{BlendMode, Canvas, FRect, TextureCreator},
+    video::{Window, WindowContext}};
+use chip8_emulator::{Chip8, Chip8Error, Chip8Options, DisplayMode, Palette};
+use crate::{config::Chip8Configuration, crash, create_texture, movie::MOVIE_FRAME_NANOS, sdl_frontend::SdlFrontend};
+
+// Second machine drawn to the right of the first with its own keys, for comparing quirks or racing the same game
+// It follows the first machine's pause and is silent, so the buzzer belongs to the first machine
+pub struct SplitMachine<'a> {
+    pub chip8: Chip8,
+    frontend: SdlFrontend<'a>,
+    key_map: Vec<(Keycode, usize)>,
+    comparison: Option<Comparison>
+}
+
+// Quirk comparison, where both machines run whole 60hz frames with the same keys until their displays differ
+struct Comparison {
+    frame_buffer: u64,  // Nanoseconds of emulation not yet run as a whole frame
+    frame_count: u64,
+    differences: Option<Vec<(u32, u32)>> // Pixels that differed on the first frame where the displays did
+}
+
+impl<'a> SplitMachine<'a> {
+    // Compares quirks when the first machine's rom runs again, instead of playing another rom with the second key map
+    pub fn new(rom: &[u8], options: &Chip8Options, is_compare: bool, texture_creator: &'a TextureCreator<WindowContext>,
+        pixel_format: PixelFormat, configuration: &Chip8Configuration) -> Result<SplitMachine<'a>, Chip8Error> {
+        let mut chip8 = Chip8::init(rom, options)?;
+        chip8.set_backend(configuration.backend);
+        let texture = create_texture(texture_creator, pixel_format, chip8.display_mode().size(), configuration.is_smooth)?;
+        let frontend = SdlFrontend::new(texture, configuration.palette, configuration.is_phosphor);
+        let comparison = match is_compare {
+            true => Some(Comparison{frame_buffer: 0, frame_count: 0, differences: None}),
+            false => None
+        };
+        Ok(SplitMachine{chip8, frontend, key_map: configuration.key_map2.clone(), comparison})
+    }
+
+    pub fn is_compare(&self) -> bool {
+        self.comparison.is_some()
+    }
+
+    // Presses or releases the chip8 keys bound to a host key in the second key map, where comparisons share the first machine's keys
+    pub fn set_key(&mut self, keycode: Keycode, is_pressed: bool) {
+        if self.is_compare() { return }
+        for &(bound_keycode, chip8_key) in &self.key_map {
+            if bound_keycode == keycode { self.chip8.set_key(chip8_key, is_pressed); }
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.frontend.set_palette(palette);
+    }
+
+    pub fn reset(&mut self) {
+        self.chip8.reset();
+        if let Some(comparison) = &mut self.comparison {
+            *comparison = Comparison{frame_buffer: 0, frame_count: 0, differences: None};
+        }
+    }
+
+    // Emulates the same time as the first machine, and follows its resolution changes
+    // Errors come with a crash report of the machine that failed
+    pub fn run(&mut self, first: &mut Chip8, delta_nanos: u64, texture_creator: &'a TextureCreator<WindowContext>, pixel_format: PixelFormat,
+        is_smooth: bool) -> Result<Option<String>, Chip8Error> {
+        let mut message = None;
+        match &mut self.comparison {
+            None => if let Some(error) = self.chip8.run(delta_nanos as f32) {
+                return Err(crash::report(&self.chip8, error))
+            }
+
+            // Runs whole frames, so both machines see every key on the same frame, and pauses on the first difference
+            Some(comparison) => {
+                comparison.frame_buffer += delta_nanos;
+                while comparison.frame_buffer >= MOVIE_FRAME_NANOS && !first.is_paused() {
+                    comparison.frame_buffer -= MOVIE_FRAME_NANOS;
+                    self.chip8.set_keys(*first.keys());
+                    if let Some(error) = first.run(MOVIE_FRAME_NANOS as f32) {
+                        return Err(crash::report(first, error))
+                    }
+                    if let Some(error) = self.chip8.run(MOVIE_FRAME_NANOS as f32) {
+                        return Err(crash::report(&self.chip8, error))
+                    }
+                    comparison.frame_count += 1;
+
+                    if comparison.differences.is_none() && first.frame_buffer() != self.chip8.frame_buffer() {
+                        comparison.differences = Some(differing_pixels(first, &self.chip8));
+                        first.pause();
+                        message = Some(format!("Displays differ on frame {}", comparison.frame_count));
+                    }
+                }
+            }
+        }
+
+        if let Some(mode) = self.chip8.take_display_mode_change() {
+            self.frontend.texture = create_texture(texture_creator, pixel_format, DisplayMode::size(mode), is_smooth)?;
+        }
+        Ok(message)
+    }
+
+    // Colors the frame and scales it into the right half of the window, beside the first machine's display
+    // A comparison's differing pixels are highlighted over both displays
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>, frame_nanos: u64, (width, height): (u32, u32)) -> Result<(), Chip8Error> {
+        self.frontend.frame_nanos = frame_nanos;
+        self.chip8.present(&mut self.frontend)?;
+        if let Err(error) = canvas.copy(&self.frontend.texture, None, Rect::new(width as i32, 0, width, height)) {
+            return Err(Chip8Error::sdl("Failed to copy texture!", error))
+        }
+
+        let Some(Comparison{differences: Some(pixels), ..}) = &self.comparison else { return Ok(()) };
+        let pixel_rects: Vec<FRect> = pixels.iter()
+            .flat_map(|&(x, y)| [FRect::new(x as f32, y as f32, 1.0, 1.0), FRect::new((x + width) as f32, y as f32, 1.0, 1.0)])
+            .collect();
+        let background_color = canvas.draw_color();
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(255, 0, 0, 160));
+        let result = canvas.fill_rects(&pixel_rects);
+        canvas.set_draw_color(background_color);
+        canvas.set_blend_mode(BlendMode::None);
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => Err(Chip8Error::sdl("Failed to highlight differing pixels!", error))
+        }
+    }
+}
+
+// Lists the pixels where two displays differ, where every pixel differs between resolutions
+fn differing_pixels(first: &Chip8, second: &Chip8) -> Vec<(u32, u32)> {
+    let (width, height) = first.display_mode().size();
+    let is_same_size = second.display_mode().size() == (width, height);
+    (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            let index = (y * width + x) as usize;
+            !is_same_size || first.frame_buffer().get(index) != second.frame_buffer().get(index)
+        })
+        .collect()
+}
@@ -0,0 +1,105 @@
+// Little endian serialization for versioned save files
+// Fields added by later versions are appended, so older files stay readable
+pub struct StateWriter {
+    bytes: Vec<u8>
+}
+
+impl StateWriter {
+    // Begins a file with a magic number and format version
+    pub fn new(magic: &[u8; 4], version: u16) -> StateWriter {
+        let mut writer = StateWriter{bytes: Vec::new()};
+        writer.bytes.extend_from_slice(magic);
+        writer.write_u16(version);
+        writer
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // Writes a length prefixed byte array
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+pub struct StateReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    version: u16
+}
+
+impl<'a> StateReader<'a> {
+    // Checks the magic number and reads the format version
+    pub fn new(bytes: &'a [u8], magic: &[u8; 4], latest_version: u16) -> Result<StateReader<'a>, &'static str> {
+        if bytes.len() < 6 || &bytes[..4] != magic {
+            return Err("Not a valid save file!")
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version == 0 || version > latest_version {
+            return Err("Save file is from an unsupported version!")
+        }
+        Ok(StateReader{bytes, position: 6, version})
+    }
+
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        if self.position + N > self.bytes.len() {
+            return Err("Save file is truncated!")
+        }
+
+        let mut array = [0; N];
+        array.copy_from_slice(&self.bytes[self.position..self.position + N]);
+        self.position += N;
+        Ok(array)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, &'static str> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, &'static str> {
+        Ok(f32::from_le_bytes(self.read_array()?))
+    }
+
+    // Reads a length prefixed byte array
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], &'static str> {
+        let length = self.read_u32()? as usize;
+        if self.position + length > self.bytes.len() {
+            return Err("Save file is truncated!")
+        }
+
+        let bytes = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::error::Chip8Error;
+
+// Names of addresses from an assembler's labels or a symbol file, for disassembly and debugging
+// Symbol files hold one "<Address> <Name>" per line, where addresses are hexadecimal and comments start with #
+#[derive(Clone, Default)]
+pub struct Symbols {
+    symbols: Vec<(u16, String)> // Sorted by address, where the first name of an address is shown
+}
+
+impl Symbols {
+    pub fn parse(text: &str) -> Result<Symbols, Chip8Error> {
+        let mut symbols = Symbols::default();
+        for (line_index, line) in text.lines().enumerate() {
+            let error = |message| Chip8Error::Assembly{line: line_index + 1, message};
+            let words: Vec<&str> = line.split('#').next().unwrap_or("").split_whitespace().collect();
+            match words.as_slice() {
+                [] => (),
+                [address, name] => {
+                    let digits = address.strip_prefix("0x").unwrap_or(address);
+                    let address = u16::from_str_radix(digits, 16).map_err(|_| error("Invalid address in the symbol file!"))?;
+                    symbols.insert(address, name);
+                }
+                _ => return Err(error("Symbol file lines need an address and a name!"))
+            }
+        }
+        Ok(symbols)
+    }
+
+    // Formats the symbols as a symbol file
+    pub fn to_text(&self) -> String {
+        self.symbols.iter().map(|(address, name)| format!("0x{address:03X} {name}\n")).collect()
+    }
+
+    pub fn insert(&mut self, address: u16, name: &str) {
+        let index = self.symbols.partition_point(|&(other, _)| other <= address);
+        self.symbols.insert(index, (address, String::from(name)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn address(&self, name: &str) -> Option<u16> {
+        self.symbols.iter().find(|(_, other)| other == name).map(|&(address, _)| address)
+    }
+
+    pub fn name(&self, address: u16) -> Option<&str> {
+        let index = self.symbols.partition_point(|&(other, _)| other < address);
+        self.symbols.get(index).filter(|&&(other, _)| other == address).map(|(_, name)| name.as_str())
+    }
+
+    // An address in hexadecimal followed by its name, like "0x202 (draw_player)"
+    pub fn describe(&self, address: u16) -> String {
+        match self.name(address) {
+            Some(name) => format!("0x{address:03X} ({name})"),
+            None => format!("0x{address:03X}")
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::{fs, path::Path};
+use chip8_emulator::{Chip8, Chip8Error, Chip8Options, Palette, Profile};
+use crate::png::encode_png;
+
+const FRAME_NANOS: f32 = 1000000000.0 / 60.0;
+
+// Part of each suite rom's file name, where the keypad and beep tests are left out since they need a person
+const SUITE_ROMS: [&str; 5] = ["chip8-logo", "ibm-logo", "corax+", "flags", "quirks"];
+
+// Frames until every suite rom shows its results, including the quirks test's timing of the display wait
+const SUITE_FRAMES: u32 = 600;
+
+// The columns of the self test's table
+const SUITE_PROFILES: [Profile; 4] = [Profile::Chip8, Profile::Vip, Profile::Schip, Profile::XoChip];
+
+// The quirks test reads its menu choice from here, so it runs the platform's test without a key press
+const MENU_ADDRESS: usize = 0x1FF;
+
+// Runs each rom in a manifest headlessly and compares its frame buffer hash after a number of frames
+// Manifest lines are "<Frames> <Hash | -> <Profile> <Rom path>", where rom paths are relative to the manifest
+// A hash of "-" prints the current hash so new roms can be added to the manifest
+pub fn run_manifest(manifest_path: &str) -> Option<&'static str> {
+    let manifest = match fs::read_to_string(manifest_path) {
+        Ok(manifest) => manifest,
+        Err(_) => return Some("Path to the test manifest is invalid!")
+    };
+    let directory = Path::new(manifest_path).parent().unwrap_or(Path::new(""));
+
+    let (mut passed, mut failed) = (0, 0);
+    for line in manifest.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() { continue }
+
+        // Parses the frame count, expected hash, profile, and rom path
+        let fields: Vec<&str> = line.splitn(4, char::is_whitespace).map(str::trim).collect();
+        let [frames, expected, profile, rom_path] = fields[..] else {
+            return Some("Test manifest lines need a frame count, hash, profile, and rom path!")
+        };
+        let Ok(frames) = frames.parse::<u32>() else { return Some("Test manifest has an invalid frame count!") };
+        let Some(profile) = Profile::parse(profile) else { return Some("Test manifest has an unknown profile!") };
+        let expected = match expected {
+            "-" => None,
+            hash => match u64::from_str_radix(hash.trim_start_matches("0x"), 16) {
+                Ok(hash) => Some(hash),
+                Err(_) => return Some("Test manifest has an invalid hash!")
+            }
+        };
+
+        let rom_path = directory.join(rom_path);
+        let hash = match run_rom(&rom_path.to_string_lossy(), profile, frames) {
+            Ok(hash) => hash,
+            Err(error) => {
+                println!("FAIL {}: {error}", rom_path.display());
+                failed += 1;
+                continue
+            }
+        };
+
+        match expected {
+            Some(expected) if expected == hash => {
+                println!("PASS {}", rom_path.display());
+                passed += 1;
+            }
+            Some(expected) => {
+                println!("FAIL {}: expected {expected:016x}, got {hash:016x}", rom_path.display());
+                failed += 1;
+            }
+            None => println!("HASH {}: {hash:016x}", rom_path.display())
+        }
+    }
+
+    println!("{passed} passed, {failed} failed");
+    match failed {
+        0 => None,
+        _ => Some("Some rom tests failed!")
+    }
+}
+
+// Emulates whole frames with the profile's defaults and a fixed seed, and hashes which pixels are lit
+fn run_rom(rom_path: &str, profile: Profile, frames: u32) -> Result<u64, Chip8Error> {
+    let options = Chip8Options::new(profile, 0);
+    let rom = match fs::read(rom_path) {
+        Ok(rom) => rom,
+        Err(_) => return Err(Chip8Error::Rom("Path to the rom is invalid!"))
+    };
+    let mut chip8 = Chip8::init(&rom, &options)?;
+    run_frames(&mut chip8, frames)
+}
+
+// Emulates whole frames and hashes which pixels are lit
+fn run_frames(chip8: &mut Chip8, frames: u32) -> Result<u64, Chip8Error> {
+    for _ in 0..frames {
+        if let Some(error) = chip8.run(FRAME_NANOS) { return Err(error) }
+    }
+
+    // FNV-1a over one byte per pixel, so the hash doesn't depend on the palette
+    let hash = chip8.frame_buffer().iter().fold(0xCBF29CE484222325, |hash: u64, &pixel| {
+        (hash ^ (pixel != 0) as u64).wrapping_mul(0x100000001B3)
+    });
+    Ok(hash)
+}
+
+// Runs Timendus's chip8 test suite from a directory under every profile, and prints a table of roms by profile
+// The configured profile's column runs with the configured quirks, and the other columns with their profile's defaults
+// Result screens are compared to signatures.txt in the directory, with "<Rom file> <Profile> <Hash>" lines recorded from a
+// trusted run, where a missing rom or signature fails, and the lines to add for missing signatures print after the table
+pub fn run_selftest(directory: &str, options: &Chip8Options) -> Option<&'static str> {
+    let files: Vec<String> = match fs::read_dir(directory) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()).collect(),
+        Err(_) => return Some("Path to the test suite directory is invalid!")
+    };
+    let signatures = fs::read_to_string(Path::new(directory).join("signatures.txt")).unwrap_or_default();
+    let signature = |file: &str, profile: &str| signatures.lines()
+        .map(|line| line.split('#').next().unwrap_or("").split_whitespace().collect::<Vec<_>>())
+        .find_map(|fields| match fields[..] {
+            [other_file, other_profile, hash] if other_file == file && other_profile == profile =>
+                u64::from_str_radix(hash.trim_start_matches("0x"), 16).ok(),
+            _ => None
+        });
+
+    let header: String = SUITE_PROFILES.iter().map(|profile| format!(" {:<8}", profile.name())).collect();
+    println!("{:<20}{}", "Rom", header.trim_end());
+
+    let (mut passed, mut failed) = (0, 0);
+    let (mut errors, mut new_signatures) = (Vec::new(), Vec::new());
+    for suite_rom in SUITE_ROMS {
+        let file = files.iter().filter(|file| file.contains(suite_rom) && file.ends_with(".ch8")).min();
+        let mut row = format!("{:<20}", file.map_or(suite_rom, String::as_str));
+        for profile in SUITE_PROFILES {
+            let name = profile.name();
+            let Some(file) = file else {
+                row += &format!(" {:<8}", "MISSING");
+                failed += 1;
+                continue
+            };
+
+            // Runs with a fixed seed, and starts menu driven roms on the profile's test
+            let options = match profile == options.profile {
+                true => Chip8Options{seed: Some(0), ..*options},
+                false => Chip8Options::new(profile, 0)
+            };
+            let hash = fs::read(Path::new(directory).join(file)).map_err(|_| Chip8Error::Rom("Path to the rom is invalid!"))
+                .and_then(|rom| Chip8::init(&rom, &options))
+                .and_then(|mut chip8| {
+                    if let Some(choice) = menu_choice(suite_rom, profile) { chip8.memory_mut()[MENU_ADDRESS] = choice; }
+                    run_frames(&mut chip8, SUITE_FRAMES)
+                });
+
+            let (result, is_passed) = match (hash, signature(file, name)) {
+                (Err(error), _) => {
+                    errors.push(format!("{file} with {name}: {error}"));
+                    ("ERROR", false)
+                }
+                (Ok(hash), Some(expected)) => match hash == expected {
+                    true => ("PASS", true),
+                    false => ("FAIL", false)
+                }
+                (Ok(hash), None) => {
+                    new_signatures.push(format!("{file} {name} {hash:016x}"));
+                    ("NEW", false)
+                }
+            };
+            match is_passed {
+                true => passed += 1,
+                false => failed += 1
+            }
+            row += &format!(" {result:<8}");
+        }
+        println!("{}", row.trim_end());
+    }
+
+    for error in errors { println!("{error}"); }
+    if !new_signatures.is_empty() {
+        println!("Roms without a signature fail. Once their screens show a pass, add these lines to signatures.txt:");
+        for line in new_signatures { println!("{line}"); }
+    }
+    println!("{passed} passed, {failed} failed");
+    match failed {
+        0 => None,
+        _ => Some("Some test suite roms failed!")
+    }
+}
+
+// Emulates a number of frames headlessly and writes each 60hz frame as a numbered png, so screens can be diffed between
+// versions or quirk profiles, where a fixed seed makes runs repeatable
+pub fn dump_frames(rom: &[u8], options: &Chip8Options, palette: &Palette, directory: &str, frames: u32) -> Option<Chip8Error> {
+    if fs::create_dir_all(directory).is_err() { return Some("Failed to create the frame directory!".into()) }
+    let options = Chip8Options{seed: Some(0), ..*options};
+    let mut chip8 = match Chip8::init(rom, &options) {
+        Ok(chip8) => chip8,
+        Err(error) => return Some(error)
+    };
+
+    for frame in 1..=frames {
+        if let Some(error) = chip8.run(FRAME_NANOS) { return Some(error) }
+        let (width, height) = chip8.display_mode().size();
+        let pixels: Vec<u32> = chip8.front_buffer().iter().map(|&index| palette[index as usize & 0xF]).collect();
+        let path = Path::new(directory).join(format!("frame-{frame:05}.png"));
+        if fs::write(path, encode_png(&pixels, width, height)).is_err() { return Some("Failed to write a frame!".into()) }
+    }
+    println!("Wrote {frames} frames to {directory}");
+    None
+}
+
+fn menu_choice(suite_rom: &str, profile: Profile) -> Option<u8> {
+    match (suite_rom, profile) {
+        ("quirks", Profile::Chip8 | Profile::Vip) => Some(1),
+        ("quirks", Profile::Schip) => Some(2),
+        ("quirks", Profile::XoChip) => Some(3),
+        _ => None
+    }
+}
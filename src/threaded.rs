@@ -0,0 +1,142 @@
+use std::ops::Range;
+use crate::{chip8::{Profile, Quirks, FLAGS_REGISTER}, decode::{decode, Instruction}};
+
+// A block cache of threaded code rather than a jit, where each block is a chain of closures that the host calls in turn
+// No native code is emitted, which keeps it portable and free of unsafe code
+
+// Longest block compiled at once, which bounds the work thrown away when a block is overwritten
+pub(crate) const MAX_BLOCK_LENGTH: usize = 64;
+
+// Register instructions compiled into closures with their operands and quirks already resolved
+type Op = Box<dyn Fn(&mut [u8; 16], &mut u16)>;
+type Condition = Box<dyn Fn(&[u8; 16]) -> bool>;
+
+// How a block leaves, where blocks without a jump or skip fall through to the interpreter's next instruction
+enum Exit {
+    Next,
+    Jump(u16),
+    Skip(Condition) // Skips the instruction after the block when true
+}
+
+// Basic block of instructions that only change registers and the index register, ending at a jump or skip
+// Memory, display, key, timer, and stack instructions are left to the interpreter, so blocks never touch them
+pub(crate) struct Block {
+    start: u16,
+    pub length: u16, // Instructions in the block, where 0 means the interpreter runs the instruction at the start
+    ops: Vec<Op>,
+    exit: Exit
+}
+
+impl Block {
+    // Runs the block and returns the address it continues at
+    pub fn execute(&self, registers: &mut [u8; 16], index: &mut u16) -> u16 {
+        for op in &self.ops {
+            op(registers, index);
+        }
+        // Wraps past the end of XO-CHIP's 64K of ram like the interpreter
+        let end = self.start.wrapping_add(self.length * 2);
+        match &self.exit {
+            Exit::Next => end,
+            Exit::Jump(address) => *address,
+            Exit::Skip(condition) => match condition(registers) {
+                true => end.wrapping_add(2),
+                false => end
+            }
+        }
+    }
+
+    // Whether a write changes any byte of the block's instructions
+    pub fn overlaps(&self, addresses: &Range<usize>) -> bool {
+        let start = self.start as usize;
+        let end = start + self.length.max(1) as usize * 2;
+        addresses.start < end && start < addresses.end
+    }
+}
+
+// Compiles the block starting at an address, stopping before the first instruction the interpreter must run
+pub(crate) fn compile(ram: &[u8], start: u16, profile: Profile, quirks: Quirks, font_address: u16) -> Block {
+    let mut ops: Vec<Op> = Vec::new();
+    let mut address = start as usize;
+    let mut exit = Exit::Next;
+    while ops.len() < MAX_BLOCK_LENGTH && address + 1 < ram.len() {
+        let opcode = u16::from_be_bytes([ram[address], ram[address + 1]]);
+        let op: Op = match decode(opcode, profile) {
+            Instruction::LdByte{x, kk} => Box::new(move |v, _| v[x as usize] = kk),
+            Instruction::AddByte{x, kk} => Box::new(move |v, _| v[x as usize] = v[x as usize].wrapping_add(kk)),
+            Instruction::Ld{x, y} => Box::new(move |v, _| v[x as usize] = v[y as usize]),
+            Instruction::Or{x, y} => bitwise(x, y, quirks.is_vf_reset, |a, b| a | b),
+            Instruction::And{x, y} => bitwise(x, y, quirks.is_vf_reset, |a, b| a & b),
+            Instruction::Xor{x, y} => bitwise(x, y, quirks.is_vf_reset, |a, b| a ^ b),
+            Instruction::Add{x, y} => Box::new(move |v, _| {
+                let (result, overflow) = v[x as usize].overflowing_add(v[y as usize]);
+                v[x as usize] = result;
+                v[FLAGS_REGISTER] = overflow as u8;
+            }),
+            Instruction::Sub{x, y} => Box::new(move |v, _| {
+                let (result, overflow) = v[x as usize].overflowing_sub(v[y as usize]);
+                v[x as usize] = result;
+                v[FLAGS_REGISTER] = !overflow as u8;
+            }),
+            Instruction::Subn{x, y} => Box::new(move |v, _| {
+                let (result, overflow) = v[y as usize].overflowing_sub(v[x as usize]);
+                v[x as usize] = result;
+                v[FLAGS_REGISTER] = !overflow as u8;
+            }),
+            Instruction::Shr{x, y} => {
+                let source = match quirks.is_shift_quirk { true => y, false => x } as usize;
+                Box::new(move |v, _| {
+                    let value = v[source];
+                    v[x as usize] = value >> 1;
+                    v[FLAGS_REGISTER] = value & 1;
+                })
+            }
+            Instruction::Shl{x, y} => {
+                let source = match quirks.is_shift_quirk { true => y, false => x } as usize;
+                Box::new(move |v, _| {
+                    let value = v[source];
+                    v[x as usize] = value << 1;
+                    v[FLAGS_REGISTER] = value >> 7;
+                })
+            }
+            Instruction::LdIndex{nnn} => Box::new(move |_, index| *index = nnn),
+            Instruction::AddIndex{x} => Box::new(move |v, index| *index = index.wrapping_add(v[x as usize] as u16)),
+            Instruction::LdFont{x} => Box::new(move |v, index| *index = font_address + v[x as usize & 0xF] as u16 * 5),
+
+            // Jumps and skips end the block after it's counted
+            Instruction::Jp{nnn} => {
+                exit = Exit::Jump(nnn);
+                break
+            }
+            Instruction::SeByte{x, kk} => {
+                exit = Exit::Skip(Box::new(move |v| v[x as usize] == kk));
+                break
+            }
+            Instruction::SneByte{x, kk} => {
+                exit = Exit::Skip(Box::new(move |v| v[x as usize] != kk));
+                break
+            }
+            Instruction::Se{x, y} => {
+                exit = Exit::Skip(Box::new(move |v| v[x as usize] == v[y as usize]));
+                break
+            }
+            Instruction::Sne{x, y} => {
+                exit = Exit::Skip(Box::new(move |v| v[x as usize] != v[y as usize]));
+                break
+            }
+            _ => break
+        };
+        ops.push(op);
+        address += 2;
+    }
+
+    let length = ops.len() as u16 + match exit { Exit::Next => 0, _ => 1 };
+    Block{start, length, ops, exit}
+}
+
+// OR, AND, and XOR, which reset the flags register with the vf reset quirk
+fn bitwise(x: u8, y: u8, is_vf_reset: bool, operation: fn(u8, u8) -> u8) -> Op {
+    Box::new(move |v, _| {
+        v[x as usize] = operation(v[x as usize], v[y as usize]);
+        if is_vf_reset { v[FLAGS_REGISTER] = 0; }
+    })
+}
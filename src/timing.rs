@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+// Sleeps for most of a frame with the most precise timer the platform has, leaving only a short margin to spin
+// Windows uses a high resolution waitable timer, Linux uses clock_nanosleep, and other platforms use the standard sleep
+pub struct PreciseSleep {
+    #[cfg(windows)]
+    timer: *mut std::ffi::c_void // Null when high resolution timers aren't supported, before Windows 10 1803
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::ffi::c_void;
+    pub const CREATE_WAITABLE_TIMER_HIGH_RESOLUTION: u32 = 0x2;
+    pub const TIMER_ALL_ACCESS: u32 = 0x1F0003;
+    pub const INFINITE: u32 = 0xFFFFFFFF;
+    unsafe extern "system" {
+        pub fn CreateWaitableTimerExW(attributes: *mut c_void, name: *const u16, flags: u32, access: u32) -> *mut c_void;
+        pub fn SetWaitableTimer(timer: *mut c_void, due_time: *const i64, period: i32, completion: *mut c_void,
+            argument: *mut c_void, resume: i32) -> i32;
+        pub fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+        pub fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::{c_int, c_long};
+    pub const CLOCK_MONOTONIC: c_int = 1;
+    #[repr(C)]
+    pub struct Timespec {
+        pub seconds: c_long,
+        pub nanoseconds: c_long
+    }
+    unsafe extern "C" {
+        pub fn clock_nanosleep(clock: c_int, flags: c_int, request: *const Timespec, remaining: *mut Timespec) -> c_int;
+    }
+}
+
+impl PreciseSleep {
+    #[cfg(windows)]
+    pub fn new() -> PreciseSleep {
+        let timer = unsafe {
+            windows::CreateWaitableTimerExW(std::ptr::null_mut(), std::ptr::null(), windows::CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+                windows::TIMER_ALL_ACCESS)
+        };
+        PreciseSleep{timer}
+    }
+
+    #[cfg(not(windows))]
+    pub fn new() -> PreciseSleep {
+        PreciseSleep{}
+    }
+
+    // Time before the end of a sleep that the timer can overshoot, which is spun instead
+    pub fn margin(&self) -> Duration {
+        #[cfg(windows)]
+        let is_precise = !self.timer.is_null();
+        #[cfg(target_os = "linux")]
+        let is_precise = true;
+        #[cfg(not(any(windows, target_os = "linux")))]
+        let is_precise = false;
+
+        match is_precise {
+            true => Duration::from_micros(250),
+            false => Duration::from_micros(1020)
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn sleep(&self, duration: Duration) {
+        if self.timer.is_null() { return std::thread::sleep(duration) }
+
+        // Negative due times are relative, in units of 100 nanoseconds
+        let due_time = -((duration.as_nanos() / 100) as i64);
+        unsafe {
+            match windows::SetWaitableTimer(self.timer, &due_time, 0, std::ptr::null_mut(), std::ptr::null_mut(), 0) {
+                0 => std::thread::sleep(duration),
+                _ => { windows::WaitForSingleObject(self.timer, windows::INFINITE); }
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn sleep(&self, duration: Duration) {
+        let request = linux::Timespec{seconds: duration.as_secs() as _, nanoseconds: duration.subsec_nanos() as _};
+        unsafe { linux::clock_nanosleep(linux::CLOCK_MONOTONIC, 0, &request, std::ptr::null_mut()); }
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    pub fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+#[cfg(windows)]
+impl Drop for PreciseSleep {
+    fn drop(&mut self) {
+        if !self.timer.is_null() { unsafe { windows::CloseHandle(self.timer); } }
+    }
+}
@@ -0,0 +1,119 @@
+use std::{env, path::PathBuf};
+
+// Values of the toml subset read from config files
+pub enum TomlValue {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+    Array(Vec<TomlValue>)
+}
+
+// Returns ~/.config/chip8-emulator/<name>, preferring XDG_CONFIG_HOME when set
+pub fn config_path(name: &str) -> Option<PathBuf> {
+    let config_directory = match env::var_os("XDG_CONFIG_HOME") {
+        Some(directory) => PathBuf::from(directory),
+        None => PathBuf::from(env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?).join(".config")
+    };
+    Some(config_directory.join("chip8-emulator").join(name))
+}
+
+// Parses tables, single line key value pairs, and comments into "table.key" names and values
+pub fn parse_toml(text: &str) -> Result<Vec<(String, TomlValue)>, &'static str> {
+    let mut entries = Vec::new();
+    let mut table = String::new();
+
+    for line in text.lines() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() { continue }
+
+        // Prefixes the following keys with the table name
+        if let Some(name) = line.strip_prefix('[') {
+            table = match name.strip_suffix(']') {
+                Some(name) => format!("{}.", name.trim()),
+                None => return Err("Config file has an unclosed table name!")
+            };
+            continue
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some(pair) => pair,
+            None => return Err("Config file has a line without a value!")
+        };
+
+        let value = match parse_value(value.trim()) {
+            Some(value) => value,
+            None => return Err("Config file has an invalid value!")
+        };
+        entries.push((table.clone() + key.trim().trim_matches('"'), value));
+    }
+    Ok(entries)
+}
+
+// Removes a comment unless the # is inside of a string
+fn strip_comment(line: &str) -> &str {
+    let mut is_string = false;
+    for (i, character) in line.char_indices() {
+        match character {
+            '"' => is_string = !is_string,
+            '#' if !is_string => return &line[..i],
+            _ => ()
+        }
+    }
+    line
+}
+
+fn parse_value(text: &str) -> Option<TomlValue> {
+    match text {
+        "true" => return Some(TomlValue::Boolean(true)),
+        "false" => return Some(TomlValue::Boolean(false)),
+        _ => ()
+    }
+
+    // Parses a string without escape sequences
+    if let Some(string) = text.strip_prefix('"') {
+        return Some(TomlValue::String(String::from(string.strip_suffix('"')?)))
+    }
+
+    // Parses an array of values separated by commas
+    if let Some(array) = text.strip_prefix('[') {
+        let array = array.strip_suffix(']')?.trim();
+        if array.is_empty() { return Some(TomlValue::Array(Vec::new())) }
+        let values: Option<Vec<TomlValue>> = array.trim_end_matches(',').split(',')
+            .map(|value| parse_value(value.trim())).collect();
+        return Some(TomlValue::Array(values?))
+    }
+
+    // Parses a decimal or hexadecimal integer with optional underscores
+    let digits = text.replace('_', "");
+    match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => digits.parse::<i64>().ok()
+    }.map(TomlValue::Integer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tables_and_values() {
+        let text = "profile = \"schip\" # Comment\n\n[keys]\nlayout = \"#qwerty\"\nclock = 0x2_00\nmute = true\ncolors = [1, -2, [],]\n";
+        let entries = parse_toml(text).unwrap();
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["profile", "keys.layout", "keys.clock", "keys.mute", "keys.colors"]);
+        assert!(matches!(&entries[0].1, TomlValue::String(string) if string == "schip"));
+        assert!(matches!(&entries[1].1, TomlValue::String(string) if string == "#qwerty"));
+        assert!(matches!(entries[2].1, TomlValue::Integer(0x200)));
+        assert!(matches!(entries[3].1, TomlValue::Boolean(true)));
+        assert!(matches!(&entries[4].1, TomlValue::Array(values)
+            if matches!(values[..], [TomlValue::Integer(1), TomlValue::Integer(-2), TomlValue::Array(ref empty)] if empty.is_empty())));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(parse_toml("[keys").is_err());
+        assert!(parse_toml("clock").is_err());
+        assert!(parse_toml("clock = fast").is_err());
+        assert!(parse_toml("profile = \"schip").is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::{io::{Read, Write}, process::{Command, Stdio}, sync::mpsc::{self, Receiver}, thread::sleep, time::{Duration, Instant}};
+use chip8_emulator::{Chip8, Chip8Error, FrameBuffer, Frontend, Keys, Palette};
+use crate::{config::{typed_key, Chip8Configuration, HostKey}, crash, ErrorPolicy};
+
+const FRAME_NANOS: f32 = 1000000000.0 / 60.0;
+
+// Terminals only report presses, so a key stays held until its auto repeat stops arriving
+const KEY_HOLD_NANOS: u128 = 150000000;
+
+// Plays in the terminal with two pixels per character, drawn as upper half blocks with both colors
+// Keys are read from raw standard input through stty, and Escape or Ctrl+C quits
+pub fn run(chip8: &mut Chip8, config: &Chip8Configuration) -> Option<Chip8Error> {
+    if config.rom_path.is_empty() { return Some("The tui frontend needs a rom path!".into()) }
+
+    let saved_mode = match stty(&["-g"]) {
+        Some(mode) => mode,
+        None => return Some("The tui frontend needs a terminal with stty!".into())
+    };
+    if stty(&["raw", "-echo"]).is_none() { return Some("Failed to put the terminal in raw mode!".into()) }
+
+    // Switches to the alternate screen and hides the cursor until the emulator quits
+    print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+    let result = run_terminal(chip8, config, read_keys());
+    print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+    let _ = std::io::stdout().flush();
+    stty(&[saved_mode.trim()]);
+    result
+}
+
+// Reads keys from the input thread and draws with ANSI escape codes
+struct TuiFrontend<'a> {
+    keys: Receiver<u8>,
+    key_map: &'a [(HostKey, usize)],
+    palette: &'a Palette,
+    held_keys: [Option<Instant>; 16],
+    is_beeping: bool,
+    is_quit: bool
+}
+
+fn run_terminal(chip8: &mut Chip8, config: &Chip8Configuration, keys: Receiver<u8>) -> Option<Chip8Error> {
+    let mut frontend = TuiFrontend{keys, key_map: &config.key_map, palette: &config.palette, held_keys: [None; 16],
+        is_beeping: false, is_quit: false};
+    let mut frame_time = Instant::now();
+
+    loop {
+        // Clears the screen for a new display size, whose rows are all presented as dirty
+        if chip8.take_display_mode_change().is_some() { print!("\x1b[2J") }
+
+        let now = Instant::now();
+        let delta = now.duration_since(frame_time).as_nanos() as f32;
+        frame_time = now;
+        // Skips failing instructions when asked, since the terminal has no debugger to pause in
+        if let Some(error) = chip8.run_frontend(&mut frontend, delta.min(FRAME_NANOS * 4.0))
+        && !(config.error_policy == ErrorPolicy::Skip && error.is_crash() && crash::skip_instruction(chip8, &error)) {
+            return Some(crash::report(chip8, error))
+        }
+        if frontend.is_quit || chip8.is_exited() { return None }
+        if std::io::stdout().flush().is_err() { return Some("Failed to write to the terminal!".into()) }
+
+        sleep(Duration::from_nanos(FRAME_NANOS as u64).saturating_sub(frame_time.elapsed()));
+    }
+}
+
+impl Frontend for TuiFrontend<'_> {
+    // Redraws the character rows covering drawn rows
+    fn present(&mut self, frame_buffer: &FrameBuffer) -> Result<(), Chip8Error> {
+        let FrameBuffer{pixels, width, height, ref dirty_rows} = *frame_buffer;
+        let color = |x: u32, y: u32| match y < height {
+            true => self.palette[pixels[(y * width + x) as usize] as usize & 0xF],
+            false => self.palette[0]
+        };
+
+        let mut text = String::new();
+        for line in dirty_rows.start / 2..dirty_rows.end.div_ceil(2) {
+            text += &format!("\x1b[{};1H", line + 1);
+            for x in 0..width {
+                let (top, bottom) = (color(x, line * 2), color(x, line * 2 + 1));
+                text += &format!("\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}", top >> 16 & 0xFF, top >> 8 & 0xFF, top & 0xFF,
+                    bottom >> 16 & 0xFF, bottom >> 8 & 0xFF, bottom & 0xFF);
+            }
+        }
+        if !text.is_empty() { print!("{text}\x1b[0m") }
+        Ok(())
+    }
+
+    // Rings the terminal bell when a beep starts
+    fn beep(&mut self, is_on: bool) {
+        if is_on && !self.is_beeping { print!("\x07") }
+        self.is_beeping = is_on;
+    }
+
+    // Presses chip8 keys bound to typed characters, and releases them once they stop repeating
+    fn poll_keys(&mut self) -> Keys {
+        while let Ok(byte) = self.keys.try_recv() {
+            if byte == 0x1B || byte == 0x03 { self.is_quit = true }
+            let host_key = typed_key(byte as char);
+            for &(mapped, key) in self.key_map {
+                if Some(mapped) == host_key { self.held_keys[key] = Some(Instant::now()); }
+            }
+        }
+
+        let mut keys = [false; 16];
+        for (key, held) in self.held_keys.iter_mut().enumerate() {
+            match held.is_some_and(|time| time.elapsed().as_nanos() <= KEY_HOLD_NANOS) {
+                true => keys[key] = true,
+                false => *held = None
+            }
+        }
+        keys
+    }
+}
+
+// Reads standard input on its own thread, since reads block until a key is typed
+fn read_keys() -> Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buffer = [0; 64];
+        while let Ok(length @ 1..) = std::io::stdin().read(&mut buffer) {
+            if buffer[..length].iter().any(|&byte| sender.send(byte).is_err()) { break }
+        }
+    });
+    receiver
+}
+
+// Runs stty on the terminal attached to standard input, returning its output
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output().ok()?;
+    match output.status.success() {
+        true => Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => None
+    }
+}
@@ -0,0 +1,89 @@
+use std::{io::Write, path::PathBuf, process::{Child, Command, Stdio}};
+use crate::{wav::{encode_wav, BeepTrack}, Oscillator};
+
+// Every display mode stretches onto this canvas before ffmpeg scales it up, like the window stretches the display
+const CANVAS_WIDTH: usize = 256;
+const CANVAS_HEIGHT: usize = 128;
+const FRAME_NANOS: f64 = 1000000000.0 / 60.0;
+
+// Pipes frames to ffmpeg at the machine's 60 hz, then muxes in the beeps rendered over the same frames
+pub struct VideoRecording {
+    ffmpeg: Child,
+    path: String,
+    video_path: PathBuf, // Silent video encoded while running
+    canvas: Vec<u32>,
+    start_frame: u64,
+    frames: u64 // Frames written since the start
+}
+
+impl VideoRecording {
+    // Starts encoding at the output size, where the machine frame marks the start of the audio
+    pub fn start(path: &str, output_size: (u32, u32), start_frame: u64) -> Result<VideoRecording, &'static str> {
+        let video_path = std::env::temp_dir().join(format!("chip8-video-{}.mkv", std::process::id()));
+        let (width, height) = (output_size.0.max(2) / 2 * 2, output_size.1.max(2) / 2 * 2); // Chroma subsampling needs even sizes
+        let ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "bgra", "-s", &format!("{CANVAS_WIDTH}x{CANVAS_HEIGHT}"),
+                "-r", "60", "-i", "-", "-vf", &format!("scale={width}:{height}:flags=neighbor"), "-c:v", "libx264", "-pix_fmt", "yuv420p"])
+            .arg(&video_path)
+            .stdin(Stdio::piped())
+            .spawn();
+        match ffmpeg {
+            Ok(ffmpeg) => {
+                println!("Started recording video");
+                Ok(VideoRecording{ffmpeg, path: path.to_string(), video_path, canvas: vec![0; CANVAS_WIDTH * CANVAS_HEIGHT], start_frame, frames: 0})
+            }
+            Err(_) => Err("Failed to start ffmpeg for the video recording!")
+        }
+    }
+
+    // Writes the displayed frame once for every machine frame since the last capture, so the video keeps to the emulated
+    // time through fast forward, slow motion, and pauses like the beeps do
+    pub fn capture(&mut self, pixels: &[u32], display_size: (u32, u32), frame_count: u64) -> Result<(), &'static str> {
+        let (width, height) = (display_size.0 as usize, display_size.1 as usize);
+        let frames = frame_count.saturating_sub(self.start_frame).saturating_sub(self.frames);
+        if frames == 0 || pixels.len() != width * height { return Ok(()) }
+
+        for (y, row) in self.canvas.chunks_mut(CANVAS_WIDTH).enumerate() {
+            let source_row = &pixels[y * height / CANVAS_HEIGHT * width..][..width];
+            for (x, pixel) in row.iter_mut().enumerate() { *pixel = source_row[x * width / CANVAS_WIDTH]; }
+        }
+        let bytes: Vec<u8> = self.canvas.iter().flat_map(|pixel| pixel.to_le_bytes()).collect();
+        let Some(stdin) = &mut self.ffmpeg.stdin else { return Err("ffmpeg's input is closed!") };
+        for _ in 0..frames {
+            if stdin.write_all(&bytes).is_err() { return Err("Failed to send a frame to ffmpeg!") }
+        }
+        self.frames += frames;
+        Ok(())
+    }
+
+    // Finishes the video, then muxes it with the beeps over the recorded frames into the output file
+    pub fn save(mut self, beep_track: &BeepTrack, oscillator: &mut Oscillator, volume: f32) -> Result<(), &'static str> {
+        drop(self.ffmpeg.stdin.take());
+        if !self.ffmpeg.wait().is_ok_and(|status| status.success()) {
+            return Err("ffmpeg failed to encode the video!")
+        }
+
+        let start_nanos = self.start_frame as f64 * FRAME_NANOS;
+        let end_nanos = (self.start_frame + self.frames) as f64 * FRAME_NANOS;
+        let audio_path = self.video_path.with_extension("wav");
+        let samples = beep_track.render(oscillator, volume, start_nanos, end_nanos);
+        if std::fs::write(&audio_path, encode_wav(&samples)).is_err() {
+            let _ = std::fs::remove_file(&self.video_path);
+            return Err("Failed to write the video's audio!")
+        }
+
+        let status = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-i"]).arg(&self.video_path).arg("-i").arg(&audio_path)
+            .args(["-c:v", "copy", "-c:a", "aac"]).arg(&self.path)
+            .status();
+        let _ = std::fs::remove_file(&self.video_path);
+        let _ = std::fs::remove_file(&audio_path);
+        match status {
+            Ok(status) if status.success() => {
+                println!("Saved the video to {}", self.path);
+                Ok(())
+            }
+            _ => Err("ffmpeg failed to mux the video and audio!")
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use crate::chip8::{Chip8, Chip8Options, Profile};
+
+// Exports plain functions for a web page, where javascript drives one machine through the module's memory
+// Browsers run the module on one thread, so the machine and rom live in thread locals instead of behind a lock
+thread_local! {
+    static CHIP8: RefCell<Option<Chip8>> = const { RefCell::new(None) };
+    static ROM: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+// Makes room for a rom, which javascript copies to the returned address before calling chip8_load
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_rom_buffer(length: usize) -> *mut u8 {
+    ROM.with_borrow_mut(|rom| {
+        rom.clear();
+        rom.resize(length, 0);
+        rom.as_mut_ptr()
+    })
+}
+
+// Starts the copied rom with profile 0 for chip8, 1 for schip, or 2 for xochip, and a seed such as from Math.random
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_load(profile: u32, seed: u32) -> bool {
+    let profile = match profile {
+        0 => Profile::Chip8,
+        1 => Profile::Schip,
+        2 => Profile::XoChip,
+        _ => return false
+    };
+    let options = Chip8Options::new(profile, seed as u64);
+
+    let chip8 = ROM.with_borrow(|rom| Chip8::init(rom, &options));
+    CHIP8.with_borrow_mut(|machine| {
+        *machine = chip8.ok();
+        machine.is_some()
+    })
+}
+
+// Emulates the nanoseconds since the last animation frame, and returns false once the machine stops
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_run(nanos: f32) -> bool {
+    CHIP8.with_borrow_mut(|machine| match machine {
+        Some(chip8) => chip8.run(nanos).is_none() && !chip8.is_exited(),
+        None => false
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_set_key(key: u32, is_pressed: bool) {
+    CHIP8.with_borrow_mut(|machine| {
+        if let Some(chip8) = machine && key < 16 {
+            chip8.set_key(key as usize, is_pressed);
+        }
+    })
+}
+
+// Points to one palette index per pixel, which stays valid until the next chip8_run or chip8_load
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_frame_buffer() -> *const u8 {
+    CHIP8.with_borrow(|machine| match machine {
+        Some(chip8) => chip8.front_buffer().as_ptr(),
+        None => std::ptr::null()
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_display_width() -> u32 {
+    CHIP8.with_borrow(|machine| machine.as_ref().map_or(0, |chip8| chip8.display_mode().size().0))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_display_height() -> u32 {
+    CHIP8.with_borrow(|machine| machine.as_ref().map_or(0, |chip8| chip8.display_mode().size().1))
+}
+
+// Web audio plays a tone while the sound timer runs, polled once per animation frame
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_is_beeping() -> bool {
+    CHIP8.with_borrow(|machine| machine.as_ref().is_some_and(|chip8| chip8.sound_timer() > 1))
+}
@@ -0,0 +1,33 @@
+use std::{fs, path::PathBuf, time::{Duration, Instant, SystemTime}};
+
+// Time between checks of the rom's modification time
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Polls the rom file so a new build from the assembler restarts emulation
+pub struct RomWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_poll: Instant
+}
+
+impl RomWatcher {
+    pub fn new(path: &str) -> RomWatcher {
+        let path = PathBuf::from(path);
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        RomWatcher{path, modified, last_poll: Instant::now()}
+    }
+
+    // Returns true once per change, waiting for the write to settle before reporting it
+    pub fn is_modified(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL { return false }
+        self.last_poll = Instant::now();
+
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok();
+        if modified.is_none() || modified == self.modified { return false }
+
+        // Skips files still being written, which are read on the next poll
+        let is_settled = modified.and_then(|time| time.elapsed().ok()).is_some_and(|age| age >= POLL_INTERVAL / 2);
+        if is_settled { self.modified = modified; }
+        is_settled
+    }
+}
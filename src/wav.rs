@@ -0,0 +1,72 @@
+use chip8_emulator::AudioEvent;
+use crate::Oscillator;
+
+const SAMPLE_RATE: u32 = 48000; // Matches the audio stream, so recordings sound like playback
+
+// Beeps on the machine's emulated timeline, which render offline into a wav file whose timing matches the sound timer
+// exactly, however the host's audio callback was scheduled
+#[derive(Default)]
+pub struct BeepTrack {
+    beeps: Vec<(f64, f64)> // Start and end in nanoseconds since the machine started
+}
+
+impl BeepTrack {
+    pub fn record(&mut self, event: AudioEvent) {
+        match event {
+            AudioEvent::Start{duration_nanos, time_nanos, ..} => {
+                self.stop(time_nanos);
+                self.beeps.push((time_nanos, time_nanos + duration_nanos as f64));
+            }
+            AudioEvent::Stop{time_nanos, ..} => self.stop(time_nanos)
+        }
+    }
+
+    // Cuts any playing beep short
+    fn stop(&mut self, time_nanos: f64) {
+        if let Some((start, end)) = self.beeps.last_mut() { *end = end.min(time_nanos).max(*start); }
+    }
+
+    // Renders the beeps between two times as samples, advancing the oscillator only while beeping like the audio callback
+    pub fn render(&self, oscillator: &mut Oscillator, volume: f32, start_nanos: f64, end_nanos: f64) -> Vec<i16> {
+        let to_sample = |nanos: f64| ((nanos - start_nanos).max(0.0) * SAMPLE_RATE as f64 / 1e9) as usize;
+        let mut samples = vec![0; to_sample(end_nanos)];
+        for &(start, end) in &self.beeps {
+            let end = to_sample(end).min(samples.len());
+            for sample in samples.get_mut(to_sample(start)..end).unwrap_or(&mut []) {
+                *sample = oscillator.next_sample(volume);
+            }
+        }
+        samples
+    }
+
+    // Writes the beeps until the end time as a wav file
+    pub fn write_wav(&self, path: &str, oscillator: &mut Oscillator, volume: f32, end_nanos: f64) -> Result<(), &'static str> {
+        match std::fs::write(path, encode_wav(&self.render(oscillator, volume, 0.0, end_nanos))) {
+            Ok(_) => {
+                println!("Saved the audio to {path}");
+                Ok(())
+            }
+            Err(_) => Err("Failed to write the audio file!")
+        }
+    }
+}
+
+// Encodes samples as a mono 16 bit wav file
+pub fn encode_wav(samples: &[i16]) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes()); // Format chunk size
+    bytes.extend_from_slice(&1u16.to_le_bytes());  // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes());  // Channels
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // Bytes a second
+    bytes.extend_from_slice(&2u16.to_le_bytes());  // Bytes a sample
+    bytes.extend_from_slice(&16u16.to_le_bytes()); // Bits a sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples { bytes.extend_from_slice(&sample.to_le_bytes()); }
+    bytes
+}
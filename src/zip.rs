@@ -0,0 +1,302 @@
+use std::io::{BufRead, Write};
+
+// File extensions of chip8 roms inside of archives
+const ROM_EXTENSIONS: [&str; 4] = [".ch8", ".c8", ".sc8", ".xo8"];
+
+// Extracts a rom from a zip archive, asking on the console which rom to load when there's more than one
+pub fn extract_rom(archive: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut roms: Vec<Entry> = read_entries(archive)?.into_iter().filter(|entry| {
+        let name = entry.name.to_ascii_lowercase();
+        ROM_EXTENSIONS.iter().any(|extension| name.ends_with(extension))
+    }).collect();
+
+    let entry = match roms.len() {
+        0 => return Err("The zip has no chip8 roms!"),
+        1 => roms.remove(0),
+        _ => {
+            for (i, entry) in roms.iter().enumerate() {
+                println!("{}) {}", i + 1, entry.name);
+            }
+            print!("Choose a rom: ");
+            let _ = std::io::stdout().flush();
+
+            let mut choice = String::new();
+            if std::io::stdin().lock().read_line(&mut choice).is_err() {
+                return Err("Failed to read the chosen rom!")
+            }
+            match choice.trim().parse::<usize>() {
+                Ok(number) if (1..=roms.len()).contains(&number) => roms.remove(number - 1),
+                _ => return Err("Invalid rom choice!")
+            }
+        }
+    };
+
+    let data = match archive.get(entry.data_offset..entry.data_offset + entry.compressed_size) {
+        Some(data) => data,
+        None => return Err("The zip is truncated!")
+    };
+    let rom = match entry.method {
+        0 => data.to_vec(),
+        8 => match inflate(data) {
+            Some(rom) => rom,
+            None => return Err("The zip has corrupted data!")
+        },
+        _ => return Err("The zip uses an unsupported compression method!")
+    };
+
+    match rom.len() == entry.uncompressed_size {
+        true => Ok(rom),
+        false => Err("The zip has corrupted data!")
+    }
+}
+
+struct Entry {
+    name: String,
+    method: u16,
+    compressed_size: usize,
+    uncompressed_size: usize,
+    data_offset: usize
+}
+
+// Reads file entries from the central directory at the end of the archive
+fn read_entries(archive: &[u8]) -> Result<Vec<Entry>, &'static str> {
+    let end = match (0..archive.len().saturating_sub(21)).rev().find(|&i| read_u32(archive, i) == Some(0x06054B50)) {
+        Some(end) => end,
+        None => return Err("Not a valid zip file!")
+    };
+
+    let entry_count = read_u16(archive, end + 10).unwrap_or(0);
+    let mut offset = read_u32(archive, end + 16).unwrap_or(0) as usize;
+    let mut entries = Vec::new();
+    for _ in 0..entry_count {
+        if read_u32(archive, offset) != Some(0x02014B50) { return Err("The zip has a corrupted directory!") }
+        let field = |position: usize| read_u16(archive, offset + position).unwrap_or(0) as usize;
+        let (name_length, extra_length, comment_length) = (field(28), field(30), field(32));
+
+        let entry = (|| {
+            let name = String::from_utf8_lossy(archive.get(offset + 46..offset + 46 + name_length)?).into_owned();
+            let local_offset = read_u32(archive, offset + 42)? as usize;
+            let local_data = local_offset + 30 + read_u16(archive, local_offset + 26)? as usize
+                + read_u16(archive, local_offset + 28)? as usize;
+            Some(Entry{name, method: read_u16(archive, offset + 10)?, compressed_size: read_u32(archive, offset + 20)? as usize,
+                uncompressed_size: read_u32(archive, offset + 24)? as usize, data_offset: local_data})
+        })();
+
+        match entry {
+            Some(entry) => entries.push(entry),
+            None => return Err("The zip has a corrupted directory!")
+        }
+        offset += 46 + name_length + extra_length + comment_length;
+    }
+    Ok(entries)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+// Base lengths and distances of deflate's length and distance codes, and their extra bits
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025,
+    1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11,
+    12, 12, 13, 13];
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+// Reads bits from the least significant bit of each byte first
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u8) -> Option<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.position / 8)?;
+            value |= ((byte >> (self.position % 8)) as u32 & 1) << i;
+            self.position += 1;
+        }
+        Some(value)
+    }
+}
+
+// Canonical huffman code as the number of codes of each length and the symbols in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; 16];
+        for &length in lengths { counts[length as usize] += 1; }
+        counts[0] = 0;
+
+        let mut offsets = [0; 16];
+        for length in 1..15 { offsets[length + 1] = offsets[length] + counts[length]; }
+
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Huffman{counts, symbols}
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Option<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return self.symbols.get((index + code - first) as usize).copied()
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+// Decompresses raw deflate data
+fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = BitReader{data, position: 0};
+    let mut output = Vec::new();
+
+    loop {
+        let is_last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            // Stored blocks start at the next byte with their length and its complement
+            0 => {
+                let start = reader.position.div_ceil(8);
+                let length = u16::from_le_bytes(data.get(start..start + 2)?.try_into().ok()?) as usize;
+                output.extend_from_slice(data.get(start + 4..start + 4 + length)?);
+                reader.position = (start + 4 + length) * 8;
+            }
+
+            // Fixed codes
+            1 => {
+                let mut lengths = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                inflate_block(&mut reader, &mut output, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+
+            // Dynamic codes, whose lengths are themselves huffman coded
+            2 => {
+                let literal_count = reader.bits(5)? as usize + 257;
+                let distance_count = reader.bits(5)? as usize + 1;
+                let code_length_count = reader.bits(4)? as usize + 4;
+
+                let mut code_length_lengths = [0; 19];
+                for &i in &CODE_LENGTH_ORDER[..code_length_count] {
+                    code_length_lengths[i] = reader.bits(3)? as u8;
+                }
+                let code_length_code = Huffman::new(&code_length_lengths);
+
+                let mut lengths = Vec::with_capacity(literal_count + distance_count);
+                while lengths.len() < literal_count + distance_count {
+                    // Symbols past 15 repeat the previous length or zeros
+                    let (length, count) = match code_length_code.decode(&mut reader)? {
+                        symbol @ 0..16 => (symbol as u8, 1),
+                        16 => (*lengths.last()?, 3 + reader.bits(2)?),
+                        17 => (0, 3 + reader.bits(3)?),
+                        _ => (0, 11 + reader.bits(7)?)
+                    };
+                    lengths.resize(lengths.len() + count as usize, length);
+                }
+                if lengths.len() > literal_count + distance_count { return None }
+
+                let literal_code = Huffman::new(&lengths[..literal_count]);
+                let distance_code = Huffman::new(&lengths[literal_count..]);
+                inflate_block(&mut reader, &mut output, &literal_code, &distance_code)?;
+            }
+            _ => return None
+        }
+
+        if is_last { return Some(output) }
+    }
+}
+
+// Decodes literals and back references until the end of block symbol
+fn inflate_block(reader: &mut BitReader, output: &mut Vec<u8>, literal_code: &Huffman, distance_code: &Huffman) -> Option<()> {
+    loop {
+        match literal_code.decode(reader)? {
+            literal @ 0..256 => output.push(literal as u8),
+            256 => return Some(()),
+            symbol => {
+                let i = symbol as usize - 257;
+                let length = *LENGTH_BASES.get(i)? as usize + reader.bits(*LENGTH_EXTRA_BITS.get(i)?)? as usize;
+                let i = distance_code.decode(reader)? as usize;
+                let distance = *DISTANCE_BASES.get(i)? as usize + reader.bits(*DISTANCE_EXTRA_BITS.get(i)?)? as usize;
+
+                // Copies byte by byte because the source may overlap the copied bytes
+                let start = output.len().checked_sub(distance)?;
+                for j in 0..length { output.push(output[start + j]); }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &[u8] = b"chip8 chip8 chip8 chip8 emulator";
+
+    #[test]
+    fn inflates_stored_block() {
+        let mut data = vec![0x01, 0x20, 0x00, 0xDF, 0xFF];
+        data.extend_from_slice(TEXT);
+        assert_eq!(inflate(&data).as_deref(), Some(TEXT));
+    }
+
+    #[test]
+    fn inflates_fixed_huffman_block() {
+        let data = [0x4B, 0xCE, 0xC8, 0x2C, 0xB0, 0x50, 0x48, 0xC6, 0x20, 0x53, 0x73, 0x4B, 0x73, 0x12, 0x4B, 0xF2, 0x8B, 0x00];
+        assert_eq!(inflate(&data).as_deref(), Some(TEXT));
+    }
+
+    #[test]
+    fn inflates_dynamic_huffman_block() {
+        let data = [0x0D, 0x86, 0x41, 0x01, 0x00, 0x00, 0x0C, 0x44, 0xB2, 0x72, 0xEB, 0x9F, 0x61, 0x78, 0x20, 0x32, 0xA7, 0x57, 0x53,
+            0x8E, 0x18, 0xDD, 0x03];
+        assert_eq!(inflate(&data).as_deref(), Some(&b"babacbcbbdbacacabadaaaacaaba"[..]));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        assert_eq!(inflate(&[0x4B, 0xCE, 0xC8, 0x2C]), None);
+    }
+
+    // A zip with a text file and a deflated rom, where only the rom is extracted
+    #[test]
+    fn extracts_only_rom() {
+        let archive = [
+            0x50, 0x4B, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0xAB, 0x75, 0x4F, 0x5D, 0xAC, 0x2A, 0x93, 0xD8, 0x04, 0x00,
+            0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x72, 0x65, 0x61, 0x64, 0x6D, 0x65, 0x2E, 0x74, 0x78, 0x74,
+            0xCB, 0xC8, 0x04, 0x00, 0x50, 0x4B, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0xAB, 0x75, 0x4F, 0x5D, 0xE9, 0x91,
+            0x13, 0xE3, 0x08, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x67, 0x61, 0x6D, 0x65, 0x2E, 0x63,
+            0x68, 0x38, 0x63, 0x78, 0x20, 0xC4, 0xC0, 0x00, 0xC5, 0x00, 0x50, 0x4B, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00,
+            0x08, 0x00, 0xAB, 0x75, 0x4F, 0x5D, 0xAC, 0x2A, 0x93, 0xD8, 0x04, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x0A, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00, 0x00, 0x00, 0x00, 0x72, 0x65, 0x61, 0x64,
+            0x6D, 0x65, 0x2E, 0x74, 0x78, 0x74, 0x50, 0x4B, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0xAB, 0x75,
+            0x4F, 0x5D, 0xE9, 0x91, 0x13, 0xE3, 0x08, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x2C, 0x00, 0x00, 0x00, 0x67, 0x61, 0x6D, 0x65, 0x2E, 0x63, 0x68, 0x38,
+            0x50, 0x4B, 0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x6E, 0x00, 0x00, 0x00, 0x5A, 0x00, 0x00, 0x00,
+            0x00, 0x00
+        ];
+        assert_eq!(extract_rom(&archive), Ok([0x00, 0xE0, 0x12, 0x00].repeat(3)));
+        assert!(extract_rom(&archive[..100]).is_err());
+    }
+}